@@ -17,6 +17,9 @@ LICENSE END */
 #include <vector>
 #include <memory>
 #include <algorithm>
+#include <limits>
+#include <functional>
+#include <utility>
 #include <iostream> // FIXME: remove: iostream always bring it lots of code so we should not have it in this header
 
 namespace sixtyfps::cbindgen_private {
@@ -40,6 +43,7 @@ extern const cbindgen_private::ItemVTable ImageVTable;
 extern const cbindgen_private::ItemVTable PathVTable;
 extern const cbindgen_private::ItemVTable FlickableVTable;
 extern const cbindgen_private::ItemVTable WindowVTable;
+extern const cbindgen_private::ItemVTable StackPageVTable;
 extern const cbindgen_private::ItemVTable TextInputVTable;
 
 extern const cbindgen_private::ItemVTable NativeButtonVTable;
@@ -62,13 +66,26 @@ using ComponentRef = vtable::VRef<private_api::ComponentVTable>;
 using ItemRef = vtable::VRef<private_api::ItemVTable>;
 using ItemVisitorRefMut = vtable::VRefMut<cbindgen_private::ItemVisitorVTable>;
 }
+using cbindgen_private::BorderStyle;
 using cbindgen_private::EasingCurve;
+using cbindgen_private::ImageFit;
+using cbindgen_private::InputType;
+using cbindgen_private::LayoutDirection;
 using cbindgen_private::PropertyAnimation;
 using cbindgen_private::Slice;
 using cbindgen_private::TextHorizontalAlignment;
+using cbindgen_private::TextOverflow;
 using cbindgen_private::TextVerticalAlignment;
+using cbindgen_private::TextWrap;
 using cbindgen_private::TraversalOrder;
 
+/// Returns the direction of the language of the given locale, such as "he_IL.UTF-8" or "ar-EG",
+/// to pass to ComponentWindow::set_layout_direction().
+inline LayoutDirection layout_direction_from_locale(const SharedString &locale)
+{
+    return cbindgen_private::sixtyfps_layout_direction_from_locale(&locale);
+}
+
 namespace private_api {
 using ItemTreeNode = cbindgen_private::ItemTreeNode<uint8_t>;
 
@@ -93,6 +110,15 @@ public:
         sixtyfps_component_window_set_scale_factor(&inner, value);
     }
 
+    LayoutDirection layout_direction() const
+    {
+        return sixtyfps_component_window_get_layout_direction(&inner);
+    }
+    void set_layout_direction(LayoutDirection direction) const
+    {
+        sixtyfps_component_window_set_layout_direction(&inner, direction);
+    }
+
     template<typename Component>
     void free_graphics_resources(Component *c) const
     {
@@ -130,6 +156,7 @@ using cbindgen_private::Flickable;
 using cbindgen_private::Image;
 using cbindgen_private::Path;
 using cbindgen_private::Rectangle;
+using cbindgen_private::StackPage;
 using cbindgen_private::Text;
 using cbindgen_private::TextInput;
 using cbindgen_private::TouchArea;
@@ -346,28 +373,54 @@ public:
 using cbindgen_private::box_layout_info;
 using cbindgen_private::BoxLayoutCellData;
 using cbindgen_private::BoxLayoutData;
+using cbindgen_private::flow_layout_info;
+using cbindgen_private::FlowLayoutData;
 using cbindgen_private::grid_layout_info;
 using cbindgen_private::GridLayoutCellData;
 using cbindgen_private::GridLayoutData;
 using cbindgen_private::LayoutAlignment;
 using cbindgen_private::LayoutInfo;
+using cbindgen_private::LayoutPass;
 using cbindgen_private::Padding;
 using cbindgen_private::PathLayoutData;
 using cbindgen_private::PathLayoutItemData;
 using cbindgen_private::Rect;
 using cbindgen_private::solve_box_layout;
+using cbindgen_private::solve_flow_layout;
 using cbindgen_private::solve_grid_layout;
 using cbindgen_private::solve_path_layout;
+using cbindgen_private::solve_stack_layout;
+using cbindgen_private::stack_layout_info;
+using cbindgen_private::StackLayoutData;
 
 inline LayoutInfo LayoutInfo::merge(const LayoutInfo &other) const
 {
     // Note: This "logic" is duplicated from LayoutInfo::merge in layout.rs.
-    return LayoutInfo { std::max(min_width, other.min_width),
-                        std::min(max_width, other.max_width),
-                        std::max(min_height, other.min_height),
-                        std::min(max_height, other.max_height),
-                        std::min(horizontal_stretch, other.horizontal_stretch),
-                        std::min(vertical_stretch, other.vertical_stretch) };
+    LayoutInfo info { std::max(min_width, other.min_width),
+                      std::min(max_width, other.max_width),
+                      std::max(min_height, other.min_height),
+                      std::min(max_height, other.max_height),
+                      std::max(preferred_width, other.preferred_width),
+                      std::max(preferred_height, other.preferred_height),
+                      std::min(horizontal_stretch, other.horizontal_stretch),
+                      std::min(vertical_stretch, other.vertical_stretch),
+                      aspect_ratio > 0 ? aspect_ratio : other.aspect_ratio };
+    // Same as LayoutInfo::fit_aspect_ratio in layout.rs
+    float ratio = info.aspect_ratio;
+    if (ratio > 0) {
+        constexpr float max = std::numeric_limits<float>::max();
+        info.min_width = std::max(info.min_width, info.min_height * ratio);
+        info.min_height = std::max(info.min_height, info.min_width / ratio);
+        if (info.max_height < max)
+            info.max_width = std::min(info.max_width, info.max_height * ratio);
+        if (info.max_width < max)
+            info.max_height = std::min(info.max_height, info.max_width / ratio);
+        if (info.preferred_width == 0)
+            info.preferred_width = info.preferred_height * ratio;
+        else
+            info.preferred_height = info.preferred_width / ratio;
+    }
+    return info;
 }
 
 // models
@@ -377,6 +430,8 @@ struct AbstractRepeaterView
     virtual void row_added(int index, int count) = 0;
     virtual void row_removed(int index, int count) = 0;
     virtual void row_changed(int index) = 0;
+    virtual void row_moved(int from, int to, int count) = 0;
+    virtual void reset() = 0;
 };
 using ModelPeer = std::weak_ptr<AbstractRepeaterView>;
 
@@ -419,6 +474,17 @@ protected:
     {
         for_each_peers([=](auto peer) { peer->row_removed(index, count); });
     }
+    /// Notify the views that `count` rows starting at `from` were moved, so that they now start
+    /// at the row `to`
+    void row_moved(int from, int to, int count)
+    {
+        for_each_peers([=](auto peer) { peer->row_moved(from, to, count); });
+    }
+    /// Notify the views that the whole content of the model changed, including the amount of rows
+    void reset()
+    {
+        for_each_peers([=](auto peer) { peer->reset(); });
+    }
 
 private:
     template<typename F>
@@ -496,6 +562,335 @@ public:
         data.erase(data.begin() + index);
         this->row_removed(index, 1);
     }
+
+    /// Replace all the rows of the model
+    void set_vector(std::vector<ModelData> array)
+    {
+        data = std::move(array);
+        this->reset();
+    }
+};
+
+namespace private_api {
+/// Forwards the notifications of the source model of a model adapter to the adapter
+template<typename Adapter>
+struct AdapterSourcePeer : AbstractRepeaterView
+{
+    Adapter *adapter;
+    AdapterSourcePeer(Adapter *adapter) : adapter(adapter) { }
+    void row_added(int index, int count) override { adapter->source_row_added(index, count); }
+    void row_removed(int index, int count) override { adapter->source_row_removed(index, count); }
+    void row_changed(int index) override { adapter->source_row_changed(index); }
+    void row_moved(int from, int to, int count) override
+    {
+        adapter->source_row_moved(from, to, count);
+    }
+    void reset() override { adapter->source_reset(); }
+};
+
+/// The new index of `row` after `count` rows starting at `from` were moved to start at `to`
+inline int moved_row(int row, int from, int to, int count)
+{
+    if (row >= from && row < from + count)
+        return to + (row - from);
+    if (row >= from + count)
+        row -= count;
+    return row >= to ? row + count : row;
+}
+} // namespace private_api
+
+/// A Model that maps each row of another model with a function.
+/// The rows cannot be changed through this model.
+template<typename SourceData, typename MappedData>
+class MapModel : public Model<MappedData>
+{
+    friend struct private_api::AdapterSourcePeer<MapModel>;
+
+    std::shared_ptr<Model<SourceData>> source;
+    std::function<MappedData(const SourceData &)> map;
+    std::shared_ptr<private_api::AdapterSourcePeer<MapModel>> peer =
+            std::make_shared<private_api::AdapterSourcePeer<MapModel>>(this);
+
+    void source_row_added(int index, int count) { this->row_added(index, count); }
+    void source_row_removed(int index, int count) { this->row_removed(index, count); }
+    void source_row_changed(int row) { this->row_changed(row); }
+    void source_row_moved(int from, int to, int count) { this->row_moved(from, to, count); }
+    void source_reset() { this->reset(); }
+
+public:
+    MapModel(std::shared_ptr<Model<SourceData>> source,
+             std::function<MappedData(const SourceData &)> map)
+        : source(std::move(source)), map(std::move(map))
+    {
+        this->source->attach_peer(peer);
+    }
+    int row_count() const override { return source->row_count(); }
+    MappedData row_data(int i) const override { return map(source->row_data(i)); }
+};
+
+/// A Model that contains the rows of another model for which a filter function returns true.
+/// The changes of the source model are forwarded, and rows are added or removed when the result
+/// of the filter for a changed row is different.
+template<typename ModelData>
+class FilterModel : public Model<ModelData>
+{
+    friend struct private_api::AdapterSourcePeer<FilterModel>;
+
+    std::shared_ptr<Model<ModelData>> source;
+    std::function<bool(const ModelData &)> filter;
+    /// The row in the source model of each row of this model, in increasing order
+    std::vector<int> mapping;
+    std::shared_ptr<private_api::AdapterSourcePeer<FilterModel>> peer =
+            std::make_shared<private_api::AdapterSourcePeer<FilterModel>>(this);
+
+    bool accepts(int source_row) const { return filter(source->row_data(source_row)); }
+
+    /// The rows of the source model which are accepted by the filter
+    std::vector<int> filtered_rows() const
+    {
+        std::vector<int> rows;
+        for (int source_row = 0; source_row < source->row_count(); ++source_row) {
+            if (accepts(source_row))
+                rows.push_back(source_row);
+        }
+        return rows;
+    }
+
+    /// The row of this model which is the given source row, or the one after it
+    int row_for_source_row(int source_row) const
+    {
+        return std::lower_bound(mapping.begin(), mapping.end(), source_row) - mapping.begin();
+    }
+
+    void source_row_changed(int source_row)
+    {
+        int row = row_for_source_row(source_row);
+        bool was_accepted = row < int(mapping.size()) && mapping[row] == source_row;
+        bool is_accepted = accepts(source_row);
+        if (was_accepted && is_accepted) {
+            this->row_changed(row);
+        } else if (was_accepted) {
+            mapping.erase(mapping.begin() + row);
+            this->row_removed(row, 1);
+        } else if (is_accepted) {
+            mapping.insert(mapping.begin() + row, source_row);
+            this->row_added(row, 1);
+        }
+    }
+
+    void source_row_added(int index, int count)
+    {
+        int row = row_for_source_row(index);
+        std::for_each(mapping.begin() + row, mapping.end(), [=](int &r) { r += count; });
+        std::vector<int> added;
+        for (int source_row = index; source_row < index + count; ++source_row) {
+            if (accepts(source_row))
+                added.push_back(source_row);
+        }
+        mapping.insert(mapping.begin() + row, added.begin(), added.end());
+        if (!added.empty())
+            this->row_added(row, added.size());
+    }
+
+    void source_row_removed(int index, int count)
+    {
+        int start = row_for_source_row(index);
+        int end = row_for_source_row(index + count);
+        mapping.erase(mapping.begin() + start, mapping.begin() + end);
+        std::for_each(mapping.begin() + start, mapping.end(), [=](int &r) { r -= count; });
+        if (end > start)
+            this->row_removed(start, end - start);
+    }
+
+    void source_row_moved(int from, int to, int count)
+    {
+        int start = row_for_source_row(from);
+        int end = row_for_source_row(from + count);
+        for (int &r : mapping)
+            r = private_api::moved_row(r, from, to, count);
+        // The moved rows that are accepted by the filter stay next to each other
+        std::sort(mapping.begin(), mapping.end());
+        if (end > start) {
+            int new_start = row_for_source_row(private_api::moved_row(from, from, to, count));
+            if (new_start != start)
+                this->row_moved(start, new_start, end - start);
+        }
+    }
+
+    void source_reset()
+    {
+        mapping = filtered_rows();
+        this->reset();
+    }
+
+public:
+    FilterModel(std::shared_ptr<Model<ModelData>> source,
+                std::function<bool(const ModelData &)> filter)
+        : source(std::move(source)), filter(std::move(filter))
+    {
+        mapping = filtered_rows();
+        this->source->attach_peer(peer);
+    }
+
+    /// Change the filter function. Only the rows for which the result changed are added or
+    /// removed.
+    void set_filter(std::function<bool(const ModelData &)> new_filter)
+    {
+        filter = std::move(new_filter);
+        int row = 0;
+        for (int source_row = 0; source_row < source->row_count(); ++source_row) {
+            bool was_accepted = row < int(mapping.size()) && mapping[row] == source_row;
+            bool is_accepted = accepts(source_row);
+            if (was_accepted && is_accepted) {
+                row++;
+            } else if (was_accepted) {
+                mapping.erase(mapping.begin() + row);
+                this->row_removed(row, 1);
+            } else if (is_accepted) {
+                mapping.insert(mapping.begin() + row, source_row);
+                this->row_added(row, 1);
+                row++;
+            }
+        }
+    }
+
+    int row_count() const override { return mapping.size(); }
+    ModelData row_data(int i) const override { return source->row_data(mapping[i]); }
+    void set_row_data(int i, const ModelData &value) override
+    {
+        source->set_row_data(mapping[i], value);
+    }
+};
+
+/// A Model that contains the rows of another model, sorted with a "less than" comparison
+/// function. The changes of the source model are forwarded: added rows are inserted at their
+/// sorted position, and changed rows are moved if their position changed.
+template<typename ModelData>
+class SortModel : public Model<ModelData>
+{
+    friend struct private_api::AdapterSourcePeer<SortModel>;
+
+    std::shared_ptr<Model<ModelData>> source;
+    std::function<bool(const ModelData &, const ModelData &)> less;
+    /// The row in the source model of each row of this model
+    std::vector<int> mapping;
+    std::shared_ptr<private_api::AdapterSourcePeer<SortModel>> peer =
+            std::make_shared<private_api::AdapterSourcePeer<SortModel>>(this);
+
+    /// All the rows of the source, sorted. The rows that compare equal stay in the source order.
+    std::vector<int> sorted_rows() const
+    {
+        std::vector<ModelData> data;
+        std::vector<int> rows;
+        for (int source_row = 0; source_row < source->row_count(); ++source_row) {
+            data.push_back(source->row_data(source_row));
+            rows.push_back(source_row);
+        }
+        std::stable_sort(rows.begin(), rows.end(),
+                         [&](int a, int b) { return less(data[a], data[b]); });
+        return rows;
+    }
+
+    /// The row at which the given source row must be inserted to keep this model sorted
+    int insertion_row(int source_row) const
+    {
+        auto data = source->row_data(source_row);
+        auto it = std::upper_bound(mapping.begin(), mapping.end(), source_row, [&](int, int r) {
+            auto other = source->row_data(r);
+            return less(data, other) || (!less(other, data) && source_row < r);
+        });
+        return it - mapping.begin();
+    }
+
+    void source_row_changed(int source_row)
+    {
+        auto it = std::find(mapping.begin(), mapping.end(), source_row);
+        if (it == mapping.end())
+            return;
+        int row = it - mapping.begin();
+        mapping.erase(it);
+        int new_row = insertion_row(source_row);
+        mapping.insert(mapping.begin() + new_row, source_row);
+        if (new_row != row)
+            this->row_moved(row, new_row, 1);
+        this->row_changed(new_row);
+    }
+
+    void source_row_added(int index, int count)
+    {
+        for (int &r : mapping) {
+            if (r >= index)
+                r += count;
+        }
+        for (int source_row = index; source_row < index + count; ++source_row) {
+            int row = insertion_row(source_row);
+            mapping.insert(mapping.begin() + row, source_row);
+            this->row_added(row, 1);
+        }
+    }
+
+    void source_row_removed(int index, int count)
+    {
+        std::vector<int> removed_rows;
+        for (int row = 0; row < int(mapping.size()); ++row) {
+            if (mapping[row] >= index + count)
+                mapping[row] -= count;
+            else if (mapping[row] >= index)
+                removed_rows.push_back(row);
+        }
+        // Remove from the end so that the rows of the next ones are still valid
+        for (auto it = removed_rows.rbegin(); it != removed_rows.rend(); ++it) {
+            mapping.erase(mapping.begin() + *it);
+            this->row_removed(*it, 1);
+        }
+    }
+
+    void source_row_moved(int from, int to, int count)
+    {
+        for (int &r : mapping)
+            r = private_api::moved_row(r, from, to, count);
+        // The order of the rows that compare equal follows the source order
+        sort();
+    }
+
+    void source_reset()
+    {
+        mapping = sorted_rows();
+        this->reset();
+    }
+
+    /// Sort the rows again, and notify a reset if their order changed
+    void sort()
+    {
+        auto sorted = sorted_rows();
+        if (sorted != mapping) {
+            mapping = std::move(sorted);
+            this->reset();
+        }
+    }
+
+public:
+    SortModel(std::shared_ptr<Model<ModelData>> source,
+              std::function<bool(const ModelData &, const ModelData &)> less)
+        : source(std::move(source)), less(std::move(less))
+    {
+        mapping = sorted_rows();
+        this->source->attach_peer(peer);
+    }
+
+    /// Change the comparison function. The model is reset if the order of the rows changed.
+    void set_comparator(std::function<bool(const ModelData &, const ModelData &)> new_less)
+    {
+        less = std::move(new_less);
+        sort();
+    }
+
+    int row_count() const override { return mapping.size(); }
+    ModelData row_data(int i) const override { return source->row_data(mapping[i]); }
+    void set_row_data(int i, const ModelData &value) override
+    {
+        source->set_row_data(mapping[i], value);
+    }
 };
 
 template<typename C, typename ModelData>
@@ -534,6 +929,28 @@ class Repeater
                 data[i].state = State::Dirty;
             }
         }
+        void row_moved(int from, int to, int count) override
+        {
+            is_dirty = true;
+            if (std::max(from, to) + count > int(data.size()))
+                return reset();
+            // Keep the existing component instances, at their new row. Only their index and data
+            // need to be updated.
+            if (from < to)
+                std::rotate(data.begin() + from, data.begin() + from + count,
+                            data.begin() + to + count);
+            else
+                std::rotate(data.begin() + to, data.begin() + from, data.begin() + from + count);
+            for (int i = std::min(from, to); i < std::max(from, to) + count; ++i) {
+                data[i].state = State::Dirty;
+            }
+        }
+        void reset() override
+        {
+            is_dirty = true;
+            // The whole content changed: the components are instantiated again
+            data.clear();
+        }
     };
 
 public:
@@ -655,6 +1072,24 @@ Flickable::~Flickable()
     sixtyfps_flickable_data_free(&data);
 }
 
+Image::Image()
+{
+    sixtyfps_animated_image_data_init(&data);
+}
+Image::~Image()
+{
+    sixtyfps_animated_image_data_free(&data);
+}
+
+TextInput::TextInput()
+{
+    sixtyfps_text_input_data_init(&data);
+}
+TextInput::~TextInput()
+{
+    sixtyfps_text_input_data_free(&data);
+}
+
 NativeStyleMetrics::NativeStyleMetrics()
 {
     sixtyfps_init_native_style_metrics(this);
@@ -674,6 +1109,22 @@ bool operator!=(const StandardListViewItem &a, const StandardListViewItem &b)
 }
 }
 
+/// Registers the fonts of the TrueType or OpenType font file (or collection) at the given path,
+/// so that their family can be used in the `font_family` property of the `Text` and `TextInput`
+/// elements. Returns false if the file could not be loaded.
+inline bool register_application_font_from_path(const SharedString &path)
+{
+    return cbindgen_private::sixtyfps_register_application_font_from_path(&path);
+}
+
+/// Same as register_application_font_from_path(), for font file data in memory. The data must
+/// stay valid until the end of the program.
+inline bool register_application_font_from_data(const uint8_t *data, std::size_t len)
+{
+    return cbindgen_private::sixtyfps_register_application_font_from_data(
+            { const_cast<uint8_t *>(data), len });
+}
+
 namespace private_api {
 template<int Major, int Minor, int Patch>
 struct VersionCheckHelper
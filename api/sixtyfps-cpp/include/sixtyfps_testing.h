@@ -31,6 +31,25 @@ inline void send_keyboard_string_sequence(const Component &component,
     cbindgen_private::send_keyboard_string_sequence(&str, &component.window);
 }
 
+template<typename Component>
+inline void send_preedit(const Component &component, const sixtyfps::SharedString &text,
+                         int cursor_position)
+{
+    cbindgen_private::sixtyfps_send_preedit(&text, cursor_position, &component.window);
+}
+
+template<typename Component>
+inline void commit_preedit(const Component &component, const sixtyfps::SharedString &text)
+{
+    cbindgen_private::sixtyfps_commit_preedit(&text, &component.window);
+}
+
+template<typename Component>
+inline void use_in_process_clipboard(const Component &component)
+{
+    cbindgen_private::sixtyfps_use_in_process_clipboard(&component.window);
+}
+
 #define assert_eq(A, B)                                                                            \
     sixtyfps::testing::private_api::assert_eq_impl(A, B, #A, #B, __FILE__, __LINE__)
 
@@ -110,6 +110,19 @@ interface ModelPeer {
      * @param count
      */
     rowRemoved(row: number, count: number): void;
+    /**
+     * Call this function from your own model to notify that one or multiple
+     * rows were moved, so that the rows starting at `from` now start at `to`.
+     * @param from
+     * @param to
+     * @param count
+     */
+    rowMoved(from: number, to: number, count: number): void;
+    /**
+     * Call this function from your own model to notify that the whole content
+     * of the model changed, including the number of rows.
+     */
+    reset(): void;
 }
 
 /**
@@ -150,6 +163,8 @@ class NullPeer implements ModelPeer {
     rowDataChanged(row: number): void { }
     rowAdded(row: number, count: number): void { }
     rowRemoved(row: number, count: number): void { }
+    rowMoved(from: number, to: number, count: number): void { }
+    reset(): void { }
 }
 
 /**
@@ -220,8 +235,515 @@ class ArrayModel<T> implements Model<T> {
     }
 }
 
+/**
+ * Implemented by the model adapters to be notified of the changes in their source model
+ * @hidden
+ */
+interface SourceModelListener {
+    sourceRowDataChanged(row: number): void;
+    sourceRowAdded(row: number, count: number): void;
+    sourceRowRemoved(row: number, count: number): void;
+    sourceRowMoved(from: number, to: number, count: number): void;
+    sourceReset(): void;
+}
+
+/**
+ * The peer that a model adapter sets on its source model
+ * @hidden
+ */
+class SourceModelPeer implements ModelPeer {
+    private listener: SourceModelListener;
+
+    constructor(listener: SourceModelListener) {
+        this.listener = listener;
+    }
+
+    rowDataChanged(row: number): void { this.listener.sourceRowDataChanged(row); }
+    rowAdded(row: number, count: number): void { this.listener.sourceRowAdded(row, count); }
+    rowRemoved(row: number, count: number): void { this.listener.sourceRowRemoved(row, count); }
+    rowMoved(from: number, to: number, count: number): void { this.listener.sourceRowMoved(from, to, count); }
+    reset(): void { this.listener.sourceReset(); }
+}
+
+/**
+ * The `notify` member of a model that is the source of model adapters: it forwards the
+ * changes to the peer set by the run-time, and to each of the adapters.
+ * @hidden
+ */
+class ModelPeerList implements ModelPeer {
+    view: ModelPeer;
+    listeners: ModelPeer[];
+
+    constructor(view: ModelPeer) {
+        this.view = view;
+        this.listeners = [];
+    }
+
+    rowDataChanged(row: number): void {
+        this.view.rowDataChanged(row);
+        this.listeners.forEach(peer => peer.rowDataChanged(row));
+    }
+    rowAdded(row: number, count: number): void {
+        this.view.rowAdded(row, count);
+        this.listeners.forEach(peer => peer.rowAdded(row, count));
+    }
+    rowRemoved(row: number, count: number): void {
+        this.view.rowRemoved(row, count);
+        this.listeners.forEach(peer => peer.rowRemoved(row, count));
+    }
+    rowMoved(from: number, to: number, count: number): void {
+        this.view.rowMoved(from, to, count);
+        this.listeners.forEach(peer => peer.rowMoved(from, to, count));
+    }
+    reset(): void {
+        this.view.reset();
+        this.listeners.forEach(peer => peer.reset());
+    }
+}
+
+/**
+ * Makes `listener` notified of the changes of `source`, in addition to the peer that the
+ * run-time sets when `source` is used in a view, before or after this call.
+ * @hidden
+ */
+function addSourceListener<T>(source: Model<T>, listener: SourceModelListener) {
+    if (source instanceof MapModel) {
+        // The MapModel has no notify member of its own
+        return source.addSourceListener(listener);
+    }
+    let peers = source.notify;
+    if (!(peers instanceof ModelPeerList)) {
+        let list = new ModelPeerList(peers || new NullPeer());
+        // The run-time assigns the notify member of the models used in a view: that assignment
+        // only replaces the peer of the view, and keeps the listeners
+        Object.defineProperty(source, "notify", {
+            get: () => list,
+            set: (peer: ModelPeer) => { list.view = peer; },
+            configurable: true,
+            enumerable: true,
+        });
+        peers = list;
+    }
+    (peers as ModelPeerList).listeners.push(new SourceModelPeer(listener));
+}
+
+/**
+ * The new index of `row` after `count` rows starting at `from` were moved to start at `to`
+ * @hidden
+ */
+function movedRow(row: number, from: number, to: number, count: number): number {
+    if (row >= from && row < from + count) {
+        return to + (row - from);
+    }
+    if (row >= from + count) {
+        row -= count;
+    }
+    return row >= to ? row + count : row;
+}
+
+/**
+ * MapModel contains the rows of another model, converted with a function.
+ * The rows cannot be changed through this model.
+ */
+class MapModel<T, U> implements Model<U> {
+    /**
+     * @hidden
+     */
+    private source: Model<T>;
+    /**
+     * @hidden
+     */
+    private map: (value: T) => U;
+
+    /**
+     * Creates a new MapModel with the rows of `source` converted with `map`.
+     *
+     * @param source
+     * @param map
+     */
+    constructor(source: Model<T>, map: (value: T) => U) {
+        this.source = source;
+        this.map = map;
+    }
+
+    rowCount() {
+        return this.source.rowCount();
+    }
+    rowData(row: number) {
+        return this.map(this.source.rowData(row));
+    }
+    setRowData(row: number, data: U) {
+    }
+
+    /**
+     * The rows are the ones of the source model, so the run-time is notified of the changes
+     * of the source model.
+     */
+    get notify(): ModelPeer {
+        return this.source.notify;
+    }
+    set notify(peer: ModelPeer) {
+        this.source.notify = peer;
+    }
+
+    /**
+     * @hidden
+     */
+    addSourceListener(listener: SourceModelListener) {
+        addSourceListener(this.source, listener);
+    }
+}
+
+/**
+ * FilterModel contains the rows of another model for which a filter function returns true.
+ *
+ * The changes of the source model are forwarded: rows added to the source are only added
+ * if they are accepted by the filter, and changed rows are added or removed when the result
+ * of the filter changes. The source model can also be used in a view, or by other model
+ * adapters.
+ */
+class FilterModel<T> implements Model<T>, SourceModelListener {
+    /**
+     * @hidden
+     */
+    private source: Model<T>;
+    /**
+     * @hidden
+     */
+    private filter: (value: T) => boolean;
+    /**
+     * The row in the source model of each row of this model, in increasing order
+     * @hidden
+     */
+    private mapping: number[];
+    notify: ModelPeer;
+
+    /**
+     * Creates a new FilterModel with the rows of `source` for which `filter` returns true.
+     *
+     * @param source
+     * @param filter
+     */
+    constructor(source: Model<T>, filter: (value: T) => boolean) {
+        this.source = source;
+        this.filter = filter;
+        this.mapping = this.filteredRows();
+        this.notify = new NullPeer();
+        addSourceListener(source, this);
+    }
+
+    /**
+     * Changes the filter function. Only the rows for which the result changed are added or removed.
+     * @param filter
+     */
+    setFilter(filter: (value: T) => boolean) {
+        this.filter = filter;
+        let row = 0;
+        for (let sourceRow = 0; sourceRow < this.source.rowCount(); ++sourceRow) {
+            let wasAccepted = this.mapping[row] === sourceRow;
+            let accepted = this.accepts(sourceRow);
+            if (wasAccepted && accepted) {
+                row += 1;
+            } else if (wasAccepted) {
+                this.mapping.splice(row, 1);
+                this.notify.rowRemoved(row, 1);
+            } else if (accepted) {
+                this.mapping.splice(row, 0, sourceRow);
+                this.notify.rowAdded(row, 1);
+                row += 1;
+            }
+        }
+    }
+
+    rowCount() {
+        return this.mapping.length;
+    }
+    rowData(row: number) {
+        return this.source.rowData(this.mapping[row]);
+    }
+    setRowData(row: number, data: T) {
+        this.source.setRowData(this.mapping[row], data);
+    }
+
+    /**
+     * @hidden
+     */
+    sourceRowDataChanged(sourceRow: number): void {
+        let row = this.rowForSourceRow(sourceRow);
+        let present = this.mapping[row] === sourceRow;
+        let accepted = this.accepts(sourceRow);
+        if (present && accepted) {
+            this.notify.rowDataChanged(row);
+        } else if (present) {
+            this.mapping.splice(row, 1);
+            this.notify.rowRemoved(row, 1);
+        } else if (accepted) {
+            this.mapping.splice(row, 0, sourceRow);
+            this.notify.rowAdded(row, 1);
+        }
+    }
+    /**
+     * @hidden
+     */
+    sourceRowAdded(index: number, count: number): void {
+        let row = this.rowForSourceRow(index);
+        let added = [];
+        for (let sourceRow = index; sourceRow < index + count; ++sourceRow) {
+            if (this.accepts(sourceRow)) {
+                added.push(sourceRow);
+            }
+        }
+        for (let i = row; i < this.mapping.length; ++i) {
+            this.mapping[i] += count;
+        }
+        this.mapping.splice(row, 0, ...added);
+        if (added.length > 0) {
+            this.notify.rowAdded(row, added.length);
+        }
+    }
+    /**
+     * @hidden
+     */
+    sourceRowRemoved(index: number, count: number): void {
+        let start = this.rowForSourceRow(index);
+        let end = this.rowForSourceRow(index + count);
+        this.mapping.splice(start, end - start);
+        for (let i = start; i < this.mapping.length; ++i) {
+            this.mapping[i] -= count;
+        }
+        if (end > start) {
+            this.notify.rowRemoved(start, end - start);
+        }
+    }
+    /**
+     * @hidden
+     */
+    sourceRowMoved(from: number, to: number, count: number): void {
+        let start = this.rowForSourceRow(from);
+        let end = this.rowForSourceRow(from + count);
+        let firstMoved = start < this.mapping.length ? movedRow(this.mapping[start], from, to, count) : -1;
+        this.mapping = this.mapping.map(r => movedRow(r, from, to, count));
+        // The moved rows that are accepted by the filter stay next to each other
+        this.mapping.sort((a, b) => a - b);
+        let newStart = this.mapping.indexOf(firstMoved);
+        if (end > start && newStart != start) {
+            this.notify.rowMoved(start, newStart, end - start);
+        }
+    }
+    /**
+     * @hidden
+     */
+    sourceReset(): void {
+        this.mapping = this.filteredRows();
+        this.notify.reset();
+    }
+
+    /**
+     * @hidden
+     */
+    private accepts(sourceRow: number): boolean {
+        return this.filter(this.source.rowData(sourceRow));
+    }
+    /**
+     * The rows of the source model which are accepted by the filter
+     * @hidden
+     */
+    private filteredRows(): number[] {
+        let rows = [];
+        for (let sourceRow = 0; sourceRow < this.source.rowCount(); ++sourceRow) {
+            if (this.accepts(sourceRow)) {
+                rows.push(sourceRow);
+            }
+        }
+        return rows;
+    }
+    /**
+     * The row of this model which is the given source row, or the one after it
+     * @hidden
+     */
+    private rowForSourceRow(sourceRow: number): number {
+        let begin = 0;
+        let end = this.mapping.length;
+        while (begin < end) {
+            let middle = (begin + end) >> 1;
+            if (this.mapping[middle] < sourceRow) {
+                begin = middle + 1;
+            } else {
+                end = middle;
+            }
+        }
+        return begin;
+    }
+}
+
+/**
+ * SortModel contains the rows of another model, sorted with a comparison function that
+ * returns a negative number, zero or a positive number, like the one of `Array.sort()`.
+ *
+ * The changes of the source model are forwarded: added rows are inserted at their sorted
+ * position, and changed rows are moved if their position changed. The source model can also
+ * be used in a view, or by other model adapters.
+ */
+class SortModel<T> implements Model<T>, SourceModelListener {
+    /**
+     * @hidden
+     */
+    private source: Model<T>;
+    /**
+     * @hidden
+     */
+    private compare: (a: T, b: T) => number;
+    /**
+     * The row in the source model of each row of this model
+     * @hidden
+     */
+    private mapping: number[];
+    notify: ModelPeer;
+
+    /**
+     * Creates a new SortModel with the rows of `source` sorted according to `compare`.
+     *
+     * @param source
+     * @param compare
+     */
+    constructor(source: Model<T>, compare: (a: T, b: T) => number) {
+        this.source = source;
+        this.compare = compare;
+        this.mapping = this.sortedRows();
+        this.notify = new NullPeer();
+        addSourceListener(source, this);
+    }
+
+    /**
+     * Changes the comparison function. The model is reset if the order of the rows changed.
+     * @param compare
+     */
+    setComparator(compare: (a: T, b: T) => number) {
+        this.compare = compare;
+        this.sort();
+    }
+
+    rowCount() {
+        return this.mapping.length;
+    }
+    rowData(row: number) {
+        return this.source.rowData(this.mapping[row]);
+    }
+    setRowData(row: number, data: T) {
+        this.source.setRowData(this.mapping[row], data);
+    }
+
+    /**
+     * @hidden
+     */
+    sourceRowDataChanged(sourceRow: number): void {
+        let row = this.mapping.indexOf(sourceRow);
+        if (row < 0) {
+            return;
+        }
+        this.mapping.splice(row, 1);
+        let newRow = this.insertionRow(sourceRow);
+        this.mapping.splice(newRow, 0, sourceRow);
+        if (newRow != row) {
+            this.notify.rowMoved(row, newRow, 1);
+        }
+        this.notify.rowDataChanged(newRow);
+    }
+    /**
+     * @hidden
+     */
+    sourceRowAdded(index: number, count: number): void {
+        this.mapping = this.mapping.map(r => r >= index ? r + count : r);
+        for (let sourceRow = index; sourceRow < index + count; ++sourceRow) {
+            let row = this.insertionRow(sourceRow);
+            this.mapping.splice(row, 0, sourceRow);
+            this.notify.rowAdded(row, 1);
+        }
+    }
+    /**
+     * @hidden
+     */
+    sourceRowRemoved(index: number, count: number): void {
+        let removedRows = [];
+        for (let row = 0; row < this.mapping.length; ++row) {
+            if (this.mapping[row] >= index + count) {
+                this.mapping[row] -= count;
+            } else if (this.mapping[row] >= index) {
+                removedRows.push(row);
+            }
+        }
+        // Remove from the end so that the rows of the next ones are still valid
+        for (let row of removedRows.reverse()) {
+            this.mapping.splice(row, 1);
+            this.notify.rowRemoved(row, 1);
+        }
+    }
+    /**
+     * @hidden
+     */
+    sourceRowMoved(from: number, to: number, count: number): void {
+        this.mapping = this.mapping.map(r => movedRow(r, from, to, count));
+        // The order of the rows that compare equal follows the source order
+        this.sort();
+    }
+    /**
+     * @hidden
+     */
+    sourceReset(): void {
+        this.mapping = this.sortedRows();
+        this.notify.reset();
+    }
+
+    /**
+     * All the rows of the source, sorted. The rows that compare equal stay in the source order.
+     * @hidden
+     */
+    private sortedRows(): number[] {
+        let data = [];
+        for (let sourceRow = 0; sourceRow < this.source.rowCount(); ++sourceRow) {
+            data.push(this.source.rowData(sourceRow));
+        }
+        let rows = data.map((_, sourceRow) => sourceRow);
+        rows.sort((a, b) => this.compare(data[a], data[b]) || a - b);
+        return rows;
+    }
+    /**
+     * The row at which the given source row must be inserted to keep this model sorted
+     * @hidden
+     */
+    private insertionRow(sourceRow: number): number {
+        let data = this.source.rowData(sourceRow);
+        let begin = 0;
+        let end = this.mapping.length;
+        while (begin < end) {
+            let middle = (begin + end) >> 1;
+            let order = this.compare(this.source.rowData(this.mapping[middle]), data)
+                || this.mapping[middle] - sourceRow;
+            if (order > 0) {
+                end = middle;
+            } else {
+                begin = middle + 1;
+            }
+        }
+        return begin;
+    }
+    /**
+     * Sort the rows again, and notify a reset if their order changed
+     * @hidden
+     */
+    private sort() {
+        let sorted = this.sortedRows();
+        if (sorted.some((sourceRow, row) => sourceRow !== this.mapping[row])) {
+            this.mapping = sorted;
+            this.notify.reset();
+        }
+    }
+}
 
 module.exports = {
     private_api: native,
-    ArrayModel: ArrayModel
+    ArrayModel: ArrayModel,
+    MapModel: MapModel,
+    FilterModel: FilterModel,
+    SortModel: SortModel
 };
@@ -140,6 +140,23 @@ declare_types! {
             }
             Ok(JsUndefined::new().as_value(&mut cx))
         }
+        method rowMoved(mut cx) {
+            let this = cx.this();
+            let from = cx.argument::<JsNumber>(0)?.value() as usize;
+            let to = cx.argument::<JsNumber>(1)?.value() as usize;
+            let count = cx.argument::<JsNumber>(2)?.value() as usize;
+            if let Some(model) = cx.borrow(&this, |x| x.0.upgrade()) {
+                model.notify.row_moved(from, to, count)
+            }
+            Ok(JsUndefined::new().as_value(&mut cx))
+        }
+        method reset(mut cx) {
+            let this = cx.this();
+            if let Some(model) = cx.borrow(&this, |x| x.0.upgrade()) {
+                model.notify.reset()
+            }
+            Ok(JsUndefined::new().as_value(&mut cx))
+        }
 
     }
 
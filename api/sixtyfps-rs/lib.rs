@@ -152,12 +152,19 @@ struct MyStruct {
 pub use sixtyfps_macros::sixtyfps;
 
 pub use sixtyfps_corelib::model::{
-    Model, ModelHandle, ModelNotify, ModelPeer, StandardListViewItem, VecModel,
+    FilterModel, MapModel, Model, ModelHandle, ModelNotify, ModelPeer, SortModel,
+    StandardListViewItem, VecModel,
 };
 pub use sixtyfps_corelib::sharedarray::SharedArray;
 pub use sixtyfps_corelib::string::SharedString;
 pub use sixtyfps_corelib::{ARGBColor, Color};
 
+pub use sixtyfps_corelib::font::{
+    register_application_font_from_memory, register_application_font_from_path,
+};
+
+pub use sixtyfps_corelib::layout::LayoutDirection;
+
 /// internal re_exports used by the macro generated
 #[doc(hidden)]
 pub mod re_exports {
@@ -204,6 +211,15 @@ pub fn create_window() -> re_exports::ComponentWindow {
     sixtyfps_rendering_backend_default::create_window()
 }
 
+/// Registers the font of an `import "font.ttf";` statement. The generated code cannot handle
+/// the error, so it is reported on the standard error output.
+#[doc(hidden)]
+pub fn register_application_font_from_resource(resource: &re_exports::Resource) {
+    if let Err(err) = sixtyfps_corelib::font::register_application_font_from_resource(resource) {
+        eprintln!("Error registering font {:?}: {}", resource, err);
+    }
+}
+
 /// Trait implemented by the generated code
 pub trait Component: re_exports::HasStaticVTable<re_exports::ComponentVTable> {
     /// Show this component and run the event loop
@@ -327,6 +343,12 @@ pub mod testing {
         )
     }
 
+    /// Make the component's window use a clipboard that only exists within the process, so
+    /// that tests don't depend on, or modify, the clipboard of the system.
+    pub fn use_in_process_clipboard<X: HasWindow>(component: core::pin::Pin<&X>) {
+        sixtyfps_corelib::tests::sixtyfps_use_in_process_clipboard(component.component_window())
+    }
+
     /// Simulate a series of key press and release event
     pub fn send_key_clicks<
         X: vtable::HasStaticVTable<sixtyfps_corelib::component::ComponentVTable> + HasWindow,
@@ -352,6 +374,28 @@ pub mod testing {
             component.component_window(),
         )
     }
+
+    /// Simulate an input method showing `text` as the text being composed, with the cursor at the
+    /// byte offset `cursor_position` in it. An empty text ends the composition.
+    pub fn send_preedit<X: HasWindow>(
+        component: core::pin::Pin<&X>,
+        text: &str,
+        cursor_position: i32,
+    ) {
+        sixtyfps_corelib::tests::sixtyfps_send_preedit(
+            &super::SharedString::from(text),
+            cursor_position,
+            component.component_window(),
+        )
+    }
+
+    /// Simulate an input method ending the composition and inserting `text`.
+    pub fn commit_preedit<X: HasWindow>(component: core::pin::Pin<&X>, text: &str) {
+        sixtyfps_corelib::tests::sixtyfps_commit_preedit(
+            &super::SharedString::from(text),
+            component.component_window(),
+        )
+    }
 }
 
 /// Include the code generated with the sixtyfps-build crate from the build script. After calling `sixtyfps_build::compile`
@@ -48,6 +48,8 @@ impl Hash for NamedReference {
 /// A function built into the run-time
 pub enum BuiltinFunction {
     GetWindowScaleFactor,
+    /// The layout direction of the window, inherited by the elements that do not set their own
+    GetWindowLayoutDirection,
     Debug,
     Mod,
     SetFocusItem,
@@ -55,6 +57,11 @@ pub enum BuiltinFunction {
     StringToFloat,
     /// the "42".is_float()
     StringIsFloat,
+    /// Registers the font of an `import "font.ttf";` statement
+    RegisterCustomFont,
+    /// A function of a builtin item, such as `copy()` of the TextInput. The first argument is
+    /// the element.
+    ItemMemberFunction(String),
 }
 
 #[derive(Debug, Clone)]
@@ -71,6 +78,10 @@ impl BuiltinFunction {
             BuiltinFunction::GetWindowScaleFactor => {
                 Type::Function { return_type: Box::new(Type::Float32), args: vec![] }
             }
+            BuiltinFunction::GetWindowLayoutDirection => Type::Function {
+                return_type: Box::new(crate::typeregister::layout_direction_type()),
+                args: vec![],
+            },
             BuiltinFunction::Debug => {
                 Type::Function { return_type: Box::new(Type::Void), args: vec![Type::String] }
             }
@@ -88,6 +99,13 @@ impl BuiltinFunction {
             BuiltinFunction::StringIsFloat => {
                 Type::Function { return_type: Box::new(Type::Bool), args: vec![Type::String] }
             }
+            BuiltinFunction::RegisterCustomFont => {
+                Type::Function { return_type: Box::new(Type::Void), args: vec![Type::Resource] }
+            }
+            BuiltinFunction::ItemMemberFunction(..) => Type::Function {
+                return_type: Box::new(Type::Void),
+                args: vec![Type::ElementReference],
+            },
         }
     }
 }
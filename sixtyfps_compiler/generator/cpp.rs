@@ -196,7 +196,7 @@ use crate::expression_tree::{
     BuiltinFunction, EasingCurve, Expression, ExpressionSpanned, NamedReference,
 };
 use crate::langtype::Type;
-use crate::layout::LayoutGeometry;
+use crate::layout::{LayoutGeometry, LayoutRect};
 use crate::object_tree::{Component, Document, Element, ElementRc, RepeatedElementInfo};
 use cpp_ast::*;
 use itertools::Itertools;
@@ -803,18 +803,22 @@ fn generate_component(
                 }),
             ));
         } else {
-            let p_x = access_member(&component.root_element, "x", component, "this");
+            let rect = LayoutRect::for_element(&component.root_element);
+            let get_property_ref = |p: &Option<NamedReference>| match p {
+                Some(nr) => format!("&{}", access_named_reference(nr, component, "this")),
+                None => "nullptr".to_owned(),
+            };
             component_struct.members.push((
                 Access::Public, // Because Repeater accesses it
                 Declaration::Function(Function {
                     name: "box_layout_data".into(),
                     signature: "() const -> sixtyfps::BoxLayoutCellData".to_owned(),
                     statements: Some(vec![format!(
-                        "return {{ layouting_info({{&component_type, const_cast<void *>(static_cast<const void *>(this))}}), &{x}, &{y}, &{w}, &{h} }};",
-                        x = p_x,
-                        y = p_y,
-                        w = p_width,
-                        h = p_height
+                        "return {{ layouting_info({{&component_type, const_cast<void *>(static_cast<const void *>(this))}}), {x}, {y}, {w}, {h} }};",
+                        x = get_property_ref(&rect.x_reference),
+                        y = get_property_ref(&rect.y_reference),
+                        w = get_property_ref(&rect.width_reference),
+                        h = get_property_ref(&rect.height_reference)
                     )]),
                     ..Function::default()
                 }),
@@ -1306,6 +1310,9 @@ fn compile_expression(e: &crate::expression_tree::Expression, component: &Rc<Com
             BuiltinFunction::GetWindowScaleFactor => {
                 format!("{}.scale_factor", window_ref_expression(component))
             }
+            BuiltinFunction::GetWindowLayoutDirection => {
+                format!("{}.layout_direction", window_ref_expression(component))
+            }
             BuiltinFunction::Debug => {
                 "[](auto... args){ (std::cout << ... << args) << std::endl; return nullptr; }"
                     .into()
@@ -1314,6 +1321,9 @@ fn compile_expression(e: &crate::expression_tree::Expression, component: &Rc<Com
             BuiltinFunction::SetFocusItem => {
                 format!("{}.set_focus_item", window_ref_expression(component))
             }
+            BuiltinFunction::ItemMemberFunction(..) => {
+                panic!("internal error: ItemMemberFunction is handled directly in FunctionCall")
+            }
 
            /*  std::from_chars is unfortunately not yet implemented in gcc
             BuiltinFunction::SringIsFloat => {
@@ -1332,6 +1342,9 @@ fn compile_expression(e: &crate::expression_tree::Expression, component: &Rc<Com
                 "[](const auto &a){ auto e1 = std::end(a); auto e2 = const_cast<char*>(e1); auto r = std::strtod(std::begin(a), &e2); return e1 == e2 ? r : 0; }"
                     .into()
             }
+            BuiltinFunction::RegisterCustomFont => {
+                panic!("internal error: RegisterCustomFont is handled directly in CallFunction")
+            }
 
         },
         Expression::ElementReference(_) => todo!("Element references are only supported in the context of built-in function calls at the moment"),
@@ -1427,6 +1440,31 @@ fn compile_expression(e: &crate::expression_tree::Expression, component: &Rc<Com
                     panic!("internal error: argument to SetFocusItem must be an element")
                 }
             }
+            Expression::BuiltinFunctionReference(BuiltinFunction::ItemMemberFunction(name)) => {
+                if let [Expression::ElementReference(item)] = arguments.as_slice() {
+                    let item = item.upgrade().unwrap();
+                    let item = item.borrow();
+                    format!(
+                        "sixtyfps::cbindgen_private::sixtyfps_{class}_{function}(&{item}, &{window})",
+                        class = item.base_type.as_native().class_name.to_lowercase(),
+                        function = name,
+                        item = item.id,
+                        window = window_ref_expression(component)
+                    )
+                } else {
+                    panic!("internal error: argument to ItemMemberFunction must be an element")
+                }
+            }
+            Expression::BuiltinFunctionReference(BuiltinFunction::RegisterCustomFont) => {
+                if let [Expression::ResourceReference { absolute_source_path }] = arguments.as_slice() {
+                    format!(
+                        r#"sixtyfps::register_application_font_from_path(sixtyfps::SharedString("{}"))"#,
+                        absolute_source_path.escape_debug()
+                    )
+                } else {
+                    panic!("internal error: argument to RegisterCustomFont must be a resource")
+                }
+            }
             _ => {
                 let mut args = arguments.iter().map(|e| compile_expression(e, component));
 
@@ -1682,93 +1720,156 @@ impl crate::layout::gen::Language for CppLanguageLayoutGen {
         box_layout: &'a crate::layout::BoxLayout,
         component: &Rc<Component>,
     ) -> crate::layout::gen::LayoutTreeItem<'a, Self> {
-        let is_static_array = box_layout
-            .elems
-            .iter()
-            .all(|i| i.element.as_ref().map_or(true, |x| x.borrow().repeated.is_none()));
+        let (mut creation_code, cell_ref_variable) =
+            box_layout_cells(layout_tree, &box_layout.elems, component);
 
-        let mut make_box_layout_cell_data = |cell: &'a crate::layout::LayoutItem| {
-            let layout_info = get_layout_info_ref(&cell, layout_tree, component);
-            let lay_rect = cell.rect();
-            let get_property_ref = |p: &Option<NamedReference>| match p {
-                Some(nr) => format!("&{}", access_named_reference(nr, component, "self")),
-                None => "nullptr".to_owned(),
-            };
-            format!(
-                "    {{ {li}, {x}, {y}, {w}, {h} }}",
-                li = layout_info,
-                x = get_property_ref(&lay_rect.x_reference),
-                y = get_property_ref(&lay_rect.y_reference),
-                w = get_property_ref(&lay_rect.width_reference),
-                h = get_property_ref(&lay_rect.height_reference)
-            )
-        };
+        let (padding, spacing) = generate_layout_padding_and_spacing(
+            &mut creation_code,
+            &box_layout.geometry,
+            &layout_tree,
+            component,
+        );
 
-        let mut creation_code = if is_static_array {
-            let mut creation_code: Vec<_> =
-                box_layout.elems.iter().map(make_box_layout_cell_data).map(|s| s + ",").collect();
-            creation_code.insert(0, "sixtyfps::BoxLayoutCellData @_data[] = {".into());
-            creation_code.push("};".to_owned());
-            creation_code
-        } else {
-            let mut push_code = vec!["std::vector<sixtyfps::BoxLayoutCellData> @_data;".into()];
-            for item in &box_layout.elems {
-                match &item.element {
-                    Some(elem) if elem.borrow().repeated.is_some() => {
-                        push_code.push(format!(
-                            "self->repeater_{}.ensure_updated(self);",
-                            elem.borrow().id
-                        ));
-                        push_code.push(format!(
-                            "if (self->repeater_{id}.inner) for (auto &&sub_comp : self->repeater_{id}.inner->data)",
-                            id = elem.borrow().id
-                        ));
-                        push_code.push(
-                            "    @_data.push_back((*sub_comp.ptr)->box_layout_data());".to_owned(),
-                        );
-                    }
-                    _ => {
-                        push_code.push(format!(
-                            "@_data.push_back({});",
-                            make_box_layout_cell_data(item)
-                        ));
-                    }
-                }
-            }
-            push_code
-        };
-        let cell_ref_variable = format!("cells_{}", layout_tree.len());
-        creation_code.iter_mut().for_each(|s| *s = s.replace('@', cell_ref_variable.as_ref()));
-        creation_code.push(format!(
-               "const sixtyfps::Slice<sixtyfps::BoxLayoutCellData> {cv}{{&*std::begin({cv}_data), std::size({cv}_data)}};",
-                cv = cell_ref_variable
-            ));
+        LayoutTreeItem::BoxLayout {
+            is_horizontal: box_layout.is_horizontal,
+            geometry: &box_layout.geometry,
+            spacing,
+            padding,
+            alignment: layout_alignment(&box_layout.geometry, component),
+            var_creation_code: creation_code.join("\n"),
+            cell_ref_variable,
+        }
+    }
+
+    fn flow_layout_tree_item<'a, 'b>(
+        layout_tree: &'b mut Vec<crate::layout::gen::LayoutTreeItem<'a, Self>>,
+        flow_layout: &'a crate::layout::FlowLayout,
+        component: &Rc<Component>,
+    ) -> crate::layout::gen::LayoutTreeItem<'a, Self> {
+        let (mut creation_code, cell_ref_variable) =
+            box_layout_cells(layout_tree, &flow_layout.elems, component);
 
         let (padding, spacing) = generate_layout_padding_and_spacing(
             &mut creation_code,
-            &box_layout.geometry,
+            &flow_layout.geometry,
             &layout_tree,
             component,
         );
 
-        let alignment = if let Some(nr) = &box_layout.geometry.alignment {
+        let width = if let Some(nr) = &flow_layout.geometry.rect.width_reference {
             format!("{}.get()", access_named_reference(nr, component, "self"))
         } else {
-            "{}".into()
+            "0.".into()
         };
 
-        LayoutTreeItem::BoxLayout {
-            is_horizontal: box_layout.is_horizontal,
-            geometry: &box_layout.geometry,
+        LayoutTreeItem::FlowLayout {
+            geometry: &flow_layout.geometry,
             spacing,
             padding,
-            alignment,
+            alignment: layout_alignment(&flow_layout.geometry, component),
+            var_creation_code: creation_code.join("\n"),
+            cell_ref_variable,
+            width,
+        }
+    }
+
+    fn stack_layout_tree_item<'a, 'b>(
+        layout_tree: &'b mut Vec<crate::layout::gen::LayoutTreeItem<'a, Self>>,
+        stack_layout: &'a crate::layout::StackLayout,
+        component: &Rc<Component>,
+    ) -> crate::layout::gen::LayoutTreeItem<'a, Self> {
+        let (mut creation_code, cell_ref_variable) =
+            box_layout_cells(layout_tree, &stack_layout.elems, component);
+
+        let (padding, _) = generate_layout_padding_and_spacing(
+            &mut creation_code,
+            &stack_layout.geometry,
+            &layout_tree,
+            component,
+        );
+
+        LayoutTreeItem::StackLayout {
+            geometry: &stack_layout.geometry,
+            padding,
             var_creation_code: creation_code.join("\n"),
             cell_ref_variable,
         }
     }
 }
 
+/// Generate the code that creates the `BoxLayoutCellData` of the cells of a BoxLayout, a
+/// FlowLayout or a StackLayout. Returns the code and the name of the variable with the slice of cells.
+fn box_layout_cells<'a, 'b>(
+    layout_tree: &'b mut Vec<LayoutTreeItem<'a>>,
+    elems: &'a [crate::layout::LayoutItem],
+    component: &Rc<Component>,
+) -> (Vec<String>, String) {
+    let is_static_array =
+        elems.iter().all(|i| i.element.as_ref().map_or(true, |x| x.borrow().repeated.is_none()));
+
+    let mut make_box_layout_cell_data = |cell: &'a crate::layout::LayoutItem| {
+        let layout_info = get_layout_info_ref(&cell, layout_tree, component);
+        let lay_rect = cell.rect();
+        let get_property_ref = |p: &Option<NamedReference>| match p {
+            Some(nr) => format!("&{}", access_named_reference(nr, component, "self")),
+            None => "nullptr".to_owned(),
+        };
+        format!(
+            "    {{ {li}, {x}, {y}, {w}, {h} }}",
+            li = layout_info,
+            x = get_property_ref(&lay_rect.x_reference),
+            y = get_property_ref(&lay_rect.y_reference),
+            w = get_property_ref(&lay_rect.width_reference),
+            h = get_property_ref(&lay_rect.height_reference)
+        )
+    };
+
+    let mut creation_code = if is_static_array {
+        let mut creation_code: Vec<_> =
+            elems.iter().map(make_box_layout_cell_data).map(|s| s + ",").collect();
+        creation_code.insert(0, "sixtyfps::BoxLayoutCellData @_data[] = {".into());
+        creation_code.push("};".to_owned());
+        creation_code
+    } else {
+        let mut push_code = vec!["std::vector<sixtyfps::BoxLayoutCellData> @_data;".into()];
+        for item in elems {
+            match &item.element {
+                Some(elem) if elem.borrow().repeated.is_some() => {
+                    push_code
+                        .push(format!("self->repeater_{}.ensure_updated(self);", elem.borrow().id));
+                    push_code.push(format!(
+                        "if (self->repeater_{id}.inner) for (auto &&sub_comp : self->repeater_{id}.inner->data)",
+                        id = elem.borrow().id
+                    ));
+                    push_code.push(
+                        "    @_data.push_back((*sub_comp.ptr)->box_layout_data());".to_owned(),
+                    );
+                }
+                _ => {
+                    push_code
+                        .push(format!("@_data.push_back({});", make_box_layout_cell_data(item)));
+                }
+            }
+        }
+        push_code
+    };
+    let cell_ref_variable = format!("cells_{}", layout_tree.len());
+    creation_code.iter_mut().for_each(|s| *s = s.replace('@', cell_ref_variable.as_ref()));
+    creation_code.push(format!(
+           "const sixtyfps::Slice<sixtyfps::BoxLayoutCellData> {cv}{{&*std::begin({cv}_data), std::size({cv}_data)}};",
+            cv = cell_ref_variable
+        ));
+    (creation_code, cell_ref_variable)
+}
+
+fn layout_alignment(geometry: &LayoutGeometry, component: &Rc<Component>) -> String {
+    if let Some(nr) = &geometry.alignment {
+        format!("{}.get()", access_named_reference(nr, component, "self"))
+    } else {
+        "{}".into()
+    }
+}
+
 type LayoutTreeItem<'a> = crate::layout::gen::LayoutTreeItem<'a, CppLanguageLayoutGen>;
 
 impl<'a> LayoutTreeItem<'a> {
@@ -1789,6 +1890,15 @@ impl<'a> LayoutTreeItem<'a> {
                 "sixtyfps::box_layout_info(&{}, {}, &{}, {}, {})",
                 cell_ref_variable, spacing, padding, alignment, is_horizontal
             ),
+            LayoutTreeItem::FlowLayout { cell_ref_variable, spacing, padding, width, .. } => {
+                format!(
+                    "sixtyfps::flow_layout_info(&{}, {}, &{}, {})",
+                    cell_ref_variable, spacing, padding, width
+                )
+            }
+            LayoutTreeItem::StackLayout { cell_ref_variable, padding, .. } => {
+                format!("sixtyfps::stack_layout_info(&{}, &{})", cell_ref_variable, padding)
+            }
             LayoutTreeItem::PathLayout(_) => "{/*layout_info for path not implemented*/}".into(),
         }
     }
@@ -1872,11 +1982,25 @@ fn generate_layout_padding_and_spacing<'a, 'b>(
 }
 
 impl<'a> LayoutTreeItem<'a> {
-    fn emit_solve_calls(&self, component: &Rc<Component>, code_stream: &mut Vec<String>) {
+    fn emit_solve_calls(
+        &self,
+        component: &Rc<Component>,
+        horizontal_pass: bool,
+        code_stream: &mut Vec<String>,
+    ) {
+        let pass = if horizontal_pass {
+            "sixtyfps::LayoutPass::Horizontal"
+        } else {
+            "sixtyfps::LayoutPass::Vertical"
+        };
         let layout_prop = |p: &Option<NamedReference>| match p {
             Some(nr) => format!("{}.get()", access_named_reference(nr, component, "self")),
             None => "0".into(),
         };
+        let layout_direction = |geometry: &LayoutGeometry| match &geometry.direction {
+            Some(nr) => format!("{}.get()", access_named_reference(nr, component, "self")),
+            None => "{}".into(),
+        };
         match self {
             LayoutTreeItem::GridLayout {
                 geometry, spacing, cell_ref_variable, padding, ..
@@ -1884,17 +2008,18 @@ impl<'a> LayoutTreeItem<'a> {
                 code_stream.push("    { ".into());
                 code_stream.push("    sixtyfps::GridLayoutData grid { ".into());
                 code_stream.push(format!(
-                    "        {w}, {h}, {x}, {y}, {s}, &{p},",
+                    "        {w}, {h}, {x}, {y}, {s}, &{p}, {d},",
                     w = layout_prop(&geometry.rect.width_reference),
                     h = layout_prop(&geometry.rect.height_reference),
                     x = layout_prop(&geometry.rect.x_reference),
                     y = layout_prop(&geometry.rect.y_reference),
                     s = spacing,
                     p = padding,
+                    d = layout_direction(geometry),
                 ));
                 code_stream.push(format!("        {cv}", cv = cell_ref_variable));
                 code_stream.push("    };".to_owned());
-                code_stream.push("    sixtyfps::solve_grid_layout(&grid);".to_owned());
+                code_stream.push(format!("    sixtyfps::solve_grid_layout(&grid, {});", pass));
                 code_stream.push("    } ".into());
             }
             LayoutTreeItem::BoxLayout {
@@ -1909,21 +2034,68 @@ impl<'a> LayoutTreeItem<'a> {
                 code_stream.push("    { ".into());
                 code_stream.push("    sixtyfps::BoxLayoutData box { ".into());
                 code_stream.push(format!(
-                    "        {w}, {h}, {x}, {y}, {s}, &{p}, {a},",
+                    "        {w}, {h}, {x}, {y}, {s}, &{p}, {a}, {d},",
                     w = layout_prop(&geometry.rect.width_reference),
                     h = layout_prop(&geometry.rect.height_reference),
                     x = layout_prop(&geometry.rect.x_reference),
                     y = layout_prop(&geometry.rect.y_reference),
                     s = spacing,
                     p = padding,
-                    a = alignment
+                    a = alignment,
+                    d = layout_direction(geometry),
                 ));
                 code_stream.push(format!("        {cv}", cv = cell_ref_variable));
                 code_stream.push("    };".to_owned());
-                code_stream
-                    .push(format!("    sixtyfps::solve_box_layout(&box, {});", is_horizontal));
+                code_stream.push(format!(
+                    "    sixtyfps::solve_box_layout(&box, {}, {});",
+                    is_horizontal, pass
+                ));
                 code_stream.push("    } ".into());
             }
+            LayoutTreeItem::FlowLayout {
+                geometry,
+                spacing,
+                cell_ref_variable,
+                padding,
+                alignment,
+                ..
+            } => {
+                code_stream.push("    { ".into());
+                code_stream.push("    sixtyfps::FlowLayoutData flow { ".into());
+                code_stream.push(format!(
+                    "        {w}, {h}, {x}, {y}, {s}, &{p}, {a}, {d},",
+                    w = layout_prop(&geometry.rect.width_reference),
+                    h = layout_prop(&geometry.rect.height_reference),
+                    x = layout_prop(&geometry.rect.x_reference),
+                    y = layout_prop(&geometry.rect.y_reference),
+                    s = spacing,
+                    p = padding,
+                    a = alignment,
+                    d = layout_direction(geometry),
+                ));
+                code_stream.push(format!("        {cv}", cv = cell_ref_variable));
+                code_stream.push("    };".to_owned());
+                code_stream.push(format!("    sixtyfps::solve_flow_layout(&flow, {});", pass));
+                code_stream.push("    } ".into());
+            }
+            LayoutTreeItem::StackLayout { geometry, cell_ref_variable, padding, .. } => {
+                code_stream.push("    { ".into());
+                code_stream.push("    sixtyfps::StackLayoutData stack { ".into());
+                code_stream.push(format!(
+                    "        {w}, {h}, {x}, {y}, &{p},",
+                    w = layout_prop(&geometry.rect.width_reference),
+                    h = layout_prop(&geometry.rect.height_reference),
+                    x = layout_prop(&geometry.rect.x_reference),
+                    y = layout_prop(&geometry.rect.y_reference),
+                    p = padding,
+                ));
+                code_stream.push(format!("        {cv}", cv = cell_ref_variable));
+                code_stream.push("    };".to_owned());
+                code_stream.push(format!("    sixtyfps::solve_stack_layout(&stack, {});", pass));
+                code_stream.push("    } ".into());
+            }
+            // The items are placed on the path once, in the last pass
+            LayoutTreeItem::PathLayout(_) if horizontal_pass => {}
             LayoutTreeItem::PathLayout(path_layout) => {
                 code_stream.push("{".to_owned());
 
@@ -2056,7 +2228,6 @@ fn compute_layout(
     component_layouts.iter().enumerate().for_each(|(idx, layout)| {
         let mut inverse_layout_tree = Vec::new();
 
-        res.push("    {".into());
         let layout_item = crate::layout::gen::collect_layouts_recursively(
             &mut inverse_layout_tree,
             layout,
@@ -2067,7 +2238,7 @@ fn compute_layout(
             layout_info = vec![intro.clone(), format!("return {};", layout_item.layout_info())];
         }
 
-        let mut creation_code = inverse_layout_tree
+        let creation_code = inverse_layout_tree
             .iter()
             .filter_map(|layout| match layout {
                 LayoutTreeItem::GridLayout { var_creation_code, .. } => {
@@ -2076,6 +2247,12 @@ fn compute_layout(
                 LayoutTreeItem::BoxLayout { var_creation_code, .. } => {
                     Some(var_creation_code.clone())
                 }
+                LayoutTreeItem::FlowLayout { var_creation_code, .. } => {
+                    Some(var_creation_code.clone())
+                }
+                LayoutTreeItem::StackLayout { var_creation_code, .. } => {
+                    Some(var_creation_code.clone())
+                }
                 LayoutTreeItem::PathLayout(_) => None,
             })
             .collect::<Vec<_>>();
@@ -2084,13 +2261,17 @@ fn compute_layout(
             layout_info.splice(1..1, creation_code.iter().cloned());
         }
 
-        res.append(&mut creation_code);
-
-        inverse_layout_tree
-            .iter()
-            .rev()
-            .for_each(|layout| layout.emit_solve_calls(component, &mut res));
-        res.push("    }".into());
+        // The cells are created again before the vertical pass, so that their height constraints
+        // are queried for the width set by the horizontal pass.
+        for horizontal_pass in [true, false].iter() {
+            res.push("    {".into());
+            res.extend(creation_code.iter().cloned());
+            inverse_layout_tree
+                .iter()
+                .rev()
+                .for_each(|layout| layout.emit_solve_calls(component, *horizontal_pass, &mut res));
+            res.push("    }".into());
+        }
     });
 
     res.append(repeater_layout_code);
@@ -18,7 +18,7 @@ use crate::expression_tree::{
     BuiltinFunction, EasingCurve, Expression, NamedReference, OperatorClass, Path,
 };
 use crate::langtype::Type;
-use crate::layout::LayoutGeometry;
+use crate::layout::{LayoutGeometry, LayoutRect};
 use crate::object_tree::{Component, Document, ElementRc};
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
@@ -396,16 +396,29 @@ fn generate_component(
                     }
                 } else {
                     // TODO: we could generate this code only if we know that this component is in a box layout
-                    let root_id = format_ident!("{}", base_component.root_element.borrow().id);
+                    let rect = LayoutRect::for_element(&base_component.root_element);
+                    let prop = |nr: &Option<NamedReference>| match nr {
+                        Some(nr) => {
+                            let p = access_named_reference(nr, base_component, quote!(self));
+                            quote!(Some(#p.get_ref()))
+                        }
+                        None => quote!(None),
+                    };
+                    let (x, y, width, height) = (
+                        prop(&rect.x_reference),
+                        prop(&rect.y_reference),
+                        prop(&rect.width_reference),
+                        prop(&rect.height_reference),
+                    );
                     quote! {
                         fn box_layout_data<'a>(self: ::core::pin::Pin<&'a Self>) -> sixtyfps::re_exports::BoxLayoutCellData<'a> {
                             use sixtyfps::re_exports::*;
                             BoxLayoutCellData {
                                 constraint: self.layout_info(),
-                                x: Some(&self.get_ref().#root_id.x),
-                                y: Some(&self.get_ref().#root_id.y),
-                                width: Some(&self.get_ref().#root_id.width),
-                                height: Some(&self.get_ref().#root_id.height),
+                                x: #x,
+                                y: #y,
+                                width: #width,
+                                height: #height,
                             }
                         }
                     }
@@ -1054,17 +1067,27 @@ fn compile_expression(e: &Expression, component: &Rc<Component>) -> TokenStream
                 let window_ref = window_ref_expression(component);
                 quote!(#window_ref.scale_factor)
             }
+            BuiltinFunction::GetWindowLayoutDirection => {
+                let window_ref = window_ref_expression(component);
+                quote!(#window_ref.layout_direction)
+            }
             BuiltinFunction::Debug => quote!((|x| println!("{:?}", x))),
             BuiltinFunction::Mod => quote!((|a1, a2| (a1 as i32) % (a2 as i32))),
             BuiltinFunction::SetFocusItem => {
                 panic!("internal error: SetFocusItem is handled directly in CallFunction")
             }
+            BuiltinFunction::ItemMemberFunction(..) => {
+                panic!("internal error: ItemMemberFunction is handled directly in CallFunction")
+            }
             BuiltinFunction::StringToFloat => {
                 quote!((|x: SharedString| -> f64 { ::core::str::FromStr::from_str(x.as_str()).unwrap_or_default() } ))
             }
             BuiltinFunction::StringIsFloat => {
                 quote!((|x: SharedString| { <f64 as ::core::str::FromStr>::from_str(x.as_str()).is_ok() } ))
             }
+            BuiltinFunction::RegisterCustomFont => {
+                quote!((|r: sixtyfps::re_exports::Resource| sixtyfps::register_application_font_from_resource(&r)))
+            }
         },
         Expression::ElementReference(_) => todo!("Element references are only supported in the context of built-in function calls at the moment"),
         Expression::MemberFunction{ .. } => panic!("member function expressions must not appear in the code generator anymore"),
@@ -1135,6 +1158,18 @@ fn compile_expression(e: &Expression, component: &Rc<Component>) -> TokenStream
                         panic!("internal error: argument to SetFocusItem must be an element")
                     }
                 }
+                Expression::BuiltinFunctionReference(BuiltinFunction::ItemMemberFunction(name)) => {
+                    if let [Expression::ElementReference(item)] = arguments.as_slice() {
+                        let item = format_ident!("{}", item.upgrade().unwrap().borrow().id);
+                        let function = format_ident!("{}", name);
+                        let window_ref = window_ref_expression(component);
+                        quote!(
+                            Self::FIELD_OFFSETS.#item.apply_pin(self_pinned.as_pin_ref()).#function(&#window_ref);
+                        )
+                    } else {
+                        panic!("internal error: argument to ItemMemberFunction must be an element")
+                    }
+                }
                 _ => {
                     let f = compile_expression(function, &component);
                     let a = arguments.iter().map(|a| compile_expression(a, &component));
@@ -1457,102 +1492,163 @@ impl crate::layout::gen::Language for RustLanguageLayoutGen {
         box_layout: &'a crate::layout::BoxLayout,
         component: &Rc<Component>,
     ) -> crate::layout::gen::LayoutTreeItem<'a, Self> {
-        let is_static_array = box_layout
-            .elems
-            .iter()
-            .all(|i| i.element.as_ref().map_or(true, |x| x.borrow().repeated.is_none()));
-
-        let mut make_box_layout_cell_data = |cell: &'a crate::layout::LayoutItem| {
-            let get_property_ref = |p: &Option<NamedReference>| match p {
-                Some(nr) => {
-                    let p = access_named_reference(nr, component, quote!(_self));
-                    quote!(Some(#p.get_ref()))
-                }
-                None => quote!(None),
-            };
-            let lay_rect = cell.rect();
-            let width = get_property_ref(&lay_rect.width_reference);
-            let height = get_property_ref(&lay_rect.height_reference);
-            let x = get_property_ref(&lay_rect.x_reference);
-            let y = get_property_ref(&lay_rect.y_reference);
-            let layout_info = get_layout_info_ref(cell, layout_tree, component);
-            quote!(BoxLayoutCellData {
-                x: #x,
-                y: #y,
-                width: #width,
-                height: #height,
-                constraint: #layout_info,
-            })
-        };
-        let cell_creation_code = if is_static_array {
-            let cells: Vec<_> = box_layout.elems.iter().map(make_box_layout_cell_data).collect();
-            let cell_ref_variable = format_ident!("cells_{}", layout_tree.len());
-            quote!(let #cell_ref_variable = [#( #cells ),*];)
-        } else {
-            let mut fixed_count = 0usize;
-            let mut repeated_count = quote!();
-            let mut push_code = quote!();
-            let component_id = component_id(component);
-            for item in &box_layout.elems {
-                match &item.element {
-                    Some(elem) if elem.borrow().repeated.is_some() => {
-                        let repeater_id = format_ident!("repeater_{}", elem.borrow().id);
-                        let rep_component_id =
-                            self::component_id(&elem.borrow().base_type.as_component());
-                        repeated_count = quote!(#repeated_count + self.#repeater_id.len());
-                        push_code = quote! {
-                            #push_code
-                            #component_id::FIELD_OFFSETS.#repeater_id.apply_pin(self).ensure_updated(
-                                || { #rep_component_id::new(self.self_weak.get().unwrap().clone()).into() }
-                            );
-                            let internal_vec = self.#repeater_id.components_vec();
-                            for sub_comp in &internal_vec {
-                                items_vec.push(sub_comp.as_pin_ref().box_layout_data())
-                            }
-                        }
-                    }
-                    _ => {
-                        let e = make_box_layout_cell_data(item);
-                        fixed_count += 1;
-                        push_code = quote! {
-                            #push_code
-                            items_vec.push(#e);
-                        }
-                    }
-                }
-            }
-            let cell_ref_variable = format_ident!("cells_{}", layout_tree.len());
-            quote! {
-                let mut items_vec = Vec::with_capacity(#fixed_count #repeated_count);
-                #push_code
-                let #cell_ref_variable = items_vec;
-            }
-        };
+        let cell_creation_code = box_layout_cells(layout_tree, &box_layout.elems, component);
         let cell_ref_variable = format_ident!("cells_{}", layout_tree.len());
 
         let (padding, spacing, spacing_creation_code) =
             generate_layout_padding_and_spacing(&layout_tree, &box_layout.geometry, component);
+        let alignment = layout_alignment(&box_layout.geometry, component);
 
-        let alignment = if let Some(expr) = &box_layout.geometry.alignment {
-            let p = access_named_reference(expr, component, quote!(_self));
+        LayoutTreeItem::BoxLayout {
+            is_horizontal: box_layout.is_horizontal,
+            geometry: &box_layout.geometry,
+            var_creation_code: quote!(#cell_creation_code #spacing_creation_code),
+            cell_ref_variable: quote!(#cell_ref_variable),
+            spacing,
+            padding,
+            alignment,
+        }
+        .into()
+    }
+
+    fn flow_layout_tree_item<'a, 'b>(
+        layout_tree: &'b mut Vec<crate::layout::gen::LayoutTreeItem<'a, Self>>,
+        flow_layout: &'a crate::layout::FlowLayout,
+        component: &Rc<Component>,
+    ) -> crate::layout::gen::LayoutTreeItem<'a, Self> {
+        let cell_creation_code = box_layout_cells(layout_tree, &flow_layout.elems, component);
+        let cell_ref_variable = format_ident!("cells_{}", layout_tree.len());
+
+        let (padding, spacing, spacing_creation_code) =
+            generate_layout_padding_and_spacing(&layout_tree, &flow_layout.geometry, component);
+        let alignment = layout_alignment(&flow_layout.geometry, component);
+        let width = if let Some(nr) = &flow_layout.geometry.rect.width_reference {
+            let p = access_named_reference(nr, component, quote!(_self));
             quote!(#p.get())
         } else {
-            quote!(::core::default::Default::default())
+            quote!(0.)
         };
 
-        LayoutTreeItem::BoxLayout {
-            is_horizontal: box_layout.is_horizontal,
-            geometry: &box_layout.geometry,
+        LayoutTreeItem::FlowLayout {
+            geometry: &flow_layout.geometry,
             var_creation_code: quote!(#cell_creation_code #spacing_creation_code),
             cell_ref_variable: quote!(#cell_ref_variable),
             spacing,
             padding,
             alignment,
+            width,
+        }
+        .into()
+    }
+
+    fn stack_layout_tree_item<'a, 'b>(
+        layout_tree: &'b mut Vec<crate::layout::gen::LayoutTreeItem<'a, Self>>,
+        stack_layout: &'a crate::layout::StackLayout,
+        component: &Rc<Component>,
+    ) -> crate::layout::gen::LayoutTreeItem<'a, Self> {
+        let cell_creation_code = box_layout_cells(layout_tree, &stack_layout.elems, component);
+        let cell_ref_variable = format_ident!("cells_{}", layout_tree.len());
+
+        let (padding, _, _) =
+            generate_layout_padding_and_spacing(&layout_tree, &stack_layout.geometry, component);
+
+        LayoutTreeItem::StackLayout {
+            geometry: &stack_layout.geometry,
+            var_creation_code: cell_creation_code,
+            cell_ref_variable: quote!(#cell_ref_variable),
+            padding,
         }
         .into()
     }
 }
 
+/// Generate the code that creates the `BoxLayoutCellData` of the cells of a BoxLayout, a
+/// FlowLayout or a StackLayout, in a variable named after the current length of the layout tree.
+fn box_layout_cells<'a, 'b>(
+    layout_tree: &'b mut Vec<LayoutTreeItem<'a>>,
+    elems: &'a [crate::layout::LayoutItem],
+    component: &Rc<Component>,
+) -> TokenStream {
+    let is_static_array =
+        elems.iter().all(|i| i.element.as_ref().map_or(true, |x| x.borrow().repeated.is_none()));
+
+    let mut make_box_layout_cell_data = |cell: &'a crate::layout::LayoutItem| {
+        let get_property_ref = |p: &Option<NamedReference>| match p {
+            Some(nr) => {
+                let p = access_named_reference(nr, component, quote!(_self));
+                quote!(Some(#p.get_ref()))
+            }
+            None => quote!(None),
+        };
+        let lay_rect = cell.rect();
+        let width = get_property_ref(&lay_rect.width_reference);
+        let height = get_property_ref(&lay_rect.height_reference);
+        let x = get_property_ref(&lay_rect.x_reference);
+        let y = get_property_ref(&lay_rect.y_reference);
+        let layout_info = get_layout_info_ref(cell, layout_tree, component);
+        quote!(BoxLayoutCellData {
+            x: #x,
+            y: #y,
+            width: #width,
+            height: #height,
+            constraint: #layout_info,
+        })
+    };
+    if is_static_array {
+        let cells: Vec<_> = elems.iter().map(make_box_layout_cell_data).collect();
+        let cell_ref_variable = format_ident!("cells_{}", layout_tree.len());
+        quote!(let #cell_ref_variable = [#( #cells ),*];)
+    } else {
+        let mut fixed_count = 0usize;
+        let mut repeated_count = quote!();
+        let mut push_code = quote!();
+        let component_id = component_id(component);
+        for item in elems {
+            match &item.element {
+                Some(elem) if elem.borrow().repeated.is_some() => {
+                    let repeater_id = format_ident!("repeater_{}", elem.borrow().id);
+                    let rep_component_id =
+                        self::component_id(&elem.borrow().base_type.as_component());
+                    repeated_count = quote!(#repeated_count + self.#repeater_id.len());
+                    push_code = quote! {
+                        #push_code
+                        #component_id::FIELD_OFFSETS.#repeater_id.apply_pin(self).ensure_updated(
+                            || { #rep_component_id::new(self.self_weak.get().unwrap().clone()).into() }
+                        );
+                        let internal_vec = self.#repeater_id.components_vec();
+                        for sub_comp in &internal_vec {
+                            items_vec.push(sub_comp.as_pin_ref().box_layout_data())
+                        }
+                    }
+                }
+                _ => {
+                    let e = make_box_layout_cell_data(item);
+                    fixed_count += 1;
+                    push_code = quote! {
+                        #push_code
+                        items_vec.push(#e);
+                    }
+                }
+            }
+        }
+        let cell_ref_variable = format_ident!("cells_{}", layout_tree.len());
+        quote! {
+            let mut items_vec = Vec::with_capacity(#fixed_count #repeated_count);
+            #push_code
+            let #cell_ref_variable = items_vec;
+        }
+    }
+}
+
+fn layout_alignment(geometry: &LayoutGeometry, component: &Rc<Component>) -> TokenStream {
+    if let Some(expr) = &geometry.alignment {
+        let p = access_named_reference(expr, component, quote!(_self));
+        quote!(#p.get())
+    } else {
+        quote!(::core::default::Default::default())
+    }
+}
+
 type LayoutTreeItem<'a> = crate::layout::gen::LayoutTreeItem<'a, RustLanguageLayoutGen>;
 
 impl<'a> LayoutTreeItem<'a> {
@@ -1571,6 +1667,12 @@ impl<'a> LayoutTreeItem<'a> {
             } => {
                 quote!(box_layout_info(&Slice::from_slice(&#cell_ref_variable), #spacing, #padding, #alignment, #is_horizontal))
             }
+            LayoutTreeItem::FlowLayout { cell_ref_variable, spacing, padding, width, .. } => {
+                quote!(flow_layout_info(&Slice::from_slice(&#cell_ref_variable), #spacing, #padding, #width))
+            }
+            LayoutTreeItem::StackLayout { cell_ref_variable, padding, .. } => {
+                quote!(stack_layout_info(&Slice::from_slice(&#cell_ref_variable), #padding))
+            }
             LayoutTreeItem::PathLayout(_) => quote!(todo!("layout_info for PathLayout in rust.rs")),
         }
     }
@@ -1652,7 +1754,17 @@ fn generate_layout_padding_and_spacing<'a, 'b>(
 }
 
 impl<'a> LayoutTreeItem<'a> {
-    fn emit_solve_calls(&self, component: &Rc<Component>, code_stream: &mut Vec<TokenStream>) {
+    fn emit_solve_calls(
+        &self,
+        component: &Rc<Component>,
+        horizontal_pass: bool,
+        code_stream: &mut Vec<TokenStream>,
+    ) {
+        let pass = if horizontal_pass {
+            quote!(LayoutPass::Horizontal)
+        } else {
+            quote!(LayoutPass::Vertical)
+        };
         let layout_prop = |p: &Option<NamedReference>| {
             if let Some(nr) = p {
                 let p = access_named_reference(nr, component, quote!(_self));
@@ -1669,6 +1781,7 @@ impl<'a> LayoutTreeItem<'a> {
                 let y_pos = layout_prop(&geometry.rect.y_reference);
                 let width = layout_prop(&geometry.rect.width_reference);
                 let height = layout_prop(&geometry.rect.height_reference);
+                let direction = layout_prop(&geometry.direction);
 
                 code_stream.push(quote! {
                     solve_grid_layout(&GridLayoutData {
@@ -1679,7 +1792,8 @@ impl<'a> LayoutTreeItem<'a> {
                         cells: Slice::from_slice(&#cell_ref_variable),
                         spacing: #spacing,
                         padding: #padding,
-                    });
+                        direction: #direction,
+                    }, #pass);
                 });
             }
             LayoutTreeItem::BoxLayout {
@@ -1695,6 +1809,7 @@ impl<'a> LayoutTreeItem<'a> {
                 let y_pos = layout_prop(&geometry.rect.y_reference);
                 let width = layout_prop(&geometry.rect.width_reference);
                 let height = layout_prop(&geometry.rect.height_reference);
+                let direction = layout_prop(&geometry.direction);
 
                 code_stream.push(quote! {
                     solve_box_layout(&BoxLayoutData {
@@ -1705,10 +1820,58 @@ impl<'a> LayoutTreeItem<'a> {
                         cells: Slice::from_slice(&#cell_ref_variable),
                         spacing: #spacing,
                         padding: #padding,
-                        alignment: #alignment
-                    }, #is_horizontal);
+                        alignment: #alignment,
+                        direction: #direction,
+                    }, #is_horizontal, #pass);
+                });
+            }
+            LayoutTreeItem::FlowLayout {
+                geometry,
+                cell_ref_variable,
+                spacing,
+                padding,
+                alignment,
+                ..
+            } => {
+                let x_pos = layout_prop(&geometry.rect.x_reference);
+                let y_pos = layout_prop(&geometry.rect.y_reference);
+                let width = layout_prop(&geometry.rect.width_reference);
+                let height = layout_prop(&geometry.rect.height_reference);
+                let direction = layout_prop(&geometry.direction);
+
+                code_stream.push(quote! {
+                    solve_flow_layout(&FlowLayoutData {
+                        width: #width,
+                        height: #height,
+                        x: #x_pos,
+                        y: #y_pos,
+                        cells: Slice::from_slice(&#cell_ref_variable),
+                        spacing: #spacing,
+                        padding: #padding,
+                        alignment: #alignment,
+                        direction: #direction,
+                    }, #pass);
                 });
             }
+            LayoutTreeItem::StackLayout { geometry, cell_ref_variable, padding, .. } => {
+                let x_pos = layout_prop(&geometry.rect.x_reference);
+                let y_pos = layout_prop(&geometry.rect.y_reference);
+                let width = layout_prop(&geometry.rect.width_reference);
+                let height = layout_prop(&geometry.rect.height_reference);
+
+                code_stream.push(quote! {
+                    solve_stack_layout(&StackLayoutData {
+                        width: #width,
+                        height: #height,
+                        x: #x_pos,
+                        y: #y_pos,
+                        cells: Slice::from_slice(&#cell_ref_variable),
+                        padding: #padding,
+                    }, #pass);
+                });
+            }
+            // The items are placed on the path once, in the last pass
+            LayoutTreeItem::PathLayout(_) if horizontal_pass => {}
             LayoutTreeItem::PathLayout(path_layout) => {
                 let path_layout_item_data =
                     |elem: &ElementRc, elem_rs: TokenStream, component_rust: TokenStream| {
@@ -1844,7 +2007,7 @@ fn compute_layout(
             layout_info = layout_item.layout_info()
         }
 
-        let mut creation_code = inverse_layout_tree
+        let creation_code = inverse_layout_tree
             .iter()
             .filter_map(|layout| match layout {
                 LayoutTreeItem::GridLayout { var_creation_code, .. } => {
@@ -1853,6 +2016,12 @@ fn compute_layout(
                 LayoutTreeItem::BoxLayout { var_creation_code, .. } => {
                     Some(var_creation_code.clone())
                 }
+                LayoutTreeItem::FlowLayout { var_creation_code, .. } => {
+                    Some(var_creation_code.clone())
+                }
+                LayoutTreeItem::StackLayout { var_creation_code, .. } => {
+                    Some(var_creation_code.clone())
+                }
                 LayoutTreeItem::PathLayout(_) => None,
             })
             .collect::<Vec<_>>();
@@ -1861,12 +2030,15 @@ fn compute_layout(
             layout_info = quote!(#(#creation_code)* #layout_info);
         }
 
-        layouts.append(&mut creation_code);
-
-        inverse_layout_tree
-            .iter()
-            .rev()
-            .for_each(|layout| layout.emit_solve_calls(component, &mut layouts));
+        // The cells are created again before the vertical pass, so that their height constraints
+        // are queried for the width set by the horizontal pass.
+        for horizontal_pass in [true, false].iter() {
+            let mut solve_calls = vec![];
+            inverse_layout_tree.iter().rev().for_each(|layout| {
+                layout.emit_solve_calls(component, *horizontal_pass, &mut solve_calls)
+            });
+            layouts.push(quote!({ #(#creation_code)* #(#solve_calls)* }));
+        }
     });
 
     let window_ref = window_ref_expression(component);
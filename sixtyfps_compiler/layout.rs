@@ -22,6 +22,8 @@ pub enum Layout {
     GridLayout(GridLayout),
     PathLayout(PathLayout),
     BoxLayout(BoxLayout),
+    FlowLayout(FlowLayout),
+    StackLayout(StackLayout),
 }
 
 impl Layout {
@@ -29,6 +31,8 @@ impl Layout {
         match self {
             Layout::GridLayout(g) => &g.geometry.rect,
             Layout::BoxLayout(g) => &g.geometry.rect,
+            Layout::FlowLayout(g) => &g.geometry.rect,
+            Layout::StackLayout(g) => &g.geometry.rect,
             Layout::PathLayout(p) => &p.rect,
         }
     }
@@ -40,6 +44,8 @@ impl Layout {
         match self {
             Layout::GridLayout(grid) => grid.visit_named_references(visitor),
             Layout::BoxLayout(l) => l.visit_named_references(visitor),
+            Layout::FlowLayout(l) => l.visit_named_references(visitor),
+            Layout::StackLayout(l) => l.visit_named_references(visitor),
             Layout::PathLayout(path) => path.visit_named_references(visitor),
         }
     }
@@ -68,19 +74,14 @@ pub struct LayoutItem {
 impl LayoutItem {
     pub fn rect(&self) -> Cow<LayoutRect> {
         if let Some(e) = &self.element {
-            let prop = |name: &str| {
-                if e.borrow().lookup_property(name) == Type::Length {
-                    Some(NamedReference::new(e, name))
-                } else {
-                    None
-                }
-            };
-            Cow::Owned(LayoutRect {
-                x_reference: prop("x"),
-                y_reference: prop("y"),
-                width_reference: if !self.fixed_width { prop("width") } else { None },
-                height_reference: if !self.fixed_height { prop("height") } else { None },
-            })
+            let mut r = LayoutRect::for_element(e);
+            if self.fixed_width {
+                r.width_reference = None;
+            }
+            if self.fixed_height {
+                r.height_reference = None;
+            }
+            Cow::Owned(r)
         } else if let Some(l) = &self.layout {
             let mut r = Cow::Borrowed(l.rect());
             if r.width_reference.is_some() && self.fixed_width {
@@ -126,7 +127,29 @@ impl LayoutRect {
         }
     }
 
-    fn visit_named_references(&mut self, mut visitor: &mut impl FnMut(&mut NamedReference)) {
+    /// The properties in which a layout places the given element: its x, y, width and height,
+    /// or the properties they are animated from.
+    pub fn for_element(element: &ElementRc) -> Self {
+        let e = element.borrow();
+        let prop = |name: &str, animated: &Option<NamedReference>| {
+            if animated.is_some() {
+                animated.clone()
+            } else if e.lookup_property(name) == Type::Length {
+                Some(NamedReference::new(element, name))
+            } else {
+                None
+            }
+        };
+        let animated = &e.animated_layout_geometry;
+        Self {
+            x_reference: prop("x", &animated.x_reference),
+            y_reference: prop("y", &animated.y_reference),
+            width_reference: prop("width", &animated.width_reference),
+            height_reference: prop("height", &animated.height_reference),
+        }
+    }
+
+    pub fn visit_named_references(&mut self, mut visitor: &mut impl FnMut(&mut NamedReference)) {
         self.width_reference.as_mut().map(&mut visitor);
         self.height_reference.as_mut().map(&mut visitor);
         self.x_reference.as_mut().map(&mut visitor);
@@ -140,8 +163,11 @@ pub struct LayoutConstraints {
     pub maximum_width: Option<NamedReference>,
     pub minimum_height: Option<NamedReference>,
     pub maximum_height: Option<NamedReference>,
+    pub preferred_width: Option<NamedReference>,
+    pub preferred_height: Option<NamedReference>,
     pub horizontal_stretch: Option<NamedReference>,
     pub vertical_stretch: Option<NamedReference>,
+    pub aspect_ratio: Option<NamedReference>,
 }
 
 impl LayoutConstraints {
@@ -151,8 +177,11 @@ impl LayoutConstraints {
             maximum_width: binding_reference(&element, "maximum_width"),
             minimum_height: binding_reference(&element, "minimum_height"),
             maximum_height: binding_reference(&element, "maximum_height"),
+            preferred_width: binding_reference(&element, "preferred_width"),
+            preferred_height: binding_reference(&element, "preferred_height"),
             horizontal_stretch: binding_reference(&element, "horizontal_stretch"),
             vertical_stretch: binding_reference(&element, "vertical_stretch"),
+            aspect_ratio: binding_reference(&element, "aspect_ratio"),
         }
     }
 
@@ -161,18 +190,24 @@ impl LayoutConstraints {
             || self.maximum_width.is_some()
             || self.minimum_height.is_some()
             || self.maximum_height.is_some()
+            || self.preferred_width.is_some()
+            || self.preferred_height.is_some()
             || self.horizontal_stretch.is_some()
             || self.vertical_stretch.is_some()
+            || self.aspect_ratio.is_some()
     }
 
-    pub fn for_each_restrictions<'a>(&'a self) -> [(&Option<NamedReference>, &'static str); 6] {
+    pub fn for_each_restrictions<'a>(&'a self) -> [(&Option<NamedReference>, &'static str); 9] {
         [
             (&self.minimum_width, "min_width"),
             (&self.maximum_width, "max_width"),
             (&self.minimum_height, "min_height"),
             (&self.maximum_height, "max_height"),
+            (&self.preferred_width, "preferred_width"),
+            (&self.preferred_height, "preferred_height"),
             (&self.horizontal_stretch, "horizontal_stretch"),
             (&self.vertical_stretch, "vertical_stretch"),
+            (&self.aspect_ratio, "aspect_ratio"),
         ]
     }
 
@@ -181,8 +216,11 @@ impl LayoutConstraints {
         self.minimum_width.as_mut().map(|e| visitor(&mut *e));
         self.maximum_height.as_mut().map(|e| visitor(&mut *e));
         self.minimum_height.as_mut().map(|e| visitor(&mut *e));
+        self.preferred_width.as_mut().map(|e| visitor(&mut *e));
+        self.preferred_height.as_mut().map(|e| visitor(&mut *e));
         self.horizontal_stretch.as_mut().map(|e| visitor(&mut *e));
         self.vertical_stretch.as_mut().map(|e| visitor(&mut *e));
+        self.aspect_ratio.as_mut().map(|e| visitor(&mut *e));
     }
 }
 
@@ -218,6 +256,7 @@ pub struct LayoutGeometry {
     pub rect: LayoutRect,
     pub spacing: Option<NamedReference>,
     pub alignment: Option<NamedReference>,
+    pub direction: Option<NamedReference>,
     pub padding: Padding,
 }
 
@@ -226,6 +265,7 @@ impl LayoutGeometry {
         self.rect.visit_named_references(visitor);
         self.spacing.as_mut().map(|e| visitor(&mut *e));
         self.alignment.as_mut().map(|e| visitor(&mut *e));
+        self.direction.as_mut().map(|e| visitor(&mut *e));
         self.padding.visit_named_references(visitor);
     }
 }
@@ -282,6 +322,7 @@ impl LayoutGeometry {
             }
         });
         let alignment = binding_reference(layout_element, "alignment");
+        let direction = binding_reference(layout_element, "layout_direction");
 
         init_fake_property(layout_element, "width", || rect.width_reference.clone());
         init_fake_property(layout_element, "height", || rect.height_reference.clone());
@@ -299,7 +340,7 @@ impl LayoutGeometry {
             bottom: binding_reference(layout_element, "padding_bottom").or_else(padding),
         };
 
-        Self { rect, spacing, padding, alignment }
+        Self { rect, spacing, padding, alignment, direction }
     }
 }
 
@@ -341,6 +382,40 @@ impl BoxLayout {
     }
 }
 
+/// Internal representation of a FlowLayout
+#[derive(Debug)]
+pub struct FlowLayout {
+    pub elems: Vec<LayoutItem>,
+    pub geometry: LayoutGeometry,
+}
+
+impl FlowLayout {
+    fn visit_named_references(&mut self, visitor: &mut impl FnMut(&mut NamedReference)) {
+        for cell in &mut self.elems {
+            cell.layout.as_mut().map(|x| x.visit_named_references(visitor));
+            cell.constraints.visit_named_references(visitor);
+        }
+        self.geometry.visit_named_references(visitor);
+    }
+}
+
+/// Internal representation of a StackLayout
+#[derive(Debug)]
+pub struct StackLayout {
+    pub elems: Vec<LayoutItem>,
+    pub geometry: LayoutGeometry,
+}
+
+impl StackLayout {
+    fn visit_named_references(&mut self, visitor: &mut impl FnMut(&mut NamedReference)) {
+        for cell in &mut self.elems {
+            cell.layout.as_mut().map(|x| x.visit_named_references(visitor));
+            cell.constraints.visit_named_references(visitor);
+        }
+        self.geometry.visit_named_references(visitor);
+    }
+}
+
 /// Internal representation of a path layout
 #[derive(Debug)]
 pub struct PathLayout {
@@ -390,6 +465,18 @@ pub mod gen {
             box_layout: &'a BoxLayout,
             component: &Rc<Component>,
         ) -> LayoutTreeItem<'a, Self>;
+        /// Returns a LayoutTree:FlowLayout
+        fn flow_layout_tree_item<'a, 'b>(
+            layout_tree: &'b mut Vec<LayoutTreeItem<'a, Self>>,
+            flow_layout: &'a FlowLayout,
+            component: &Rc<Component>,
+        ) -> LayoutTreeItem<'a, Self>;
+        /// Returns a LayoutTree:StackLayout
+        fn stack_layout_tree_item<'a, 'b>(
+            layout_tree: &'b mut Vec<LayoutTreeItem<'a, Self>>,
+            stack_layout: &'a StackLayout,
+            component: &Rc<Component>,
+        ) -> LayoutTreeItem<'a, Self>;
     }
 
     #[derive(derive_more::From)]
@@ -410,6 +497,22 @@ pub mod gen {
             cell_ref_variable: L::CompiledCode,
             is_horizontal: bool,
         },
+        FlowLayout {
+            geometry: &'a LayoutGeometry,
+            spacing: L::CompiledCode,
+            padding: L::CompiledCode,
+            alignment: L::CompiledCode,
+            var_creation_code: L::CompiledCode,
+            cell_ref_variable: L::CompiledCode,
+            /// The current width of the layout, from which the layout info computes the height
+            width: L::CompiledCode,
+        },
+        StackLayout {
+            geometry: &'a LayoutGeometry,
+            padding: L::CompiledCode,
+            var_creation_code: L::CompiledCode,
+            cell_ref_variable: L::CompiledCode,
+        },
         #[from]
         PathLayout(&'a PathLayout),
     }
@@ -445,6 +548,14 @@ pub mod gen {
                 let i = L::box_layout_tree_item(layout_tree, box_layout, component);
                 layout_tree.push(i);
             }
+            Layout::FlowLayout(flow_layout) => {
+                let i = L::flow_layout_tree_item(layout_tree, flow_layout, component);
+                layout_tree.push(i);
+            }
+            Layout::StackLayout(stack_layout) => {
+                let i = L::stack_layout_tree_item(layout_tree, stack_layout, component);
+                layout_tree.push(i);
+            }
             Layout::PathLayout(layout) => layout_tree.push(layout.into()),
         }
         layout_tree.last().unwrap()
@@ -90,9 +90,18 @@ pub fn lex_string(text: &str) -> usize {
     if !text.starts_with('"') {
         return 0;
     }
-    let end = text[1..].find('"').unwrap_or(0) + 2;
-    assert!(!text[..end].contains("\\"), "escape code not yet supported");
-    end
+    let mut chars = text.char_indices().skip(1);
+    while let Some((pos, c)) = chars.next() {
+        match c {
+            '"' => return pos + 1,
+            // skip the escaped character, which may be a quote
+            '\\' => {
+                chars.next();
+            }
+            _ => {}
+        }
+    }
+    2
 }
 
 pub fn lex_number(text: &str) -> usize {
@@ -202,6 +211,15 @@ fn basic_lexer_test() {
         ],
     );
 
+    compare(
+        r#""a\"b\n" "c""#,
+        &[
+            (crate::parser::SyntaxKind::StringLiteral, r#""a\"b\n""#),
+            (crate::parser::SyntaxKind::Whitespace, " "),
+            (crate::parser::SyntaxKind::StringLiteral, r#""c""#),
+        ],
+    );
+
     compare(
         r#"12px+5.2+=0.7%"#,
         &[
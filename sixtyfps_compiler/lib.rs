@@ -40,12 +40,16 @@ pub mod typeregister;
 
 mod passes {
     pub mod check_expressions;
+    pub mod collect_custom_fonts;
     pub mod collect_globals;
     pub mod collect_resources;
     pub mod compile_paths;
     pub mod deduplicate_property_read;
     pub mod focus_item;
+    pub mod inherit_border_radius;
+    pub mod inherit_layout_direction;
     pub mod inlining;
+    pub mod lower_anchors;
     pub mod lower_layout;
     pub mod lower_states;
     pub mod materialize_fake_properties;
@@ -153,10 +157,17 @@ pub async fn run_passes<'a>(
     passes::unique_id::assign_unique_id(&doc.root_component);
     passes::focus_item::determine_initial_focus_item(&doc.root_component, diag);
     passes::materialize_fake_properties::materialize_fake_properties(&doc.root_component);
+    passes::lower_anchors::lower_anchors(&doc.root_component, diag);
+    passes::collect_custom_fonts::collect_custom_fonts(
+        &doc.root_component,
+        doc.custom_fonts.iter().chain(type_loader.all_custom_fonts()),
+    );
     passes::collect_resources::collect_resources(&doc.root_component);
     doc.root_component.embed_file_resources.set(compiler_config.embed_resources);
+    passes::inherit_border_radius::inherit_border_radius(&doc.root_component);
     passes::lower_states::lower_states(&doc.root_component, diag);
     passes::repeater_component::process_repeater_components(&doc.root_component);
+    passes::inherit_layout_direction::inherit_layout_direction(&doc.root_component);
     passes::lower_layout::lower_layouts(&doc.root_component, &mut type_loader, diag).await;
     passes::deduplicate_property_read::deduplicate_property_read(&doc.root_component);
     passes::move_declarations::move_declarations(&doc.root_component, diag);
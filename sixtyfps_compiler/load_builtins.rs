@@ -159,6 +159,7 @@ pub fn load_builtins(register: &mut TypeRegister) {
     }
 
     register.property_animation_type = Type::Builtin(natives.remove("PropertyAnimation").unwrap());
+    register.stack_page_type = Type::Builtin(natives.remove("StackPage").unwrap());
 
     if !diag.is_empty() {
         let vec = diag.to_string_vec();
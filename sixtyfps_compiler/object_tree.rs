@@ -28,6 +28,8 @@ pub struct Document {
     pub inner_structs: Vec<Type>,
     pub root_component: Rc<Component>,
     pub local_registry: TypeRegister,
+    /// The absolute paths of the font files imported with `import "font.ttf";`
+    pub custom_fonts: Vec<String>,
     exports: Exports,
 }
 
@@ -42,6 +44,7 @@ impl Document {
         let mut local_registry = TypeRegister::new(parent_registry);
         let mut inner_components = vec![];
         let mut inner_structs = vec![];
+        let mut custom_fonts = vec![];
 
         let mut process_component =
             |n: syntax_nodes::Component,
@@ -85,6 +88,30 @@ impl Document {
                         }
                     }
                 }
+                SyntaxKind::ImportSpecifier => {
+                    let import: syntax_nodes::ImportSpecifier = n.into();
+                    if import.ImportIdentifierList().is_some() {
+                        continue;
+                    }
+                    let import_uri = import.child_token(SyntaxKind::StringLiteral).expect(
+                        "Internal error: missing import uri literal, this is a parsing/grammar bug",
+                    );
+                    let path = import_uri.text().trim_matches('\"').to_string();
+                    let extension = std::path::Path::new(&path)
+                        .extension()
+                        .map(|e| e.to_string_lossy().to_lowercase());
+                    if !matches!(extension.as_deref(), Some("ttf") | Some("ttc") | Some("otf")) {
+                        diag.push_error(
+                            "Only font files (.ttf, .ttc or .otf) can be imported without a list of names"
+                                .into(),
+                            &import_uri,
+                        );
+                        continue;
+                    }
+                    let source_file = import.source_file.as_ref().map(|f| f.as_path());
+                    custom_fonts
+                        .push(crate::passes::resolving::absolute_source_path(path, source_file));
+                }
                 _ => {}
             };
         }
@@ -96,6 +123,7 @@ impl Document {
             inner_components,
             inner_structs,
             local_registry,
+            custom_fonts,
             exports,
         }
     }
@@ -226,6 +254,9 @@ pub struct Element {
     pub transitions: Vec<Transition>,
 
     pub child_of_layout: bool,
+    /// When the geometry set by a layout is animated, the layout sets these properties, to which
+    /// the x, y, width or height of this element are bound. (see lower_layout)
+    pub animated_layout_geometry: crate::layout::LayoutRect,
 
     /// The AST node, if available
     pub node: Option<syntax_nodes::Element>,
@@ -1012,6 +1043,10 @@ pub fn visit_all_named_references(elem: &ElementRc, mut vis: impl FnMut(&mut Nam
         }
     }
     elem.borrow_mut().repeated = repeated;
+    let mut animated_layout_geometry =
+        std::mem::take(&mut elem.borrow_mut().animated_layout_geometry);
+    animated_layout_geometry.visit_named_references(&mut vis);
+    elem.borrow_mut().animated_layout_geometry = animated_layout_geometry;
 }
 
 #[derive(Debug, Clone)]
@@ -342,7 +342,8 @@ declare_syntax! {
         ExportIdentifier -> [],
         ExportName -> [],
         /// import { foo, bar, baz } from "blah"; The import uri is stored as string literal.
-        ImportSpecifier -> [ ImportIdentifierList ],
+        /// `import "font.ttf";` has no ImportIdentifierList
+        ImportSpecifier -> [ ?ImportIdentifierList ],
         ImportIdentifierList -> [ *ImportIdentifier ],
         /// { foo as bar } or just { foo }
         ImportIdentifier -> [ ExternalName, ?InternalName ],
@@ -644,11 +644,16 @@ fn parse_export_specifier(p: &mut impl Parser) -> bool {
 #[cfg_attr(test, parser_test)]
 /// ```test,ImportSpecifier
 /// import { Type1, Type2 } from "somewhere";
+/// import "fonts/Inter.ttf";
 /// ```
 fn parse_import_specifier(p: &mut impl Parser) -> bool {
     debug_assert_eq!(p.peek().as_str(), "import");
     let mut p = p.start_node(SyntaxKind::ImportSpecifier);
     p.consume(); // "import"
+    if p.peek().kind() == SyntaxKind::StringLiteral {
+        p.consume();
+        return p.expect(SyntaxKind::Semicolon);
+    }
     if !parse_import_identifier_list(&mut *p) {
         return false;
     }
@@ -0,0 +1,39 @@
+/* LICENSE BEGIN
+    This file is part of the SixtyFPS Project -- https://sixtyfps.io
+    Copyright (c) 2020 Olivier Goffart <olivier.goffart@sixtyfps.io>
+    Copyright (c) 2020 Simon Hausmann <simon.hausmann@sixtyfps.io>
+
+    SPDX-License-Identifier: GPL-3.0-only
+    This file is also available under commercial licensing terms.
+    Please contact info@sixtyfps.io for more information.
+LICENSE END */
+//! This pass registers the fonts imported with `import "font.ttf";` in the setup code of the root component
+
+use std::rc::Rc;
+
+use crate::{
+    expression_tree::{BuiltinFunction, Expression},
+    object_tree::*,
+};
+
+pub fn collect_custom_fonts<'a>(
+    root_component: &Rc<Component>,
+    all_fonts: impl Iterator<Item = &'a String>,
+) {
+    let mut all_fonts: Vec<_> = all_fonts.collect();
+    all_fonts.sort();
+    all_fonts.dedup();
+
+    // Register the fonts before running any other setup code
+    root_component.setup_code.borrow_mut().splice(
+        0..0,
+        all_fonts.into_iter().map(|font_path| Expression::FunctionCall {
+            function: Box::new(Expression::BuiltinFunctionReference(
+                BuiltinFunction::RegisterCustomFont,
+            )),
+            arguments: vec![Expression::ResourceReference {
+                absolute_source_path: font_path.clone(),
+            }],
+        }),
+    );
+}
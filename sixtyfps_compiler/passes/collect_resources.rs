@@ -14,7 +14,10 @@ use std::rc::Rc;
 pub fn collect_resources(component: &Rc<Component>) {
     recurse_elem(&component.root_element, &(), &mut |elem, _| {
         visit_element_expressions(elem, |e, _, _| collect_resources_from_expression(e, component));
-    })
+    });
+    for e in component.setup_code.borrow().iter() {
+        collect_resources_from_expression(e, component);
+    }
 }
 
 fn collect_resources_from_expression(e: &Expression, component: &Rc<Component>) {
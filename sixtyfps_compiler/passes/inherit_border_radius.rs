@@ -0,0 +1,69 @@
+/* LICENSE BEGIN
+    This file is part of the SixtyFPS Project -- https://sixtyfps.io
+    Copyright (c) 2020 Olivier Goffart <olivier.goffart@sixtyfps.io>
+    Copyright (c) 2020 Simon Hausmann <simon.hausmann@sixtyfps.io>
+
+    SPDX-License-Identifier: GPL-3.0-only
+    This file is also available under commercial licensing terms.
+    Please contact info@sixtyfps.io for more information.
+LICENSE END */
+//! This pass binds the corner radii of a Rectangle that are not set to its border_radius.
+//! This must run before lower_states so that the states see the binding to the border_radius.
+
+use std::rc::Rc;
+
+use crate::expression_tree::{Expression, NamedReference};
+use crate::langtype::Type;
+use crate::object_tree::*;
+
+const CORNER_RADII: [&str; 4] = [
+    "border_top_left_radius",
+    "border_top_right_radius",
+    "border_bottom_right_radius",
+    "border_bottom_left_radius",
+];
+
+pub fn inherit_border_radius(component: &Rc<Component>) {
+    // The border_radius can also only be set by a state
+    let mut set_by_states = vec![];
+    recurse_elem_including_sub_components(&component.root_element, &(), &mut |elem, _| {
+        for state in &elem.borrow().states {
+            set_by_states.extend(
+                state
+                    .property_changes
+                    .iter()
+                    .filter(|(ne, _)| ne.name == "border_radius")
+                    .map(|(ne, _)| ne.clone()),
+            );
+        }
+    });
+
+    recurse_elem_including_sub_components(&component.root_element, &(), &mut |elem, _| {
+        if !has_corner_radii(&elem.borrow().base_type) {
+            return;
+        }
+        let border_radius = NamedReference::new(elem, "border_radius");
+        if !elem.borrow().bindings.contains_key("border_radius")
+            && !set_by_states.contains(&border_radius)
+        {
+            return;
+        }
+        for corner in CORNER_RADII.iter() {
+            if !elem.borrow().bindings.contains_key(*corner) {
+                elem.borrow_mut().bindings.insert(
+                    (*corner).into(),
+                    Expression::PropertyReference(border_radius.clone()).into(),
+                );
+            }
+        }
+    });
+}
+
+/// Returns true for the builtin elements that have a radius for each of their corners
+fn has_corner_radii(base_type: &Type) -> bool {
+    match base_type {
+        Type::Builtin(b) => b.properties.contains_key(CORNER_RADII[0]),
+        Type::Native(n) => n.lookup_property(CORNER_RADII[0]).is_some(),
+        _ => false,
+    }
+}
@@ -0,0 +1,53 @@
+/* LICENSE BEGIN
+    This file is part of the SixtyFPS Project -- https://sixtyfps.io
+    Copyright (c) 2020 Olivier Goffart <olivier.goffart@sixtyfps.io>
+    Copyright (c) 2020 Simon Hausmann <simon.hausmann@sixtyfps.io>
+
+    SPDX-License-Identifier: GPL-3.0-only
+    This file is also available under commercial licensing terms.
+    Please contact info@sixtyfps.io for more information.
+LICENSE END */
+//! This pass makes the layouts and the texts follow the layout_direction of the closest ancestor
+//! that sets one, or of the window otherwise.
+
+use std::rc::Rc;
+
+use crate::expression_tree::{BuiltinFunction, Expression, NamedReference};
+use crate::langtype::Type;
+use crate::object_tree::*;
+
+pub fn inherit_layout_direction(component: &Rc<Component>) {
+    let window_direction = Expression::FunctionCall {
+        function: Box::new(Expression::BuiltinFunctionReference(
+            BuiltinFunction::GetWindowLayoutDirection,
+        )),
+        arguments: vec![],
+    };
+    recurse_elem_including_sub_components(
+        &component.root_element,
+        &window_direction,
+        &mut |elem, direction| {
+            if elem.borrow().bindings.contains_key("layout_direction") {
+                return Expression::PropertyReference(NamedReference::new(
+                    elem,
+                    "layout_direction",
+                ));
+            }
+            if uses_layout_direction(&elem.borrow().base_type) {
+                elem.borrow_mut()
+                    .bindings
+                    .insert("layout_direction".into(), direction.clone().into());
+            }
+            direction.clone()
+        },
+    );
+}
+
+/// Returns true for the builtin elements that are placed according to their layout_direction
+fn uses_layout_direction(base_type: &Type) -> bool {
+    match base_type {
+        Type::Builtin(b) => b.properties.contains_key("layout_direction"),
+        Type::Native(n) => n.lookup_property("layout_direction").is_some(),
+        _ => false,
+    }
+}
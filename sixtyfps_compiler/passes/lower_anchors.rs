@@ -0,0 +1,173 @@
+/* LICENSE BEGIN
+    This file is part of the SixtyFPS Project -- https://sixtyfps.io
+    Copyright (c) 2020 Olivier Goffart <olivier.goffart@sixtyfps.io>
+    Copyright (c) 2020 Simon Hausmann <simon.hausmann@sixtyfps.io>
+
+    SPDX-License-Identifier: GPL-3.0-only
+    This file is also available under commercial licensing terms.
+    Please contact info@sixtyfps.io for more information.
+LICENSE END */
+//! This pass turns the anchor_* properties into bindings for the x, y, width and height properties
+
+use std::rc::Rc;
+
+use crate::diagnostics::BuildDiagnostics;
+use crate::expression_tree::{Expression, ExpressionSpanned, NamedReference, Unit};
+use crate::langtype::Type;
+use crate::object_tree::*;
+
+/// The names of the properties involved in anchoring along one axis
+struct Axis {
+    pos: &'static str,
+    size: &'static str,
+    start: &'static str,
+    end: &'static str,
+    center: &'static str,
+}
+
+const HORIZONTAL: Axis = Axis {
+    pos: "x",
+    size: "width",
+    start: "anchor_left",
+    end: "anchor_right",
+    center: "anchor_horizontal_center",
+};
+
+const VERTICAL: Axis = Axis {
+    pos: "y",
+    size: "height",
+    start: "anchor_top",
+    end: "anchor_bottom",
+    center: "anchor_vertical_center",
+};
+
+pub fn lower_anchors(component: &Rc<Component>, diag: &mut BuildDiagnostics) {
+    recurse_elem(&component.root_element, &None, &mut |elem, parent: &Option<ElementRc>| {
+        for axis in &[HORIZONTAL, VERTICAL] {
+            lower_anchors_on_axis(elem, parent.as_ref(), axis, diag);
+        }
+        Some(elem.clone())
+    });
+}
+
+fn lower_anchors_on_axis(
+    elem: &ElementRc,
+    parent: Option<&ElementRc>,
+    axis: &Axis,
+    diag: &mut BuildDiagnostics,
+) {
+    let binding = |name: &str| elem.borrow().bindings.get(name).cloned();
+    let anchors: Vec<(&str, ExpressionSpanned)> = [axis.start, axis.end, axis.center]
+        .iter()
+        .filter_map(|name| binding(name).map(|b| (*name, b)))
+        .collect();
+    if anchors.is_empty() {
+        return;
+    }
+
+    let parent = match parent {
+        Some(parent) if !is_layout(parent) => parent,
+        Some(_) => {
+            for (name, b) in &anchors {
+                diag.push_error(
+                    format!("{} cannot be used on an element placed in a layout", name),
+                    b,
+                );
+            }
+            return;
+        }
+        None => {
+            for (name, b) in &anchors {
+                diag.push_error(format!("{} cannot be used on the root element", name), b);
+            }
+            return;
+        }
+    };
+
+    let mut has_error = false;
+    let mut conflict = |name: &str, other: &str, b: &ExpressionSpanned| {
+        diag.push_error(format!("Cannot specify both {} and {}", name, other), b);
+        has_error = true;
+    };
+    let (start, end, center) = (binding(axis.start), binding(axis.end), binding(axis.center));
+    if let Some(b) = &center {
+        if start.is_some() {
+            conflict(axis.center, axis.start, b);
+        }
+        if end.is_some() {
+            conflict(axis.center, axis.end, b);
+        }
+    }
+    for (name, b) in &anchors {
+        if elem.borrow().bindings.contains_key(axis.pos) {
+            conflict(name, axis.pos, b);
+        }
+    }
+    if let (Some(_), Some(b)) = (&start, &end) {
+        if elem.borrow().bindings.contains_key(axis.size) {
+            conflict(axis.end, axis.size, b);
+        }
+    }
+    if has_error {
+        return;
+    }
+
+    let prop = |e: &ElementRc, name: &str| {
+        Box::new(Expression::PropertyReference(NamedReference::new(e, name)))
+    };
+    let binary = |lhs: Box<Expression>, op: char, rhs: Box<Expression>| {
+        Box::new(Expression::BinaryExpression { lhs, rhs, op })
+    };
+    let free_space = || binary(prop(parent, axis.size), '-', prop(elem, axis.size));
+
+    let (pos, size, span) = match (start, end, center) {
+        (Some(start), None, None) => (prop(elem, axis.start), None, start.span),
+        (None, Some(end), None) => {
+            (binary(free_space(), '-', prop(elem, axis.end)), None, end.span)
+        }
+        (Some(_), Some(end), None) => (
+            prop(elem, axis.start),
+            Some(binary(
+                binary(prop(parent, axis.size), '-', prop(elem, axis.start)),
+                '-',
+                prop(elem, axis.end),
+            )),
+            end.span,
+        ),
+        (None, None, Some(center)) => (
+            binary(
+                binary(free_space(), '/', Box::new(Expression::NumberLiteral(2., Unit::None))),
+                '+',
+                prop(elem, axis.center),
+            ),
+            None,
+            center.span,
+        ),
+        _ => return,
+    };
+
+    let mut elem = elem.borrow_mut();
+    elem.bindings
+        .insert(axis.pos.into(), ExpressionSpanned { expression: *pos, span: span.clone() });
+    if let Some(size) = size {
+        elem.bindings.insert(axis.size.into(), ExpressionSpanned { expression: *size, span });
+    }
+}
+
+/// Returns true if the children of this element are placed by a layout
+fn is_layout(elem: &ElementRc) -> bool {
+    if let Type::Builtin(be) = &elem.borrow().base_type {
+        matches!(
+            be.native_class.class_name.as_str(),
+            "GridLayout"
+                | "Row"
+                | "HorizontalLayout"
+                | "VerticalLayout"
+                | "FlowLayout"
+                | "StackLayout"
+                | "PathLayout"
+        )
+    } else {
+        false
+    }
+}
@@ -14,6 +14,7 @@ use crate::langtype::Type;
 use crate::layout::*;
 use crate::object_tree::*;
 use crate::{diagnostics::BuildDiagnostics, typeloader::TypeLoader};
+use std::cell::RefCell;
 use std::rc::Rc;
 
 fn lower_grid_layout(
@@ -88,20 +89,80 @@ fn lower_box_layout(
     diag: &mut BuildDiagnostics,
 ) -> Option<Layout> {
     let is_horizontal = layout_element.borrow().base_type.to_string() == "HorizontalLayout";
-    let mut layout = BoxLayout {
+    let layout = BoxLayout {
         is_horizontal,
-        elems: Default::default(),
+        elems: create_layout_items(
+            component,
+            layout_element,
+            collected_children,
+            style_metrics,
+            diag,
+        ),
         geometry: LayoutGeometry::new(rect, &layout_element, style_metrics),
     };
-    let layout_children = std::mem::take(&mut layout_element.borrow_mut().children);
-    for layout_child in layout_children {
-        if let Some(item) =
-            create_layout_item(&layout_child, component, collected_children, style_metrics, diag)
-        {
-            layout.elems.push(item)
+    if !layout.elems.is_empty() {
+        Some(layout.into())
+    } else {
+        None
+    }
+}
+
+fn lower_flow_layout(
+    component: &Rc<Component>,
+    rect: LayoutRect,
+    layout_element: &ElementRc,
+    collected_children: &mut Vec<ElementRc>,
+    style_metrics: &Option<Rc<Component>>,
+    diag: &mut BuildDiagnostics,
+) -> Option<Layout> {
+    let layout = FlowLayout {
+        elems: create_layout_items(
+            component,
+            layout_element,
+            collected_children,
+            style_metrics,
+            diag,
+        ),
+        geometry: LayoutGeometry::new(rect, &layout_element, style_metrics),
+    };
+    if !layout.elems.is_empty() {
+        Some(layout.into())
+    } else {
+        None
+    }
+}
+
+fn lower_stack_layout(
+    component: &Rc<Component>,
+    rect: LayoutRect,
+    layout_element: &ElementRc,
+    collected_children: &mut Vec<ElementRc>,
+    style_metrics: &Option<Rc<Component>>,
+    diag: &mut BuildDiagnostics,
+) -> Option<Layout> {
+    let mut elems = Vec::new();
+    // The children were put in pages by create_stack_pages: the pages stay in the tree, with the
+    // items of their child inside
+    let pages = std::mem::take(&mut layout_element.borrow_mut().children);
+    for page in pages {
+        let mut page_children = Vec::new();
+        for child in std::mem::take(&mut page.borrow_mut().children) {
+            elems.extend(create_layout_item(
+                &child,
+                component,
+                &mut page_children,
+                style_metrics,
+                diag,
+            ));
         }
+        page.borrow_mut().children = page_children;
+        collected_children.push(page);
     }
     component.optimized_elements.borrow_mut().push(layout_element.clone());
+    let mut geometry = LayoutGeometry::new(rect, &layout_element, style_metrics);
+    // The cells are on top of each other, so there is no spacing between them
+    geometry.spacing = None;
+    let layout = StackLayout { elems, geometry };
     if !layout.elems.is_empty() {
         Some(layout.into())
     } else {
@@ -109,6 +170,25 @@ fn lower_box_layout(
     }
 }
 
+/// Create the LayoutItem for each child of a layout that places its children in order
+fn create_layout_items(
+    component: &Rc<Component>,
+    layout_element: &ElementRc,
+    collected_children: &mut Vec<ElementRc>,
+    style_metrics: &Option<Rc<Component>>,
+    diag: &mut BuildDiagnostics,
+) -> Vec<LayoutItem> {
+    let layout_children = std::mem::take(&mut layout_element.borrow_mut().children);
+    let items = layout_children
+        .iter()
+        .filter_map(|layout_child| {
+            create_layout_item(layout_child, component, collected_children, style_metrics, diag)
+        })
+        .collect();
+    component.optimized_elements.borrow_mut().push(layout_element.clone());
+    items
+}
+
 fn lower_path_layout(
     component: &Rc<Component>,
     rect: LayoutRect,
@@ -170,6 +250,8 @@ fn layout_parse_function(
             "GridLayout" => Some(&lower_grid_layout),
             "HorizontalLayout" => Some(&lower_box_layout),
             "VerticalLayout" => Some(&lower_box_layout),
+            "FlowLayout" => Some(&lower_flow_layout),
+            "StackLayout" => Some(&lower_stack_layout),
             "PathLayout" => Some(&lower_path_layout),
             _ => None,
         }
@@ -248,9 +330,60 @@ pub async fn lower_layouts<'a>(
             &mut build_diags_to_ignore,
         )
         .await;
+    let stack_page_type = type_loader.global_type_registry.borrow().stack_page_type.clone();
+    create_stack_pages(component, &stack_page_type, diag);
     lower_layouts_impl(component, &style_metrics, diag);
 }
 
+/// Put each child of the StackLayouts in a StackPage element, which only shows its children
+/// when the `current_index` of the layout is the index of the child.
+fn create_stack_pages(
+    component: &Rc<Component>,
+    stack_page_type: &Type,
+    diag: &mut BuildDiagnostics,
+) {
+    recurse_elem_including_sub_components(&component.root_element, &(), &mut |elem, _| {
+        let is_stack_layout = if let Type::Builtin(be) = &elem.borrow().base_type {
+            be.native_class.class_name == "StackLayout"
+        } else {
+            false
+        };
+        if !is_stack_layout {
+            return;
+        }
+        let children = std::mem::take(&mut elem.borrow_mut().children);
+        let pages = children
+            .into_iter()
+            .enumerate()
+            .map(|(index, child)| {
+                if child.borrow().repeated.as_ref().map_or(false, |r| !r.is_conditional_element) {
+                    diag.push_error(
+                        "'for' is not supported in a StackLayout".into(),
+                        &*child.borrow(),
+                    );
+                }
+                let visible = Expression::BinaryExpression {
+                    lhs: Box::new(Expression::PropertyReference(NamedReference::new(
+                        elem,
+                        "current_index",
+                    ))),
+                    rhs: Box::new(Expression::NumberLiteral(index as f64, Unit::None)),
+                    op: '=',
+                };
+                Rc::new(RefCell::new(Element {
+                    id: format!("{}_page{}", elem.borrow().id, index),
+                    base_type: stack_page_type.clone(),
+                    bindings: std::iter::once(("visible".to_owned(), visible.into())).collect(),
+                    children: vec![child],
+                    enclosing_component: elem.borrow().enclosing_component.clone(),
+                    ..Default::default()
+                }))
+            })
+            .collect();
+        elem.borrow_mut().children = pages;
+    });
+}
+
 pub fn lower_layouts_impl(
     component: &Rc<Component>,
     style_metrics: &Option<Rc<Component>>,
@@ -327,6 +460,13 @@ fn create_layout_item(
     } else {
         item_element.borrow_mut().child_of_layout = true;
         collected_children.push(item_element.clone());
+        let geometry_element = match &item_element.borrow().base_type {
+            Type::Component(base) if item_element.borrow().repeated.is_some() => {
+                base.root_element.clone()
+            }
+            _ => item_element.clone(),
+        };
+        animate_layout_geometry(&geometry_element);
         let element = item_element.clone();
         let layout = {
             let mut layouts = lower_element_layout(component, &element, style_metrics, diag);
@@ -340,6 +480,41 @@ fn create_layout_item(
     }
 }
 
+/// When the x, y, width or height of an element placed by a layout have an animation but no
+/// binding, the layout sets another property to which they are bound, so that the change is animated.
+fn animate_layout_geometry(elem: &ElementRc) {
+    let animated_property = |prop: &str| {
+        let e = elem.borrow();
+        if !e.property_animations.contains_key(prop)
+            || e.bindings.contains_key(prop)
+            || e.lookup_property(prop) != Type::Length
+        {
+            return None;
+        }
+        drop(e);
+        let mut name = format!("layout_{}", prop);
+        while elem.borrow().lookup_property(&name) != Type::Invalid {
+            name += "_";
+        }
+        elem.borrow_mut().property_declarations.insert(
+            name.clone(),
+            PropertyDeclaration { property_type: Type::Length, ..Default::default() },
+        );
+        let nr = NamedReference::new(elem, &name);
+        elem.borrow_mut()
+            .bindings
+            .insert(prop.into(), Expression::PropertyReference(nr.clone()).into());
+        Some(nr)
+    };
+    let geometry = LayoutRect {
+        x_reference: animated_property("x"),
+        y_reference: animated_property("y"),
+        width_reference: animated_property("width"),
+        height_reference: animated_property("height"),
+    };
+    elem.borrow_mut().animated_layout_geometry = geometry;
+}
+
 impl GridLayout {
     fn add_element(
         &mut self,
@@ -347,31 +347,10 @@ impl Expression {
                     }
                 };
 
-                let absolute_source_path = {
-                    let path = std::path::Path::new(&s);
-
-                    if path.is_absolute() || s.starts_with("http://") || s.starts_with("https://") {
-                        s
-                    } else {
-                        let path = node
-                            .source_file
-                            .unwrap_or_default()
-                            .parent()
-                            .map(|b| b.join(path))
-                            .unwrap_or_else(|| path.to_owned());
-                        if path.is_absolute() {
-                            path.to_string_lossy().to_string()
-                        } else {
-                            std::env::current_dir()
-                                .map(|b| b.join(&path))
-                                .unwrap_or(path)
-                                .to_string_lossy()
-                                .to_string()
-                        }
-                    }
-                };
-
-                Expression::ResourceReference { absolute_source_path }
+                let source_file = node.source_file.as_ref().map(|f| f.as_path());
+                Expression::ResourceReference {
+                    absolute_source_path: absolute_source_path(s, source_file),
+                }
             }
             Some(x) => {
                 ctx.diag.push_error(format!("Unknown bang keyword `{}`", x), &node);
@@ -1075,13 +1054,59 @@ fn test_parse_color_literal() {
     assert_eq!(parse_color_literal("#1234567890"), None);
 }
 
+/// Resolves the path of a resource relative to the directory of the file that references it.
+/// URLs and absolute paths are returned unchanged.
+pub fn absolute_source_path(s: String, source_file: Option<&std::path::Path>) -> String {
+    let path = std::path::Path::new(&s);
+
+    if path.is_absolute() || s.starts_with("http://") || s.starts_with("https://") {
+        s
+    } else {
+        let path = source_file
+            .and_then(|f| f.parent())
+            .map(|b| b.join(path))
+            .unwrap_or_else(|| path.to_owned());
+        if path.is_absolute() {
+            path.to_string_lossy().to_string()
+        } else {
+            std::env::current_dir()
+                .map(|b| b.join(&path))
+                .unwrap_or(path)
+                .to_string_lossy()
+                .to_string()
+        }
+    }
+}
+
 fn unescape_string(string: &str) -> Option<String> {
     if !string.starts_with('"') || !string.ends_with('"') {
         return None;
     }
     let string = &string[1..(string.len() - 1)];
-    // TODO: remove slashes
-    return Some(string.into());
+    let mut result = String::with_capacity(string.len());
+    let mut chars = string.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        result.push(match chars.next()? {
+            'n' => '\n',
+            't' => '\t',
+            '"' => '"',
+            '\\' => '\\',
+            _ => return None,
+        });
+    }
+    Some(result)
+}
+
+#[test]
+fn test_unescape_string() {
+    assert_eq!(unescape_string(r#""hello""#), Some("hello".into()));
+    assert_eq!(unescape_string(r#""a\nb""#), Some("a\nb".into()));
+    assert_eq!(unescape_string(r#""\"\\\t""#), Some("\"\\\t".into()));
+    assert_eq!(unescape_string(r#""\x""#), None);
 }
 
 fn parse_number_literal(s: String) -> Result<Expression, String> {
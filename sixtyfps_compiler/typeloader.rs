@@ -95,16 +95,19 @@ impl ImportedName {
     pub fn extract_imported_names(
         import: &syntax_nodes::ImportSpecifier,
     ) -> impl Iterator<Item = ImportedName> {
-        import.ImportIdentifierList().ImportIdentifier().map(|importident| {
-            let external_name = importident.ExternalName().text().to_string().trim().to_string();
-
-            let internal_name = match importident.InternalName() {
-                Some(name_ident) => name_ident.text().to_string().trim().to_string(),
-                None => external_name.clone(),
-            };
+        import.ImportIdentifierList().into_iter().flat_map(|l| l.ImportIdentifier()).map(
+            |importident| {
+                let external_name =
+                    importident.ExternalName().text().to_string().trim().to_string();
+
+                let internal_name = match importident.InternalName() {
+                    Some(name_ident) => name_ident.text().to_string().trim().to_string(),
+                    None => external_name.clone(),
+                };
 
-            ImportedName { internal_name, external_name }
-        })
+                ImportedName { internal_name, external_name }
+            },
+        )
     }
 }
 
@@ -362,6 +365,11 @@ impl<'a> TypeLoader<'a> {
             })
     }
 
+    /// Returns the font files imported by all the loaded dependencies.
+    pub fn all_custom_fonts(&self) -> impl Iterator<Item = &String> {
+        self.all_documents.docs.values().flat_map(|doc| doc.custom_fonts.iter())
+    }
+
     async fn collect_dependencies(
         &mut self,
         doc: &syntax_nodes::Document,
@@ -372,6 +380,10 @@ impl<'a> TypeLoader<'a> {
         let mut dependencies = DependenciesByFile::new();
 
         for import in doc.ImportSpecifier() {
+            if import.ImportIdentifierList().is_none() {
+                // `import "font.ttf";` is handled by Document::from_node
+                continue;
+            }
             let import_uri = import.child_token(SyntaxKind::StringLiteral).expect(
                 "Internal error: missing import uri literal, this is a parsing/grammar bug",
             );
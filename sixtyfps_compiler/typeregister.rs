@@ -14,6 +14,20 @@ use crate::expression_tree::{BuiltinFunction, Expression};
 use crate::langtype::{Enumeration, Type};
 use crate::object_tree::Component;
 
+thread_local! {
+    static LAYOUT_DIRECTION_TYPE: Type = Type::Enumeration(Rc::new(Enumeration {
+        name: "LayoutDirection".to_owned(),
+        values: vec!["ltr".to_owned(), "rtl".to_owned()],
+        default_value: 0,
+    }));
+}
+
+/// The `LayoutDirection` enumeration, which is also the type of the reserved `layout_direction`
+/// property
+pub fn layout_direction_type() -> Type {
+    LAYOUT_DIRECTION_TYPE.with(|ty| ty.clone())
+}
+
 /// reserved property injected in every item
 pub fn reserved_property(name: &str) -> Type {
     for (p, t) in [
@@ -25,6 +39,8 @@ pub fn reserved_property(name: &str) -> Type {
         ("minimum_height", Type::Length),
         ("maximum_width", Type::Length),
         ("maximum_height", Type::Length),
+        ("preferred_width", Type::Length),
+        ("preferred_height", Type::Length),
         ("padding", Type::Length),
         ("padding_left", Type::Length),
         ("padding_right", Type::Length),
@@ -32,6 +48,14 @@ pub fn reserved_property(name: &str) -> Type {
         ("padding_bottom", Type::Length),
         ("horizontal_stretch", Type::Float32),
         ("vertical_stretch", Type::Float32),
+        ("aspect_ratio", Type::Float32),
+        ("anchor_left", Type::Length),
+        ("anchor_right", Type::Length),
+        ("anchor_top", Type::Length),
+        ("anchor_bottom", Type::Length),
+        ("anchor_horizontal_center", Type::Length),
+        ("anchor_vertical_center", Type::Length),
+        ("layout_direction", layout_direction_type()),
         ("clip", Type::Bool),
         ("opacity", Type::Float32),
         ("visible", Type::Bool),
@@ -57,6 +81,8 @@ pub struct TypeRegister {
     types: HashMap<String, Type>,
     supported_property_animation_types: HashSet<String>,
     pub(crate) property_animation_type: Type,
+    /// The element in which the children of a StackLayout are put
+    pub(crate) stack_page_type: Type,
     /// Map from a context restricted type to the list of contexts (parent type) it is allowed in. This is
     /// used to construct helpful error messages, such as "Row can only be within a GridLayout element".
     context_restricted_types: HashMap<String, HashSet<String>>,
@@ -101,10 +127,16 @@ impl TypeRegister {
 
         declare_enum("TextHorizontalAlignment", &["align_left", "align_center", "align_right"]);
         declare_enum("TextVerticalAlignment", &["align_top", "align_center", "align_bottom"]);
+        declare_enum("TextWrap", &["no_wrap", "word_wrap"]);
+        declare_enum("TextOverflow", &["clip", "elide"]);
+        declare_enum("InputType", &["text", "password", "number"]);
+        declare_enum("BorderStyle", &["solid", "dashed", "dotted"]);
+        declare_enum("ImageFit", &["fill", "contain", "cover", "none"]);
         declare_enum(
             "LayoutAlignment",
             &["stretch", "center", "start", "end", "space_between", "space_around"],
         );
+        register.insert_type_with_name(layout_direction_type(), "LayoutDirection".to_owned());
 
         register.supported_property_animation_types.insert(Type::Float32.to_string());
         register.supported_property_animation_types.insert(Type::Int32.to_string());
@@ -131,6 +163,14 @@ impl TypeRegister {
                     "focus".into(),
                     Expression::BuiltinFunctionReference(BuiltinFunction::SetFocusItem),
                 );
+                for name in &["copy", "cut", "paste", "select_all", "undo", "redo"] {
+                    let function = BuiltinFunction::ItemMemberFunction(name.to_string());
+                    Rc::get_mut(b).unwrap().properties.insert(name.to_string(), function.ty());
+                    Rc::get_mut(b)
+                        .unwrap()
+                        .member_functions
+                        .insert(name.to_string(), Expression::BuiltinFunctionReference(function));
+                }
             }
             _ => unreachable!(),
         };
@@ -0,0 +1,215 @@
+/* LICENSE BEGIN
+    This file is part of the SixtyFPS Project -- https://sixtyfps.io
+    Copyright (c) 2020 Olivier Goffart <olivier.goffart@sixtyfps.io>
+    Copyright (c) 2020 Simon Hausmann <simon.hausmann@sixtyfps.io>
+
+    SPDX-License-Identifier: GPL-3.0-only
+    This file is also available under commercial licensing terms.
+    Please contact info@sixtyfps.io for more information.
+LICENSE END */
+/*!
+Decoding and playback of animated images (GIF and APNG).
+
+The frames are decoded once and shared between the `Image` item, which only needs the delays to
+decide which frame to show, and the rendering backend, which uploads the frames.
+*/
+
+use crate::graphics::{Resource, ResourceCacheKey};
+use crate::items::Image;
+use core::cell::RefCell;
+use core::pin::Pin;
+use core::time::Duration;
+use instant::Instant;
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+
+/// Frames with a shorter delay are shown for DEFAULT_FRAME_DELAY instead. Browsers do the
+/// same, and many GIF files rely on it.
+const MINIMUM_FRAME_DELAY: Duration = Duration::from_millis(10);
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(100);
+
+/// A single frame of an animated image.
+pub struct AnimatedImageFrame {
+    /// The fully composed frame, with the size of the image.
+    pub image: image::RgbaImage,
+    /// How long this frame is shown before advancing to the next one.
+    pub delay: Duration,
+}
+
+/// The decoded frames of an animated image.
+pub struct AnimatedImage {
+    /// The frames, in the order they are played.
+    pub frames: Vec<AnimatedImageFrame>,
+}
+
+thread_local! {
+    static ANIMATED_IMAGE_CACHE: RefCell<HashMap<ResourceCacheKey, Option<Weak<AnimatedImage>>>> =
+        Default::default();
+}
+
+/// Decodes all the frames of the image referenced by the resource. Returns None if the resource
+/// is not an animated image, i.e. if it is not a GIF or APNG with more than one frame.
+/// The result is cached as long as a reference to the image is kept.
+pub fn load_animated_image(resource: &Resource) -> Option<Rc<AnimatedImage>> {
+    let key = resource.cache_key()?;
+
+    if let Some(cached) = ANIMATED_IMAGE_CACHE.with(|cache| {
+        cache.borrow().get(&key).map(|entry| entry.as_ref().map(|weak| weak.upgrade()))
+    }) {
+        match cached {
+            // Known not to be animated
+            None => return None,
+            Some(Some(animation)) => return Some(animation),
+            // Dropped since, decode it again
+            Some(None) => {}
+        }
+    }
+
+    let animation = match resource {
+        #[cfg(not(target_arch = "wasm32"))]
+        Resource::AbsoluteFilePath(path) => {
+            std::fs::read(path.as_str()).ok().and_then(|data| decode_frames(&data))
+        }
+        Resource::EmbeddedData(data) => decode_frames(data.as_slice()),
+        _ => None,
+    }
+    .map(Rc::new);
+
+    ANIMATED_IMAGE_CACHE.with(|cache| {
+        cache.borrow_mut().insert(key, animation.as_ref().map(Rc::downgrade));
+    });
+
+    animation
+}
+
+fn decode_frames(data: &[u8]) -> Option<AnimatedImage> {
+    use image::AnimationDecoder;
+
+    let frames = match image::guess_format(data).ok()? {
+        image::ImageFormat::Gif => image::codecs::gif::GifDecoder::new(std::io::Cursor::new(data))
+            .ok()?
+            .into_frames()
+            .collect_frames()
+            .ok()?,
+        image::ImageFormat::Png => {
+            let decoder = image::codecs::png::PngDecoder::new(std::io::Cursor::new(data)).ok()?;
+            if !decoder.is_apng() {
+                return None;
+            }
+            decoder.apng().into_frames().collect_frames().ok()?
+        }
+        _ => return None,
+    };
+
+    if frames.len() < 2 {
+        return None;
+    }
+
+    Some(AnimatedImage {
+        frames: frames
+            .into_iter()
+            .map(|frame| {
+                let delay: Duration = frame.delay().into();
+                let delay = if delay < MINIMUM_FRAME_DELAY { DEFAULT_FRAME_DELAY } else { delay };
+                AnimatedImageFrame { image: frame.into_buffer(), delay }
+            })
+            .collect(),
+    })
+}
+
+#[derive(Default)]
+struct AnimatedImageDataInner {
+    source: Resource,
+    animation: Option<Rc<AnimatedImage>>,
+    /// When the current frame was first shown, None if the animation is not running.
+    frame_shown_at: Option<Instant>,
+}
+
+/// The playback state of an `Image` item.
+#[derive(Default)]
+pub struct AnimatedImageData {
+    inner: RefCell<AnimatedImageDataInner>,
+    tick_callback: RefCell<Option<Rc<dyn Fn(Instant)>>>,
+}
+
+impl AnimatedImageData {
+    /// Registers the callback that advances the frames with the animation driver. It is called
+    /// for every animation tick as long as it is kept alive by this object.
+    pub fn set_tick_callback(&self, callback: Rc<dyn Fn(Instant)>) {
+        crate::animations::CURRENT_ANIMATION_DRIVER
+            .with(|driver| driver.register_tick_callback(&callback));
+        *self.tick_callback.borrow_mut() = Some(callback);
+    }
+
+    /// Returns the index of the frame to render, or None if the image is not animated.
+    ///
+    /// This does not change the state of the image, the frames are advanced by [`Self::advance`]
+    /// on the next animation tick, which is requested if the source changed or if the animation
+    /// needs to be started.
+    pub fn current_frame(&self, image: Pin<&Image>) -> Option<u32> {
+        let source = Image::FIELD_OFFSETS.source.apply_pin(image).get();
+        let playing = Image::FIELD_OFFSETS.playing.apply_pin(image).get();
+        let current_frame = Image::FIELD_OFFSETS.current_frame.apply_pin(image).get();
+        let inner = self.inner.borrow();
+        let source_changed = inner.source != source;
+        if source_changed
+            || (playing && inner.animation.is_some() && inner.frame_shown_at.is_none())
+        {
+            crate::animations::CURRENT_ANIMATION_DRIVER
+                .with(|driver| driver.set_has_active_animations());
+        }
+        if source_changed {
+            return None;
+        }
+        let frame_count = inner.animation.as_ref()?.frames.len();
+        Some((current_frame.max(0) as usize % frame_count) as u32)
+    }
+
+    /// Updates the frame_count property if the source changed, and advances to the next frame if
+    /// the delay of the current one has elapsed at the given tick.
+    pub fn advance(&self, image: Pin<&Image>, now: Instant) {
+        let source = Image::FIELD_OFFSETS.source.apply_pin(image).get();
+        let mut inner = self.inner.borrow_mut();
+        if inner.source != source {
+            inner.animation = load_animated_image(&source);
+            inner.frame_shown_at = None;
+            let frame_count = match (&inner.animation, &source) {
+                (Some(animation), _) => animation.frames.len() as i32,
+                (None, Resource::None) => 0,
+                (None, _) => 1,
+            };
+            inner.source = source;
+            Image::FIELD_OFFSETS.frame_count.apply_pin(image).set(frame_count);
+        }
+
+        let animation = match inner.animation.clone() {
+            Some(animation) => animation,
+            None => return,
+        };
+
+        if !Image::FIELD_OFFSETS.playing.apply_pin(image).get() {
+            inner.frame_shown_at = None;
+            return;
+        }
+
+        let frame_count = animation.frames.len();
+        let current_frame_property = Image::FIELD_OFFSETS.current_frame.apply_pin(image);
+        let mut current_frame = current_frame_property.get().max(0) as usize % frame_count;
+        let shown_at = *inner.frame_shown_at.get_or_insert(now);
+        let next_frame_at = shown_at + animation.frames[current_frame].delay;
+        if now >= next_frame_at {
+            current_frame = (current_frame + 1) % frame_count;
+            current_frame_property.set(current_frame as i32);
+            // Keep the pace of the animation, unless there was no tick for a while.
+            inner.frame_shown_at =
+                Some(if now - next_frame_at < animation.frames[current_frame].delay {
+                    next_frame_at
+                } else {
+                    now
+                });
+        }
+
+        crate::animations::CURRENT_ANIMATION_DRIVER
+            .with(|driver| driver.set_has_active_animations());
+    }
+}
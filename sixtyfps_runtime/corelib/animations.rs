@@ -9,7 +9,8 @@
 LICENSE END */
 #![warn(missing_docs)]
 
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
 
 /// The representation of an easing curve, for animations
 #[repr(C, u32)]
@@ -34,6 +35,8 @@ pub struct AnimationDriver {
     active_animations: Cell<bool>,
     global_instant: core::pin::Pin<Box<crate::Property<instant::Instant>>>,
     initial_instant: instant::Instant,
+    /// Callbacks run for every new tick, for animations that are not expressed with a binding.
+    tick_callbacks: RefCell<Vec<Weak<dyn Fn(instant::Instant)>>>,
 }
 
 impl Default for AnimationDriver {
@@ -42,6 +45,7 @@ impl Default for AnimationDriver {
             active_animations: Cell::default(),
             global_instant: Box::pin(crate::Property::new(instant::Instant::now())),
             initial_instant: instant::Instant::now(),
+            tick_callbacks: Default::default(),
         }
     }
 }
@@ -52,6 +56,22 @@ impl AnimationDriver {
     pub fn update_animations(&self, new_tick: instant::Instant) {
         self.active_animations.set(false);
         self.global_instant.as_ref().set(new_tick);
+        let callbacks: Vec<_> = {
+            let mut tick_callbacks = self.tick_callbacks.borrow_mut();
+            tick_callbacks.retain(|callback| callback.strong_count() > 0);
+            tick_callbacks.iter().filter_map(Weak::upgrade).collect()
+        };
+        for callback in callbacks {
+            callback(new_tick);
+        }
+    }
+
+    /// Registers a callback that is called with the new tick every time the animations are updated.
+    /// The driver only keeps a weak reference: the callback is unregistered when it is dropped.
+    /// The callback must call set_has_active_animations if it needs to be called again.
+    pub fn register_tick_callback(&self, callback: &Rc<dyn Fn(instant::Instant)>) {
+        self.tick_callbacks.borrow_mut().push(Rc::downgrade(callback));
+        self.set_has_active_animations();
     }
 
     /// Returns true if there are any active or ready animations. This is used by the windowing system to determine
@@ -0,0 +1,86 @@
+/* LICENSE BEGIN
+    This file is part of the SixtyFPS Project -- https://sixtyfps.io
+    Copyright (c) 2020 Olivier Goffart <olivier.goffart@sixtyfps.io>
+    Copyright (c) 2020 Simon Hausmann <simon.hausmann@sixtyfps.io>
+
+    SPDX-License-Identifier: GPL-3.0-only
+    This file is also available under commercial licensing terms.
+    Please contact info@sixtyfps.io for more information.
+LICENSE END */
+#![warn(missing_docs)]
+/*!
+    This module contains the [Clipboard] trait through which the text editing items
+    access the clipboard of a window, as well as the implementations provided by the run-time.
+*/
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A clipboard that can hold text. Each [`GenericWindow`][`crate::eventloop::GenericWindow`]
+/// provides one, the backends typically give access to the clipboard of the windowing system.
+pub trait Clipboard {
+    /// Replaces the content of the clipboard with the given text.
+    fn set_text(&self, text: &str);
+    /// Returns the text contained in the clipboard, or None if it doesn't contain text.
+    fn text(&self) -> Option<String>;
+}
+
+/// A clipboard that only exists within the process. This is useful for testing, as it
+/// doesn't touch the clipboard of the system.
+#[derive(Default)]
+pub struct InProcessClipboard {
+    text: RefCell<Option<String>>,
+}
+
+impl Clipboard for InProcessClipboard {
+    fn set_text(&self, text: &str) {
+        *self.text.borrow_mut() = Some(text.into());
+    }
+
+    fn text(&self) -> Option<String> {
+        self.text.borrow().clone()
+    }
+}
+
+/// The clipboard of the system, accessed through the copypasta crate. The connection to the
+/// system clipboard is only established when it is first used. When the system clipboard is not
+/// available, it behaves like an [`InProcessClipboard`].
+#[derive(Default)]
+pub struct SystemClipboard {
+    context: RefCell<Option<Option<copypasta::ClipboardContext>>>,
+    fallback: InProcessClipboard,
+}
+
+impl SystemClipboard {
+    fn with_context<R>(&self, f: impl FnOnce(&mut copypasta::ClipboardContext) -> R) -> Option<R> {
+        let mut context = self.context.borrow_mut();
+        context.get_or_insert_with(|| copypasta::ClipboardContext::new().ok()).as_mut().map(f)
+    }
+}
+
+impl Clipboard for SystemClipboard {
+    fn set_text(&self, text: &str) {
+        use copypasta::ClipboardProvider;
+        let stored = self.with_context(|context| context.set_contents(text.into()).is_ok());
+        if stored != Some(true) {
+            self.fallback.set_text(text);
+        }
+    }
+
+    fn text(&self) -> Option<String> {
+        use copypasta::ClipboardProvider;
+        match self.with_context(|context| context.get_contents().ok()) {
+            Some(text) => text,
+            None => self.fallback.text(),
+        }
+    }
+}
+
+thread_local! {
+    static SYSTEM_CLIPBOARD: Rc<SystemClipboard> = Default::default();
+}
+
+/// Returns the clipboard of the system, shared by all the windows of the thread.
+pub fn system_clipboard() -> Rc<dyn Clipboard> {
+    SYSTEM_CLIPBOARD.with(|clipboard| clipboard.clone())
+}
@@ -80,6 +80,11 @@ pub trait GenericWindow {
     fn scale_factor(&self) -> f32;
     /// Sets an overriding scale factor for the window. This is typically only used for testing.
     fn set_scale_factor(&self, factor: f32);
+    /// Returns the direction in which the layouts and the texts of the window are placed, unless
+    /// an element sets its own `layout_direction`.
+    fn layout_direction(&self) -> crate::layout::LayoutDirection;
+    /// Sets the direction in which the layouts and the texts of the window are placed.
+    fn set_layout_direction(&self, direction: crate::layout::LayoutDirection);
     /// Sets the size of the window to the specified `width`. This method is typically called in response to receiving a
     /// window resize event from the windowing system.
     fn set_width(&self, width: f32);
@@ -114,6 +119,12 @@ pub trait GenericWindow {
     /// Sets the focus on the window to true or false, depending on the have_focus argument.
     /// This results in WindowFocusReceived and WindowFocusLost events.
     fn set_focus(self: Rc<Self>, have_focus: bool);
+
+    /// Returns the clipboard that the text editing items of this window copy to and paste from.
+    fn clipboard(&self) -> Rc<dyn crate::clipboard::Clipboard>;
+    /// Replaces the clipboard of the window. Backends use this to give access to the clipboard
+    /// of their windowing system, and tests to use an [`crate::clipboard::InProcessClipboard`].
+    fn set_clipboard(&self, clipboard: Rc<dyn crate::clipboard::Clipboard>);
 }
 
 /// The ComponentWindow is the (rust) facing public type that can render the items
@@ -149,6 +160,18 @@ impl ComponentWindow {
         self.0.set_scale_factor(factor)
     }
 
+    /// Returns the direction in which the layouts and the texts of the window are placed.
+    pub fn layout_direction(&self) -> crate::layout::LayoutDirection {
+        self.0.layout_direction()
+    }
+
+    /// Sets the direction in which the layouts and the texts of the window are placed. Use
+    /// [`LayoutDirection::from_locale`](crate::layout::LayoutDirection::from_locale) to follow
+    /// the language of the application.
+    pub fn set_layout_direction(&self, direction: crate::layout::LayoutDirection) {
+        self.0.set_layout_direction(direction)
+    }
+
     /// This function is called by the generated code when a component and therefore its tree of items are destroyed. The
     /// implementation typically uses this to free the underlying graphics resources cached via [RenderingCache][`crate::graphics::RenderingCache`].
     pub fn free_graphics_resources(
@@ -191,6 +214,17 @@ impl ComponentWindow {
         self.0.clone().set_focus_item(component, item.as_ptr())
     }
 
+    /// Returns the clipboard of the window.
+    pub fn clipboard(&self) -> Rc<dyn crate::clipboard::Clipboard> {
+        self.0.clipboard()
+    }
+
+    /// Replaces the clipboard of the window, for example with an
+    /// [`InProcessClipboard`][`crate::clipboard::InProcessClipboard`] in tests.
+    pub fn set_clipboard(&self, clipboard: Rc<dyn crate::clipboard::Clipboard>) {
+        self.0.set_clipboard(clipboard)
+    }
+
     /// Associates this window with the specified component, for future event handling, etc.
     pub fn set_component(&self, component: &ComponentRc) {
         self.0.clone().set_component(component)
@@ -583,6 +617,25 @@ pub mod ffi {
         window.set_scale_factor(value)
     }
 
+    /// Returns the layout direction of the window.
+    #[no_mangle]
+    pub unsafe extern "C" fn sixtyfps_component_window_get_layout_direction(
+        handle: *const ComponentWindowOpaque,
+    ) -> crate::layout::LayoutDirection {
+        let window = &*(handle as *const ComponentWindow);
+        window.layout_direction()
+    }
+
+    /// Sets the layout direction of the window.
+    #[no_mangle]
+    pub unsafe extern "C" fn sixtyfps_component_window_set_layout_direction(
+        handle: *const ComponentWindowOpaque,
+        direction: crate::layout::LayoutDirection,
+    ) {
+        let window = &*(handle as *const ComponentWindow);
+        window.set_layout_direction(direction)
+    }
+
     /// Sets the window scale factor, merely for testing purposes.
     #[no_mangle]
     pub unsafe extern "C" fn sixtyfps_component_window_free_graphics_resources(
@@ -7,7 +7,55 @@
     This file is also available under commercial licensing terms.
     Please contact info@sixtyfps.io for more information.
 LICENSE END */
-use std::hash::Hash;
+use std::cell::RefCell;
+
+thread_local! {
+    /// The address of the font data registered with [`register_font_from_memory`].
+    static REGISTERED_FONT_DATA: RefCell<Vec<usize>> = Default::default();
+}
+
+/// Loading fonts from files is not possible in the browser.
+pub(crate) fn register_font_from_path(
+    path: &std::path::Path,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    Err(format!("Cannot load font {} from the file system", path.display()).into())
+}
+
+/// Adds a FontFace for the font to the document, with the family name, weight and style read
+/// from the font, returns false if it was already registered.
+pub(crate) fn register_font_from_memory(
+    data: &'static [u8],
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let already_registered = REGISTERED_FONT_DATA.with(|registered| {
+        let mut registered = registered.borrow_mut();
+        let address = data.as_ptr() as usize;
+        if registered.contains(&address) {
+            true
+        } else {
+            registered.push(address);
+            false
+        }
+    });
+    if already_registered {
+        return Ok(false);
+    }
+
+    let face = ttf_parser::Face::from_slice(data, 0)?;
+    let family = face.family_name().ok_or("The font has no family name")?;
+    let mut descriptors = web_sys::FontFaceDescriptors::new();
+    descriptors.weight(&face.weight().to_number().to_string());
+    descriptors.style(if face.is_italic() { "italic" } else { "normal" });
+
+    let js_error = |e: wasm_bindgen::JsValue| format!("{:?}", e);
+    let font_face = web_sys::FontFace::new_with_u8_array_and_descriptors(
+        &family,
+        &mut data.to_vec(),
+        &descriptors,
+    )
+    .map_err(js_error)?;
+    web_sys::window().unwrap().document().unwrap().fonts().add(&font_face).map_err(js_error)?;
+    Ok(true)
+}
 
 #[derive(Clone)]
 struct GlyphMetrics {
@@ -16,22 +64,23 @@ struct GlyphMetrics {
 
 pub struct Font {
     pub pixel_size: f32,
-    font_family: String,
+    /// The value of the canvas font property
+    css_font: String,
     text_canvas: web_sys::HtmlCanvasElement,
     canvas_context: web_sys::CanvasRenderingContext2d,
 }
 
 impl Font {
-    pub fn text_width(&self, text: &str) -> f32 {
+    pub fn text_width(&self, text: &str, letter_spacing: f32) -> f32 {
         let text_metrics = self.canvas_context.measure_text(text).unwrap();
-        text_metrics.width() as _
+        text_metrics.width() as f32 + letter_spacing * text.chars().count() as f32
     }
 
-    pub fn text_offset_for_x_position(&self, text: &str, x: f32) -> usize {
+    pub fn text_offset_for_x_position(&self, text: &str, x: f32, letter_spacing: f32) -> usize {
         // This is pretty cruel ...
         let mut last_width = 0.;
         for offset in text.char_indices().map(|(offset, _)| offset) {
-            let new_width = self.text_width(&text[0..offset]);
+            let new_width = self.text_width(&text[0..offset], letter_spacing);
 
             if new_width > last_width {
                 let advance = new_width - last_width;
@@ -46,25 +95,31 @@ impl Font {
         text.len()
     }
 
+    /// Returns the x position of the cursor placed at the given byte offset in the text.
+    pub fn cursor_x_position(&self, text: &str, offset: usize, letter_spacing: f32) -> f32 {
+        self.text_width(&text[..offset], letter_spacing)
+    }
+
     pub fn height(&self) -> f32 {
         self.pixel_size
     }
 
-    pub fn render_text<'a>(&'a self, text: &str) -> &'a web_sys::HtmlCanvasElement {
-        let text_metrics = self.canvas_context.measure_text(text).unwrap();
+    pub fn render_text<'a>(
+        &'a self,
+        text: &str,
+        letter_spacing: f32,
+    ) -> &'a web_sys::HtmlCanvasElement {
+        let text_width = self.text_width(text, letter_spacing);
 
-        self.text_canvas.set_width(text_metrics.width() as _);
+        self.text_canvas.set_width(text_width as _);
         self.text_canvas.set_height(self.pixel_size as _);
-        self.text_canvas
-            .style()
-            .set_property("width", &format!("{}px", text_metrics.width()))
-            .unwrap();
+        self.text_canvas.style().set_property("width", &format!("{}px", text_width)).unwrap();
         self.text_canvas.style().set_property("height", &format!("{}px", self.pixel_size)).unwrap();
 
         // Re-apply after resize :(
-        self.canvas_context.set_font(&format!("{}px \"{}\"", self.pixel_size, self.font_family));
+        self.canvas_context.set_font(&self.css_font);
 
-        self.canvas_context.set_text_align("center");
+        self.canvas_context.set_text_align("left");
         self.canvas_context.set_text_baseline("middle");
         self.canvas_context.set_fill_style(&wasm_bindgen::JsValue::from_str("transparent"));
         self.canvas_context.fill_rect(
@@ -75,38 +130,40 @@ impl Font {
         );
 
         self.canvas_context.set_fill_style(&wasm_bindgen::JsValue::from_str("rgb(0, 0, 0)"));
-        self.canvas_context
-            .fill_text(
-                text,
-                (self.text_canvas.width() / 2) as _,
-                (self.text_canvas.height() / 2) as _,
-            )
-            .unwrap();
+        let y = (self.text_canvas.height() / 2) as f64;
+        if letter_spacing == 0. {
+            self.canvas_context.fill_text(text, 0., y).unwrap();
+        } else {
+            // The canvas has no letter spacing, so draw one character at a time
+            let mut x = 0.;
+            let mut utf8 = [0; 4];
+            for ch in text.chars() {
+                let ch = ch.encode_utf8(&mut utf8);
+                self.canvas_context.fill_text(ch, x as f64, y).unwrap();
+                x += self.text_width(ch, letter_spacing);
+            }
+        }
 
         &self.text_canvas
     }
 }
 
-#[derive(Clone)]
-pub struct FontHandle(String);
-
-impl Hash for FontHandle {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.0.hash(state)
-    }
-}
-
-impl PartialEq for FontHandle {
-    fn eq(&self, other: &Self) -> bool {
-        self.0.eq(&other.0)
-    }
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct FontHandle {
+    family: String,
+    weight: i32,
+    italic: bool,
 }
 
-impl Eq for FontHandle {}
-
 impl FontHandle {
     pub fn load(&self, pixel_size: f32) -> Result<Font, ()> {
-        let font_family = &self.0;
+        let css_font = format!(
+            "{} {} {}px \"{}\"",
+            if self.italic { "italic" } else { "normal" },
+            self.weight,
+            pixel_size,
+            self.family
+        );
 
         let text_canvas = web_sys::window()
             .unwrap()
@@ -125,12 +182,12 @@ impl FontHandle {
             .dyn_into::<web_sys::CanvasRenderingContext2d>()
             .unwrap();
 
-        canvas_context.set_font(&format!("{}px \"{}\"", pixel_size, font_family));
+        canvas_context.set_font(&css_font);
 
-        Ok(Font { pixel_size, font_family: font_family.clone(), text_canvas, canvas_context })
+        Ok(Font { pixel_size, css_font, text_canvas, canvas_context })
     }
 
-    pub fn new_from_match(family: &str) -> Self {
-        Self(family.to_owned())
+    pub fn new_from_match(family: &str, weight: i32, italic: bool) -> Self {
+        Self { family: family.to_owned(), weight, italic }
     }
 }
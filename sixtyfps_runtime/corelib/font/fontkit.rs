@@ -14,11 +14,204 @@ use pathfinder_geometry::{
 };
 use std::collections::HashMap;
 use std::hash::Hash;
+use std::ops::Range;
+use std::sync::Arc;
 use std::{cell::RefCell, rc::Rc};
 
-#[derive(Clone)]
-pub struct GlyphMetrics {
+/// Families that are tried, in order, for characters the requested font has no glyph for. The
+/// ones that are not installed are skipped.
+const FALLBACK_FAMILIES: &[&str] = &[
+    "Noto Sans",
+    "DejaVu Sans",
+    "Segoe UI",
+    "Helvetica Neue",
+    "Noto Sans Arabic",
+    "Noto Naskh Arabic",
+    "Noto Sans Hebrew",
+    "Noto Sans Devanagari",
+    "Noto Sans Bengali",
+    "Noto Sans Thai",
+    "Noto Sans CJK SC",
+    "Noto Sans CJK JP",
+    "Microsoft YaHei",
+    "PingFang SC",
+    "Hiragino Sans",
+    "Arial Unicode MS",
+    "Noto Color Emoji",
+    "Segoe UI Emoji",
+    "Apple Color Emoji",
+    "Segoe UI Symbol",
+    "Noto Sans Symbols",
+    "Noto Sans Symbols2",
+];
+
+/// The shaping results are cached per font, up to this number of strings.
+const SHAPED_TEXT_CACHE_SIZE: usize = 1000;
+
+thread_local! {
+    static FALLBACK_FONTS: Vec<(FontHandle, font_kit::font::Font)> = load_fallback_fonts();
+    static FALLBACK_FONT_FOR_CHAR: RefCell<HashMap<char, Option<FontHandle>>> = Default::default();
+    /// The fonts registered with [`register_font_from_path`] and [`register_font_from_memory`].
+    static APPLICATION_FONTS: RefCell<Vec<(FontHandle, font_kit::font::Font)>> = Default::default();
+}
+
+/// Registers all the fonts of the file, returns false if it was already registered.
+pub(crate) fn register_font_from_path(
+    path: &std::path::Path,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let file_type = font_kit::font::Font::analyze_path(path)?;
+    register_font_handles(file_type, |font_index| {
+        font_kit::handle::Handle::from_path(path.to_owned(), font_index)
+    })
+}
+
+/// Registers all the fonts of the font file data, returns false if it was already registered.
+pub(crate) fn register_font_from_memory(
+    data: &'static [u8],
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let bytes = Arc::new(data.to_vec());
+    let file_type = font_kit::font::Font::analyze_bytes(bytes.clone())?;
+    register_font_handles(file_type, |font_index| {
+        font_kit::handle::Handle::from_memory(bytes.clone(), font_index)
+    })
+}
+
+fn register_font_handles(
+    file_type: font_kit::file_type::FileType,
+    handle_for_index: impl Fn(u32) -> font_kit::handle::Handle,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let font_count = match file_type {
+        font_kit::file_type::FileType::Single => 1,
+        font_kit::file_type::FileType::Collection(count) => count,
+    };
+    APPLICATION_FONTS.with(|fonts| {
+        let mut fonts = fonts.borrow_mut();
+        let mut registered_new_font = false;
+        for font_index in 0..font_count {
+            let handle: FontHandle = handle_for_index(font_index).into();
+            if fonts.iter().any(|(existing, _)| *existing == handle) {
+                continue;
+            }
+            let font = handle.0.load()?;
+            fonts.push((handle, font));
+            registered_new_font = true;
+        }
+        if registered_new_font {
+            // The new fonts may cover characters that previously needed a system font.
+            FALLBACK_FONT_FOR_CHAR.with(|cache| cache.borrow_mut().clear());
+        }
+        Ok(registered_new_font)
+    })
+}
+
+/// Returns the registered application font of the family that matches the properties best.
+fn application_font_match(
+    family: &str,
+    properties: &font_kit::properties::Properties,
+) -> Option<FontHandle> {
+    APPLICATION_FONTS.with(|fonts| {
+        let fonts = fonts.borrow();
+        let candidates: Vec<_> = fonts
+            .iter()
+            .filter(|(_, font)| font.family_name().eq_ignore_ascii_case(family))
+            .collect();
+        let candidate_properties: Vec<_> =
+            candidates.iter().map(|(_, font)| font.properties()).collect();
+        let index = font_kit::matching::find_best_match(&candidate_properties, properties).ok()?;
+        Some(candidates[index].0.clone())
+    })
+}
+
+fn load_fallback_fonts() -> Vec<(FontHandle, font_kit::font::Font)> {
+    let source = font_kit::source::SystemSource::new();
+    FALLBACK_FAMILIES
+        .iter()
+        .filter_map(|family| {
+            let handle = source
+                .select_best_match(
+                    &[font_kit::family_name::FamilyName::Title(family.to_string())],
+                    &font_kit::properties::Properties::new(),
+                )
+                .ok()?;
+            let font = handle.load().ok()?;
+            Some((handle.into(), font))
+        })
+        .collect()
+}
+
+/// Returns the first application font, or else the first font of the fallback chain, that has a
+/// glyph for the character. If none has one, ask the platform, using `font` as the reference.
+fn fallback_font_for_char(font: &font_kit::font::Font, ch: char) -> Option<FontHandle> {
+    FALLBACK_FONT_FOR_CHAR.with(|cache| {
+        cache
+            .borrow_mut()
+            .entry(ch)
+            .or_insert_with(|| {
+                APPLICATION_FONTS
+                    .with(|fonts| {
+                        fonts
+                            .borrow()
+                            .iter()
+                            .find(|(_, font)| font.glyph_for_char(ch).is_some())
+                            .map(|(handle, _)| handle.clone())
+                    })
+                    .or_else(|| {
+                        FALLBACK_FONTS.with(|fonts| {
+                            fonts
+                                .iter()
+                                .find(|(_, font)| font.glyph_for_char(ch).is_some())
+                                .map(|(handle, _)| handle.clone())
+                        })
+                    })
+                    .or_else(|| {
+                        let mut utf8 = [0; 4];
+                        font.get_fallbacks(ch.encode_utf8(&mut utf8), "")
+                            .fonts
+                            .into_iter()
+                            .find(|fallback| fallback.font.glyph_for_char(ch).is_some())
+                            .and_then(|fallback| fallback.font.handle())
+                            .map(FontHandle::from)
+                    })
+            })
+            .clone()
+    })
+}
+
+/// Zero width joiners, variation selectors, combining marks and emoji modifiers are rendered by
+/// the font of the character they modify.
+fn continues_cluster(ch: char) -> bool {
+    matches!(ch,
+        '\u{200c}'..='\u{200d}'
+        | '\u{fe00}'..='\u{fe0f}'
+        | '\u{0300}'..='\u{036f}'
+        | '\u{1f3fb}'..='\u{1f3ff}'
+        | '\u{e0020}'..='\u{e007f}'
+    )
+}
+
+/// A glyph of a shaped text, see [`Font::shape_text`].
+#[derive(Clone, Debug)]
+pub struct ShapedGlyph {
+    /// The font of the glyph: 0 for the font that shaped the text, otherwise a fallback font.
+    pub font_index: usize,
+    pub glyph_id: u32,
+    /// The position of the pen when the glyph is drawn, relative to the start of the text.
+    pub x: f32,
+    /// The offset of the glyph from the pen position.
+    pub x_offset: f32,
+    /// The offset of the glyph from the baseline, positive values move the glyph up.
+    pub y_offset: f32,
     pub advance: f32,
+    /// The byte range in the text of the characters this glyph represents.
+    pub cluster: Range<usize>,
+    /// True if the glyph is part of a right-to-left run.
+    pub is_rtl: bool,
+}
+
+/// The glyphs of a text, in visual order from left to right.
+pub struct ShapedText {
+    pub glyphs: Vec<ShapedGlyph>,
+    pub width: f32,
 }
 
 pub struct Font {
@@ -27,59 +220,242 @@ pub struct Font {
     // Handle::Memory, which is much slower to hash.
     handle: Rc<font_kit::handle::Handle>,
     font: font_kit::font::Font,
+    font_data: Arc<Vec<u8>>,
+    font_index: u32,
     metrics: font_kit::metrics::Metrics,
-    glyph_metrics_cache: RefCell<HashMap<u32, GlyphMetrics>>,
+    /// The fallback fonts used so far, at the same pixel size.
+    fallbacks: RefCell<Vec<Rc<Font>>>,
+    shaped_text_cache: RefCell<HashMap<String, Rc<ShapedText>>>,
 }
 
 impl Font {
-    pub fn string_to_glyphs<'a>(
-        &'a self,
-        text: &'a str,
-    ) -> impl Iterator<Item = (usize, char, u32)> + 'a {
-        text.char_indices().map(move |(offset, ch)| {
-            (
-                offset,
-                ch,
-                self.font.glyph_for_char(ch).unwrap_or_else(|| {
-                    self.font
-                        .glyph_for_char('\u{FFFD}')
-                        .unwrap_or_else(|| self.font.glyph_for_char('?').unwrap())
-                }),
-            )
-        })
+    /// Shapes the text: lays out the glyphs of each bidi run in visual order, applying the
+    /// shaping rules of the script (ligatures, contextual forms, marks, ...) and picking a
+    /// fallback font for the characters this font has no glyph for. The letter spacing is added
+    /// after each cluster.
+    pub fn shape_text(&self, text: &str, letter_spacing: f32) -> Rc<ShapedText> {
+        let shaped_text = self.shape_text_cached(text);
+        if letter_spacing == 0. {
+            return shaped_text;
+        }
+
+        let mut glyphs = shaped_text.glyphs.clone();
+        let mut x = 0.;
+        for index in 0..glyphs.len() {
+            let ends_cluster =
+                glyphs.get(index + 1).map_or(true, |next| next.cluster != glyphs[index].cluster);
+            let glyph = &mut glyphs[index];
+            if ends_cluster {
+                glyph.advance += letter_spacing;
+            }
+            glyph.x = x;
+            x += glyph.advance;
+        }
+        Rc::new(ShapedText { glyphs, width: x })
     }
 
-    pub fn text_width(&self, text: &str) -> f32 {
-        self.string_to_glyphs(text)
-            .map(|(_, _, glyph)| self.glyph_metrics(glyph))
-            .fold(0., |width, glyph| width + glyph.advance)
+    fn shape_text_cached(&self, text: &str) -> Rc<ShapedText> {
+        if let Some(shaped_text) = self.shaped_text_cache.borrow().get(text) {
+            return shaped_text.clone();
+        }
+
+        let shaped_text = Rc::new(self.shape_text_uncached(text));
+
+        let mut cache = self.shaped_text_cache.borrow_mut();
+        if cache.len() >= SHAPED_TEXT_CACHE_SIZE {
+            cache.clear();
+        }
+        cache.insert(text.to_owned(), shaped_text.clone());
+        shaped_text
     }
 
-    pub fn text_offset_for_x_position<'a>(&self, text: &'a str, x: f32) -> usize {
-        let mut current_x = 0.;
-        // This assumes a 1:1 mapping between glyphs and characters right now -- this is wrong.
-        for (offset, _, glyph_id) in self.string_to_glyphs(text) {
-            let metrics = self.glyph_metrics(glyph_id);
+    fn shape_text_uncached(&self, text: &str) -> ShapedText {
+        let mut glyphs = Vec::new();
+        let mut x = 0.;
 
-            if current_x + metrics.advance / 2. >= x {
-                return offset;
+        let bidi_info = unicode_bidi::BidiInfo::new(text, None);
+        for paragraph in &bidi_info.paragraphs {
+            let (levels, runs) = bidi_info.visual_runs(paragraph, paragraph.range.clone());
+            for run in runs {
+                let is_rtl = levels[run.start].is_rtl();
+                let mut font_runs = self.font_runs(text, run);
+                if is_rtl {
+                    font_runs.reverse();
+                }
+                for (font_index, range) in font_runs {
+                    self.with_font(font_index, |font| {
+                        font.shape_run(text, range, is_rtl, font_index, &mut x, &mut glyphs)
+                    });
+                }
             }
-            current_x += metrics.advance;
         }
 
-        text.len()
+        ShapedText { glyphs, width: x }
     }
 
-    pub fn glyph_metrics(&self, glyph: u32) -> GlyphMetrics {
-        self.glyph_metrics_cache
-            .borrow_mut()
-            .entry(glyph)
-            .or_insert_with(|| {
-                let advance =
-                    self.font.advance(glyph).unwrap().x() * self.font_units_to_pixel_size();
-                GlyphMetrics { advance }
-            })
-            .clone()
+    /// Splits the range of text in runs of characters that are rendered with the same font.
+    fn font_runs(&self, text: &str, range: Range<usize>) -> Vec<(usize, Range<usize>)> {
+        let mut runs: Vec<(usize, Range<usize>)> = Vec::new();
+        for (offset, ch) in text[range.clone()].char_indices() {
+            let start = range.start + offset;
+            let end = start + ch.len_utf8();
+            let font_index = match runs.last() {
+                Some((font_index, _)) if continues_cluster(ch) => *font_index,
+                _ => self.font_index_for_char(ch),
+            };
+            match runs.last_mut() {
+                Some((last_font_index, last_range)) if *last_font_index == font_index => {
+                    last_range.end = end
+                }
+                _ => runs.push((font_index, start..end)),
+            }
+        }
+        runs
+    }
+
+    fn font_index_for_char(&self, ch: char) -> usize {
+        if ch.is_whitespace() || ch.is_control() || self.font.glyph_for_char(ch).is_some() {
+            return 0;
+        }
+
+        let mut fallbacks = self.fallbacks.borrow_mut();
+        if let Some(index) =
+            fallbacks.iter().position(|fallback| fallback.font.glyph_for_char(ch).is_some())
+        {
+            return index + 1;
+        }
+
+        match fallback_font_for_char(&self.font, ch) {
+            Some(handle) => match fallbacks.iter().position(|fallback| fallback.handle() == handle)
+            {
+                Some(index) => index + 1,
+                None => match handle.load(self.pixel_size) {
+                    Ok(font) => {
+                        fallbacks.push(Rc::new(font));
+                        fallbacks.len()
+                    }
+                    Err(_) => 0,
+                },
+            },
+            // Render the replacement glyph of this font
+            None => 0,
+        }
+    }
+
+    fn with_font<R>(&self, font_index: usize, f: impl FnOnce(&Font) -> R) -> R {
+        if font_index == 0 {
+            f(self)
+        } else {
+            f(&self.fallbacks.borrow()[font_index - 1])
+        }
+    }
+
+    fn shape_run(
+        &self,
+        text: &str,
+        range: Range<usize>,
+        is_rtl: bool,
+        font_index: usize,
+        x: &mut f32,
+        glyphs: &mut Vec<ShapedGlyph>,
+    ) {
+        let face = match rustybuzz::Face::from_slice(&self.font_data, self.font_index) {
+            Some(face) => face,
+            None => return,
+        };
+
+        let mut buffer = rustybuzz::UnicodeBuffer::new();
+        buffer.push_str(&text[range.clone()]);
+        buffer.set_direction(if is_rtl {
+            rustybuzz::Direction::RightToLeft
+        } else {
+            rustybuzz::Direction::LeftToRight
+        });
+        buffer.guess_segment_properties();
+
+        let output = rustybuzz::shape(&face, &[], buffer);
+        let scale = self.font_units_to_pixel_size();
+
+        // The clusters are byte offsets in the run, a cluster ends where the next one starts.
+        let mut cluster_starts =
+            output.glyph_infos().iter().map(|info| info.cluster as usize).collect::<Vec<_>>();
+        cluster_starts.sort_unstable();
+        cluster_starts.dedup();
+
+        for (info, position) in output.glyph_infos().iter().zip(output.glyph_positions()) {
+            let cluster_start = info.cluster as usize;
+            let cluster_end = cluster_starts
+                .iter()
+                .find(|start| **start > cluster_start)
+                .map_or(range.len(), |start| *start);
+            let advance = position.x_advance as f32 * scale;
+            glyphs.push(ShapedGlyph {
+                font_index,
+                glyph_id: info.codepoint,
+                x: *x,
+                x_offset: position.x_offset as f32 * scale,
+                y_offset: position.y_offset as f32 * scale,
+                advance,
+                cluster: range.start + cluster_start..range.start + cluster_end,
+                is_rtl,
+            });
+            *x += advance;
+        }
+    }
+
+    pub fn text_width(&self, text: &str, letter_spacing: f32) -> f32 {
+        self.shape_text(text, letter_spacing).width
+    }
+
+    /// Returns the byte offset in the text of the cursor position closest to `x`.
+    pub fn text_offset_for_x_position<'a>(
+        &self,
+        text: &'a str,
+        x: f32,
+        letter_spacing: f32,
+    ) -> usize {
+        let shaped_text = self.shape_text(text, letter_spacing);
+        for glyph in &shaped_text.glyphs {
+            if x < glyph.x + glyph.advance {
+                let left_half = x < glyph.x + glyph.advance / 2.;
+                return if left_half != glyph.is_rtl {
+                    glyph.cluster.start
+                } else {
+                    glyph.cluster.end
+                };
+            }
+        }
+
+        match shaped_text.glyphs.last() {
+            Some(glyph) if glyph.is_rtl => glyph.cluster.start,
+            _ => text.len(),
+        }
+    }
+
+    /// Returns the x position of the cursor placed at the given byte offset in the text.
+    pub fn cursor_x_position(&self, text: &str, offset: usize, letter_spacing: f32) -> f32 {
+        let shaped_text = self.shape_text(text, letter_spacing);
+
+        for glyph in &shaped_text.glyphs {
+            if glyph.cluster.start <= offset && offset < glyph.cluster.end {
+                // Within a ligature, assume all characters have the same width.
+                let fraction = (offset - glyph.cluster.start) as f32 / glyph.cluster.len() as f32;
+                return if glyph.is_rtl {
+                    glyph.x + glyph.advance * (1. - fraction)
+                } else {
+                    glyph.x + glyph.advance * fraction
+                };
+            }
+        }
+
+        // At the end of a run
+        for glyph in &shaped_text.glyphs {
+            if glyph.cluster.end == offset {
+                return if glyph.is_rtl { glyph.x } else { glyph.x + glyph.advance };
+            }
+        }
+
+        shaped_text.width
     }
 
     #[inline]
@@ -99,7 +475,17 @@ impl Font {
         (self.metrics.ascent - self.metrics.descent + 1.) * self.font_units_to_pixel_size()
     }
 
-    pub fn rasterize_glyph(&self, glyph_id: u32) -> (f32, f32, ImageBuffer<Rgba<u8>, Vec<u8>>) {
+    /// Rasterizes a glyph of [`ShapedText`], returning the offset of the image from the pen
+    /// position and the image.
+    pub fn rasterize_glyph(
+        &self,
+        font_index: usize,
+        glyph_id: u32,
+    ) -> (f32, f32, ImageBuffer<Rgba<u8>, Vec<u8>>) {
+        self.with_font(font_index, |font| font.rasterize_own_glyph(glyph_id))
+    }
+
+    fn rasterize_own_glyph(&self, glyph_id: u32) -> (f32, f32, ImageBuffer<Rgba<u8>, Vec<u8>>) {
         let hinting = font_kit::hinting::HintingOptions::None;
         let raster_opts = font_kit::canvas::RasterizationOptions::GrayscaleAa;
 
@@ -190,26 +576,46 @@ impl FontHandle {
     pub fn load(&self, pixel_size: f32) -> Result<Font, font_kit::error::FontLoadingError> {
         let font = self.0.load()?;
         let metrics = font.metrics();
+        let font_data = font
+            .copy_font_data()
+            .ok_or(font_kit::error::FontLoadingError::NoSuchFontInCollection)?;
+        let font_index = match self.0.as_ref() {
+            font_kit::handle::Handle::Path { font_index, .. }
+            | font_kit::handle::Handle::Memory { font_index, .. } => *font_index,
+        };
         Ok(Font {
             pixel_size,
             font,
             handle: self.0.clone(),
+            font_data,
+            font_index,
             metrics,
-            glyph_metrics_cache: Default::default(),
+            fallbacks: Default::default(),
+            shaped_text_cache: Default::default(),
         })
     }
 
-    pub fn new_from_match(family: &str) -> Self {
+    pub fn new_from_match(family: &str, weight: i32, italic: bool) -> Self {
         let family_name = if family.len() == 0 {
             font_kit::family_name::FamilyName::SansSerif
         } else {
             font_kit::family_name::FamilyName::Title(family.into())
         };
 
+        let mut properties = font_kit::properties::Properties::new();
+        properties.weight = font_kit::properties::Weight(weight as f32);
+        if italic {
+            properties.style = font_kit::properties::Style::Italic;
+        }
+
+        if let Some(handle) = application_font_match(family, &properties) {
+            return handle;
+        }
+
         font_kit::source::SystemSource::new()
             .select_best_match(
                 &[family_name, font_kit::family_name::FamilyName::SansSerif],
-                &font_kit::properties::Properties::new(),
+                &properties,
             )
             .unwrap()
             .into()
@@ -221,3 +627,123 @@ impl From<font_kit::handle::Handle> for FontHandle {
         Self(Rc::new(h))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_font() -> Rc<Font> {
+        crate::font::FONT_CACHE.with(|cache| {
+            cache.find_font(&crate::font::FontRequest { pixel_size: 16., ..Default::default() })
+        })
+    }
+
+    #[test]
+    fn shaping() {
+        let font = test_font();
+        let text = "Hello World";
+        let shaped_text = font.shape_text(text, 0.);
+
+        assert!(shaped_text.glyphs.iter().all(|glyph| !glyph.is_rtl && glyph.glyph_id != 0));
+        let advances: f32 = shaped_text.glyphs.iter().map(|glyph| glyph.advance).sum();
+        assert!((shaped_text.width - advances).abs() < 0.001);
+        assert_eq!(shaped_text.glyphs.first().unwrap().cluster.start, 0);
+        assert_eq!(shaped_text.glyphs.last().unwrap().cluster.end, text.len());
+
+        let mut previous_x = -1.;
+        for offset in 0..=text.len() {
+            let x = font.cursor_x_position(text, offset, 0.);
+            assert!(x > previous_x);
+            assert_eq!(font.text_offset_for_x_position(text, x + 0.1, 0.), offset);
+            previous_x = x;
+        }
+        assert_eq!(font.cursor_x_position(text, text.len(), 0.), shaped_text.width);
+    }
+
+    #[test]
+    fn letter_spacing() {
+        let font = test_font();
+        let text = "Hello";
+        let width = font.text_width(text, 0.);
+        assert!((font.text_width(text, 2.) - (width + 5. * 2.)).abs() < 0.001);
+        for offset in 0..=text.len() {
+            let x = font.cursor_x_position(text, offset, 0.);
+            let spaced_x = font.cursor_x_position(text, offset, 2.);
+            assert!((spaced_x - (x + offset as f32 * 2.)).abs() < 0.001);
+        }
+        assert_eq!(font.shape_text(text, 0.).width, width);
+
+        // The same font is used for all the letter spacings
+        let spaced_font = crate::font::FONT_CACHE.with(|cache| {
+            cache.find_font(&crate::font::FontRequest {
+                pixel_size: 16.,
+                letter_spacing: 3.,
+                ..Default::default()
+            })
+        });
+        assert!(Rc::ptr_eq(&font, &spaced_font));
+    }
+
+    #[test]
+    fn right_to_left_runs() {
+        let font = test_font();
+        let text = "abc \u{5d0}\u{5d1}\u{5d2} def";
+        let shaped_text = font.shape_text(text, 0.);
+
+        // The glyphs are in visual order, the hebrew run is reversed.
+        let clusters: Vec<_> = shaped_text
+            .glyphs
+            .iter()
+            .map(|glyph| (text[glyph.cluster.clone()].to_owned(), glyph.is_rtl))
+            .collect();
+        let expected: Vec<_> =
+            ["a", "b", "c", " ", "\u{5d2}", "\u{5d1}", "\u{5d0}", " ", "d", "e", "f"]
+                .iter()
+                .map(|cluster| (cluster.to_string(), cluster.chars().any(|ch| ch > '\u{5cf}')))
+                .collect();
+        assert_eq!(clusters, expected);
+        assert!(shaped_text.glyphs.windows(2).all(|glyphs| glyphs[0].x < glyphs[1].x));
+
+        // The cursor before the first hebrew letter is at the right of the run.
+        let alef = text.find('\u{5d0}').unwrap();
+        let gimel_glyph = &shaped_text.glyphs[4];
+        let alef_glyph = &shaped_text.glyphs[6];
+        assert_eq!(font.cursor_x_position(text, alef, 0.), alef_glyph.x + alef_glyph.advance);
+        assert!(font.cursor_x_position(text, alef + 2, 0.) < alef_glyph.x + alef_glyph.advance);
+        assert!(font.cursor_x_position(text, alef + 4, 0.) >= gimel_glyph.x);
+    }
+
+    #[test]
+    fn fallback() {
+        let font = test_font();
+        // Find a character that the font does not have, but another font of the system has.
+        let missing = ['\u{4e2d}', '\u{0915}', '\u{0e01}', '\u{10d0}', '\u{1f600}', '\u{2603}']
+            .iter()
+            .copied()
+            .find(|ch| {
+                font.font.glyph_for_char(*ch).is_none()
+                    && fallback_font_for_char(&font.font, *ch).is_some()
+            });
+        let missing = match missing {
+            Some(ch) => ch,
+            // Every character is in the font, or there is no other font to fall back to.
+            None => return,
+        };
+
+        let text = format!("a{}b", missing);
+        let shaped_text = font.shape_text(&text, 0.);
+        let font_indices: Vec<_> = shaped_text
+            .glyphs
+            .iter()
+            .map(|glyph| (glyph.font_index, glyph.cluster.clone()))
+            .collect();
+        assert_eq!(font_indices.len(), 3);
+        assert_eq!(font_indices[0], (0, 0..1));
+        assert!(font_indices[1].0 > 0);
+        assert_eq!(font_indices[1].1, 1..1 + missing.len_utf8());
+        assert_eq!(font_indices[2].0, 0);
+        assert_ne!(shaped_text.glyphs[1].glyph_id, 0);
+        assert!(shaped_text.glyphs[1].advance > 0.);
+        assert!(shaped_text.glyphs[1].x > shaped_text.glyphs[0].x);
+    }
+}
@@ -7,6 +7,7 @@
     This file is also available under commercial licensing terms.
     Please contact info@sixtyfps.io for more information.
 LICENSE END */
+use crate::items::{TextHorizontalAlignment, TextOverflow, TextVerticalAlignment, TextWrap};
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
@@ -21,6 +22,41 @@ mod canvasfont;
 #[cfg(target_arch = "wasm32")]
 pub use canvasfont::*;
 
+/// The weight of regular text, used when the requested weight is 0.
+pub const DEFAULT_FONT_WEIGHT: i32 = 400;
+
+/// Describes the font to use for rendering text.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FontRequest {
+    /// The family name, or an empty string for the default sans-serif font.
+    pub family: crate::SharedString,
+    /// The weight, between 100 (thin) and 900 (black). 0 selects [`DEFAULT_FONT_WEIGHT`].
+    pub weight: i32,
+    pub italic: bool,
+    pub pixel_size: f32,
+    /// Additional space added after each character, in pixels. It is not part of the [`Font`],
+    /// but passed to the functions that shape or measure the text.
+    pub letter_spacing: f32,
+}
+
+impl FontRequest {
+    /// Returns the requested weight, clamped to the valid range.
+    pub fn weight(&self) -> i32 {
+        if self.weight == 0 {
+            DEFAULT_FONT_WEIGHT
+        } else {
+            self.weight.max(100).min(900)
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Hash)]
+struct FontMatchKey {
+    family: String,
+    weight: i32,
+    italic: bool,
+}
+
 struct FontMatch {
     handle: FontHandle,
     fonts_per_pixel_size: Vec<Rc<Font>>,
@@ -28,32 +64,405 @@ struct FontMatch {
 
 #[derive(Default)]
 pub struct FontCache {
-    // index by family name
-    loaded_fonts: RefCell<HashMap<String, FontMatch>>,
+    // index by family name, weight and style
+    loaded_fonts: RefCell<HashMap<FontMatchKey, FontMatch>>,
 }
 
 impl FontCache {
-    pub fn find_font(&self, family: &str, pixel_size: f32) -> Rc<Font> {
-        assert_ne!(pixel_size, 0.0);
+    pub fn find_font(&self, request: &FontRequest) -> Rc<Font> {
+        assert_ne!(request.pixel_size, 0.0);
 
         let mut loaded_fonts = self.loaded_fonts.borrow_mut();
-        let font_match = loaded_fonts.entry(family.to_owned()).or_insert_with(|| FontMatch {
-            handle: FontHandle::new_from_match(family),
+        let key = FontMatchKey {
+            family: request.family.as_str().to_owned(),
+            weight: request.weight(),
+            italic: request.italic,
+        };
+        let font_match = loaded_fonts.entry(key).or_insert_with(|| FontMatch {
+            handle: FontHandle::new_from_match(&request.family, request.weight(), request.italic),
             fonts_per_pixel_size: Vec::new(),
         });
 
         font_match
             .fonts_per_pixel_size
             .iter()
-            .find_map(|font| if font.pixel_size == pixel_size { Some(font.clone()) } else { None })
+            .find_map(
+                |font| {
+                    if font.pixel_size == request.pixel_size {
+                        Some(font.clone())
+                    } else {
+                        None
+                    }
+                },
+            )
             .unwrap_or_else(|| {
-                let fnt = Rc::new(font_match.handle.load(pixel_size).unwrap());
+                let fnt = Rc::new(font_match.handle.load(request.pixel_size).unwrap());
                 font_match.fonts_per_pixel_size.push(fnt.clone());
                 fnt
             })
     }
+
+    pub fn register_application_font_from_path(
+        &self,
+        path: &std::path::Path,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if register_font_from_path(path)? {
+            // Fonts that were matched before may now resolve to the new font
+            self.loaded_fonts.borrow_mut().clear();
+        }
+        Ok(())
+    }
+
+    pub fn register_application_font_from_memory(
+        &self,
+        data: &'static [u8],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if register_font_from_memory(data)? {
+            self.loaded_fonts.borrow_mut().clear();
+        }
+        Ok(())
+    }
 }
 
 thread_local! {
     pub static FONT_CACHE: FontCache = Default::default();
 }
+
+/// Registers the fonts of the TrueType or OpenType font file (or collection) at the given path,
+/// so that their family can be used in the `font_family` property of the `Text` and `TextInput`
+/// elements. Registered fonts take precedence over the system fonts of the same family.
+///
+/// Registering the same file again does nothing.
+pub fn register_application_font_from_path(
+    path: impl AsRef<std::path::Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    FONT_CACHE.with(|cache| cache.register_application_font_from_path(path.as_ref()))
+}
+
+/// Like [`register_application_font_from_path`], for font file data embedded in the program.
+pub fn register_application_font_from_memory(
+    data: &'static [u8],
+) -> Result<(), Box<dyn std::error::Error>> {
+    FONT_CACHE.with(|cache| cache.register_application_font_from_memory(data))
+}
+
+/// Registers the font referenced by a resource. This is called by the generated code for the
+/// `import "font.ttf";` statements.
+pub fn register_application_font_from_resource(
+    resource: &crate::Resource,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match resource {
+        crate::Resource::AbsoluteFilePath(path) => {
+            register_application_font_from_path(path.as_str())
+        }
+        crate::Resource::EmbeddedData(data) => {
+            register_application_font_from_memory(data.as_slice())
+        }
+        _ => Err("Unsupported font resource".into()),
+    }
+}
+
+/// The character appended to elided lines.
+pub const ELLIPSIS: &str = "\u{2026}";
+
+/// A line of a paragraph of text, as laid out by [`TextLayout::lines`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TextLine {
+    /// The byte offset in the text where the line starts.
+    pub start: usize,
+    /// The byte offset in the text where the line ends, excluding trailing white space and
+    /// the line break.
+    pub end: usize,
+    /// True if the line was elided, in which case [`ELLIPSIS`] is rendered after its text.
+    pub elided: bool,
+    /// The x position of the line, relative to the element.
+    pub x: f32,
+    /// The y position of the top of the line, relative to the element.
+    pub y: f32,
+    /// The width of the line, including the ellipsis.
+    pub width: f32,
+}
+
+/// Breaks a paragraph of text into lines and positions them in the available space.
+///
+/// Lines are broken at explicit line breaks, and if `wrap` is [`TextWrap::word_wrap`], at the
+/// break opportunities of the Unicode line breaking algorithm so that the lines fit in `width`.
+pub struct TextLayout<'a> {
+    pub font: &'a Font,
+    pub text: &'a str,
+    /// Additional space added after each character, in pixels.
+    pub letter_spacing: f32,
+    /// The available width, None if unconstrained.
+    pub width: Option<f32>,
+    /// The available height, None if unconstrained.
+    pub height: Option<f32>,
+    pub wrap: TextWrap,
+    pub overflow: TextOverflow,
+    pub horizontal_alignment: TextHorizontalAlignment,
+    pub vertical_alignment: TextVerticalAlignment,
+}
+
+impl<'a> TextLayout<'a> {
+    /// Returns the lines of the text, elided if requested by `overflow`, with their position.
+    pub fn lines(&self) -> Vec<TextLine> {
+        let mut lines = self.break_lines(self.wrap_width());
+        let line_height = self.font.height();
+
+        if self.overflow == TextOverflow::elide {
+            if let Some(height) = self.height {
+                let max_lines = ((height / line_height).floor() as usize).max(1);
+                if lines.len() > max_lines {
+                    lines.truncate(max_lines);
+                    let last_line = lines.last_mut().unwrap();
+                    self.elide_line(last_line, self.width.unwrap_or(f32::MAX));
+                }
+            }
+            if let Some(width) = self.width {
+                for line in lines.iter_mut().filter(|line| line.width > width) {
+                    self.elide_line(line, width);
+                }
+            }
+        }
+
+        let block_width = self
+            .width
+            .unwrap_or_else(|| lines.iter().fold(0., |width, line| line.width.max(width)));
+        let block_height = lines.len() as f32 * line_height;
+        let y = match (self.vertical_alignment, self.height) {
+            (TextVerticalAlignment::align_top, _) | (_, None) => 0.,
+            (TextVerticalAlignment::align_center, Some(height)) => (height - block_height) / 2.,
+            (TextVerticalAlignment::align_bottom, Some(height)) => height - block_height,
+        };
+
+        for (index, line) in lines.iter_mut().enumerate() {
+            line.x = match self.horizontal_alignment {
+                TextHorizontalAlignment::align_left => 0.,
+                TextHorizontalAlignment::align_center => (block_width - line.width) / 2.,
+                TextHorizontalAlignment::align_right => block_width - line.width,
+            };
+            line.y = y + index as f32 * line_height;
+        }
+
+        lines
+    }
+
+    /// The minimum width needed to show the text: the width of the widest line, or of the widest
+    /// word if the text wraps. An elided text only needs space for the ellipsis.
+    pub fn min_width(&self) -> f32 {
+        if self.overflow == TextOverflow::elide && self.wrap == TextWrap::no_wrap {
+            return self.font.text_width(ELLIPSIS, self.letter_spacing);
+        }
+        let wrap_width = if self.wrap == TextWrap::word_wrap { Some(0.) } else { None };
+        self.break_lines(wrap_width).iter().fold(0., |width, line| line.width.max(width))
+    }
+
+    /// The width needed to show the text without wrapping or eliding it: the width of the widest
+    /// line.
+    pub fn preferred_width(&self) -> f32 {
+        self.break_lines(None).iter().fold(0., |width, line| line.width.max(width))
+    }
+
+    /// The height needed to show all the lines of the text, when wrapped to the available width.
+    pub fn height_for_width(&self) -> f32 {
+        self.break_lines(self.wrap_width()).len() as f32 * self.font.height()
+    }
+
+    fn wrap_width(&self) -> Option<f32> {
+        if self.wrap == TextWrap::word_wrap {
+            self.width
+        } else {
+            None
+        }
+    }
+
+    fn break_lines(&self, wrap_width: Option<f32>) -> Vec<TextLine> {
+        use unicode_linebreak::BreakOpportunity;
+
+        let mut lines = Vec::new();
+        let mut line_start = 0;
+        // The text between two break opportunities, it is measured only once.
+        let mut segment_start = 0;
+        // The width of the text between line_start and segment_start.
+        let mut line_width = 0.;
+
+        for (offset, opportunity) in unicode_linebreak::linebreaks(self.text) {
+            let segment = &self.text[segment_start..offset];
+            if let Some(wrap_width) = wrap_width {
+                if segment_start > line_start
+                    && line_width + self.font.text_width(segment.trim_end(), self.letter_spacing)
+                        > wrap_width
+                {
+                    lines.push(self.line(line_start, segment_start));
+                    line_start = segment_start;
+                    line_width = 0.;
+                }
+            }
+            if let BreakOpportunity::Mandatory = opportunity {
+                lines.push(self.line(line_start, offset));
+                line_start = offset;
+                line_width = 0.;
+            } else if wrap_width.is_some() {
+                line_width += self.font.text_width(segment, self.letter_spacing);
+            }
+            segment_start = offset;
+        }
+
+        // An explicit line break at the end starts a new, empty, line
+        if lines.is_empty()
+            || matches!(
+                self.text.chars().last(),
+                Some('\n') | Some('\r') | Some('\u{2028}') | Some('\u{2029}')
+            )
+        {
+            lines.push(self.line(self.text.len(), self.text.len()));
+        }
+
+        lines
+    }
+
+    fn line(&self, start: usize, end: usize) -> TextLine {
+        let end = start + self.text[start..end].trim_end().len();
+        TextLine {
+            start,
+            end,
+            width: self.font.text_width(&self.text[start..end], self.letter_spacing),
+            ..Default::default()
+        }
+    }
+
+    /// Shortens the line so that it fits in `available_width` with the ellipsis appended.
+    fn elide_line(&self, line: &mut TextLine, available_width: f32) {
+        let ellipsis_width = self.font.text_width(ELLIPSIS, self.letter_spacing);
+        let text = &self.text[line.start..line.end];
+        let mut end = 0;
+        for (offset, ch) in text.char_indices() {
+            let candidate_end = offset + ch.len_utf8();
+            if self.font.text_width(text[..candidate_end].trim_end(), self.letter_spacing)
+                + ellipsis_width
+                > available_width
+            {
+                break;
+            }
+            end = candidate_end;
+        }
+        let elided_text = text[..end].trim_end();
+        line.end = line.start + elided_text.len();
+        line.elided = true;
+        line.width = self.font.text_width(elided_text, self.letter_spacing) + ellipsis_width;
+    }
+}
+
+pub(crate) mod ffi {
+    use super::*;
+
+    #[no_mangle]
+    /// Registers the font file at the given path, returns false on error.
+    pub extern "C" fn sixtyfps_register_application_font_from_path(
+        path: &crate::SharedString,
+    ) -> bool {
+        register_application_font_from_path(path.as_str()).is_ok()
+    }
+
+    #[no_mangle]
+    /// Registers the font file data, which must stay valid until the end of the program.
+    /// Returns false on error.
+    pub extern "C" fn sixtyfps_register_application_font_from_data(
+        data: crate::slice::Slice<'static, u8>,
+    ) -> bool {
+        register_application_font_from_memory(data.as_slice()).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout<'a>(font: &'a Font, text: &'a str) -> TextLayout<'a> {
+        TextLayout {
+            font,
+            text,
+            letter_spacing: 0.,
+            width: None,
+            height: None,
+            wrap: TextWrap::word_wrap,
+            overflow: TextOverflow::clip,
+            horizontal_alignment: TextHorizontalAlignment::align_left,
+            vertical_alignment: TextVerticalAlignment::align_top,
+        }
+    }
+
+    fn ranges(lines: &[TextLine]) -> Vec<(usize, usize)> {
+        lines.iter().map(|line| (line.start, line.end)).collect()
+    }
+
+    fn test_font() -> Rc<Font> {
+        FONT_CACHE
+            .with(|cache| cache.find_font(&FontRequest { pixel_size: 16., ..Default::default() }))
+    }
+
+    #[test]
+    fn line_breaking() {
+        let font = test_font();
+        let text = "aaa bbb ccc\nddd";
+
+        // Only the explicit line break without a width
+        assert_eq!(ranges(&layout(&font, text).lines()), vec![(0, 11), (12, 15)]);
+
+        let width = font.text_width("aaa bbb", 0.) + 0.5;
+        let wrapped = TextLayout { width: Some(width), ..layout(&font, text) };
+        assert_eq!(ranges(&wrapped.lines()), vec![(0, 7), (8, 11), (12, 15)]);
+        assert_eq!(wrapped.height_for_width(), 3. * font.height());
+        assert!(wrapped.lines().iter().all(|line| line.width <= width));
+
+        let no_wrap = TextLayout { wrap: TextWrap::no_wrap, ..wrapped };
+        assert_eq!(ranges(&no_wrap.lines()), vec![(0, 11), (12, 15)]);
+
+        // Every word on its own line when there is no space
+        let narrow = TextLayout { width: Some(1.), ..layout(&font, text) };
+        assert_eq!(ranges(&narrow.lines()), vec![(0, 3), (4, 7), (8, 11), (12, 15)]);
+        let widest_word = ["aaa", "bbb", "ccc", "ddd"]
+            .iter()
+            .fold(0., |width, word| font.text_width(word, 0.).max(width));
+        assert_eq!(narrow.min_width(), widest_word);
+
+        // A line break at the end starts an empty line
+        assert_eq!(ranges(&layout(&font, "aaa\n").lines()), vec![(0, 3), (4, 4)]);
+        assert_eq!(ranges(&layout(&font, "").lines()), vec![(0, 0)]);
+    }
+
+    #[test]
+    fn eliding() {
+        let font = test_font();
+        let text = "aaa bbb ccc";
+        let ellipsis_width = font.text_width(ELLIPSIS, 0.);
+
+        let width = font.text_width("aaa b", 0.) + ellipsis_width + 0.5;
+        let elided = TextLayout {
+            width: Some(width),
+            wrap: TextWrap::no_wrap,
+            overflow: TextOverflow::elide,
+            ..layout(&font, text)
+        };
+        let lines = elided.lines();
+        assert_eq!(ranges(&lines), vec![(0, 5)]);
+        assert!(lines[0].elided);
+        assert!(lines[0].width <= width);
+        assert_eq!(elided.min_width(), ellipsis_width);
+
+        // The text fits: nothing is elided
+        let fits = TextLayout { width: Some(font.text_width(text, 0.) + 0.5), ..elided };
+        assert!(!fits.lines()[0].elided);
+
+        // Wrapped lines that do not fit in the height are elided on the last visible line
+        let width = font.text_width("aaa bbb", 0.) + ellipsis_width + 0.5;
+        let elided = TextLayout {
+            width: Some(width),
+            height: Some(font.height() * 1.5),
+            overflow: TextOverflow::elide,
+            ..layout(&font, text)
+        };
+        let lines = elided.lines();
+        assert_eq!(ranges(&lines), vec![(0, 7)]);
+        assert!(lines[0].elided);
+    }
+}
@@ -44,6 +44,10 @@ pub type Rect = euclid::default::Rect<f32>;
 pub type Point = euclid::default::Point2D<f32>;
 /// 2D Size
 pub type Size = euclid::default::Size2D<f32>;
+/// 2D Rectangle with integer coordinates
+pub type IntRect = euclid::default::Rect<i32>;
+/// Integer offsets from the four sides of a rectangle
+pub type IntSideOffsets = euclid::default::SideOffsets2D<i32>;
 
 /// ARGBColor stores the red, green, blue and alpha components of a color
 /// with the precision of the generic parameter T. For example if T is f32,
@@ -241,6 +245,144 @@ impl Default for Resource {
     }
 }
 
+impl Resource {
+    /// Returns true if the resource refers to a scalable vector image (SVG) that needs to be
+    /// rasterized at the size it is going to be displayed at.
+    pub fn is_svg(&self) -> bool {
+        match self {
+            Resource::AbsoluteFilePath(path) => {
+                std::path::Path::new(path.as_str()).extension().map_or(false, |ext| {
+                    ext.eq_ignore_ascii_case("svg") || ext.eq_ignore_ascii_case("svgz")
+                })
+            }
+            Resource::EmbeddedData(data) => is_svg_data(data.as_slice()),
+            Resource::None | Resource::EmbeddedRgbaImage { .. } => false,
+        }
+    }
+
+    /// Returns the key that identifies the file or the embedded data of the resource, None if it
+    /// has neither.
+    pub(crate) fn cache_key(&self) -> Option<ResourceCacheKey> {
+        match self {
+            Resource::AbsoluteFilePath(path) => {
+                Some(ResourceCacheKey::Path(path.as_str().to_owned()))
+            }
+            Resource::EmbeddedData(data) => Some(ResourceCacheKey::EmbeddedData(
+                data.as_slice().as_ptr() as usize,
+                data.as_slice().len(),
+            )),
+            Resource::None | Resource::EmbeddedRgbaImage { .. } => None,
+        }
+    }
+
+    /// Returns the size in pixels of a bitmap image, read from its header. Returns None for
+    /// vector images, which have no natural size in pixels, and when the image cannot be read.
+    /// The header of a file or embedded data is only read the first time.
+    pub fn natural_size(&self) -> Option<Size> {
+        let key = match self.cache_key() {
+            Some(key) => key,
+            None => return self.read_natural_size(),
+        };
+        if let Some(size) = NATURAL_SIZE_CACHE.with(|cache| cache.borrow().get(&key).copied()) {
+            return size;
+        }
+        let size = self.read_natural_size();
+        NATURAL_SIZE_CACHE.with(|cache| cache.borrow_mut().insert(key, size));
+        size
+    }
+
+    fn read_natural_size(&self) -> Option<Size> {
+        if self.is_svg() {
+            return None;
+        }
+        let (width, height) = match self {
+            Resource::None => return None,
+            #[cfg(not(target_arch = "wasm32"))]
+            Resource::AbsoluteFilePath(path) => image::image_dimensions(path.as_str()).ok()?,
+            #[cfg(target_arch = "wasm32")]
+            Resource::AbsoluteFilePath(_) => return None,
+            Resource::EmbeddedData(data) => {
+                image::io::Reader::new(std::io::Cursor::new(data.as_slice()))
+                    .with_guessed_format()
+                    .ok()?
+                    .into_dimensions()
+                    .ok()?
+            }
+            Resource::EmbeddedRgbaImage { width, height, .. } => (*width, *height),
+        };
+        Some(Size::new(width as f32, height as f32))
+    }
+}
+
+/// Identifies the file or the embedded data of a [`Resource`], to cache what is read from it.
+#[derive(PartialEq, Eq, Hash)]
+pub(crate) enum ResourceCacheKey {
+    Path(String),
+    /// The address and the length of the data
+    EmbeddedData(usize, usize),
+}
+
+thread_local! {
+    static NATURAL_SIZE_CACHE: RefCell<std::collections::HashMap<ResourceCacheKey, Option<Size>>> =
+        Default::default();
+}
+
+/// Sniffs the content of the data to find out if it is a SVG document. Bitmap formats start with a
+/// binary signature, while SVG documents start with an XML declaration, a comment or the svg tag itself.
+/// Compressed SVG documents are recognized by the gzip signature.
+pub fn is_svg_data(data: &[u8]) -> bool {
+    if data.starts_with(&[0x1f, 0x8b]) {
+        return true;
+    }
+    let text = &data[..data.len().min(512)];
+    let start = text.iter().position(|c| !c.is_ascii_whitespace()).unwrap_or(text.len());
+    let text = &text[start..];
+    let text = if text.starts_with(&[0xef, 0xbb, 0xbf]) { &text[3..] } else { text };
+    text.starts_with(b"<?xml") || text.starts_with(b"<svg") || text.starts_with(b"<!--")
+}
+
+/// The radius of each of the four corners of a rectangle.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[repr(C)]
+#[allow(missing_docs)]
+pub struct BorderRadii {
+    pub top_left: f32,
+    pub top_right: f32,
+    pub bottom_right: f32,
+    pub bottom_left: f32,
+}
+
+impl BorderRadii {
+    /// Creates a BorderRadii where all four corners have the same radius.
+    pub fn uniform(radius: f32) -> Self {
+        Self { top_left: radius, top_right: radius, bottom_right: radius, bottom_left: radius }
+    }
+
+    /// Returns the radius if all four corners share the same value, None otherwise.
+    pub fn as_uniform(&self) -> Option<f32> {
+        if self.top_left == self.top_right
+            && self.top_left == self.bottom_right
+            && self.top_left == self.bottom_left
+        {
+            Some(self.top_left)
+        } else {
+            None
+        }
+    }
+
+    /// Returns a copy of the radii where each radius is reduced by `inset` and clamped so that
+    /// neighbouring corners do not overlap in a rectangle of the given size.
+    pub fn adjusted(&self, inset: f32, size: Size) -> Self {
+        let clamp = |r: f32| (r - inset).max(0.).min(size.width / 2.).min(size.height / 2.);
+        Self {
+            top_left: clamp(self.top_left),
+            top_right: clamp(self.top_right),
+            bottom_right: clamp(self.bottom_right),
+            bottom_left: clamp(self.bottom_left),
+        }
+    }
+}
+
 /// The run-time library uses this enum to instruct the [GraphicsBackend] to render SixtyFPS
 /// graphics items.
 /// The different variants of this enum closely resemble the properties found in the `.60`
@@ -262,25 +404,55 @@ pub enum HighLevelRenderingPrimitive {
     NoContents,
     /// Renders a rectangle with the specified `width` and `height`, as well as a border
     /// around it. The `border_width` specifies the width to use for the border, and the
-    /// `border_radius` can be used to render a rounded rectangle.
+    /// `border_radius` can be used to render a rectangle with rounded corners. The
+    /// `border_style` selects whether the border is drawn as a solid, dashed or dotted line.
     ///
     /// Expected rendering variables:
     /// * [`RenderingVariable::Color`]: The color to fill the rectangle with.
     /// * [`RenderingVariable::Color`]: The color to use for stroking the border of the rectangle.
-    Rectangle { width: f32, height: f32, border_width: f32, border_radius: f32 },
-    /// Renders a image referenced by the specified `source`.
+    Rectangle {
+        width: f32,
+        height: f32,
+        border_width: f32,
+        border_radius: BorderRadii,
+        border_style: crate::items::BorderStyle,
+    },
+    /// Renders a image referenced by the specified `source`. If the `source_clip_rect` is not empty,
+    /// only that part of the source image is rendered. The `image_fit` determines how the image is
+    /// fitted into the scaled size. If any of the `nine_slice` offsets is not zero, the image is
+    /// instead split into nine parts, where the corners keep their size, the edges are stretched
+    /// in one direction and the center is stretched in both.
+    ///
+    /// Vector images are rasterized at the `rasterization_size`, which is in physical pixels. If
+    /// one of its dimensions is zero, the natural size of the image multiplied by the `scale_factor`
+    /// is used instead. Both fields are ignored for bitmap images.
     ///
     /// Optional rendering variables:
+    /// * [`RenderingVariable::ImageFrame`]: The frame of an animated image to render.
     /// * [`RenderingVariable::ScaledWidth`]: The image will be scaled to the specified width.
     /// * [`RenderingVariable::ScaledHeight`]: The image will be scaled to the specified height.
-    Image { source: crate::Resource },
-    /// Renders the specified `text` with a font that matches the specified family (`font_family`) and the given
-    /// pixel size (`font_size`).
+    Image {
+        source: crate::Resource,
+        source_clip_rect: IntRect,
+        image_fit: crate::items::ImageFit,
+        nine_slice: IntSideOffsets,
+        rasterization_size: Size,
+        scale_factor: f32,
+    },
+    /// Renders the specified `text` with a font that matches the `font_request`. Each of the `lines` is rendered at its position, followed by an ellipsis if
+    /// it was elided. If `clip` is set, the glyphs are clipped to a rectangle of that size.
     ///
     /// Expected rendering variables:
     /// * [`RenderingVariable::Color`]: The color to use for rendering the glyphs.
-    /// * [`RenderingVariable::TextCursor`]: Draw a text cursor.
-    Text { text: crate::SharedString, font_family: crate::SharedString, font_size: f32 },
+    /// * [`RenderingVariable::TextSelection`]: Optional, draw selected text.
+    /// * [`RenderingVariable::TextUnderline`]: Optional, underline text. May be repeated.
+    /// * [`RenderingVariable::TextCursor`]: Optional, draw a text cursor.
+    Text {
+        text: crate::SharedString,
+        font_request: crate::font::FontRequest,
+        lines: Vec<crate::font::TextLine>,
+        clip: Option<Size>,
+    },
     /// Renders a path specified by the `elements` parameter. The path will be scaled to fit into the given
     /// `width` and `height`. If the `stroke_width` is greater than zero, then path will also be outlined.
     ///
@@ -316,11 +488,17 @@ pub enum RenderingVariable {
     ScaledWidth(f32),
     /// Scale the primitive by the specified height.
     ScaledHeight(f32),
-    /// Draw a text cursor. The parameters provide the x coordiante and the width/height as (x, width, height) tuple.
-    TextCursor(f32, f32, f32),
-    /// Draw a text selection. The parameters provide the starting x coordinate, the width and the height. This variable
-    /// must be followed by two colors, foreground and background.
-    TextSelection(f32, f32, f32),
+    /// Draw a text cursor. The parameters provide the position and the size as (x, y, width, height) tuple.
+    TextCursor(f32, f32, f32, f32),
+    /// Draw a text selection. The parameters provide the rectangle of the selection as (x, y, width, height) tuple.
+    /// A selection over several lines is drawn with one variable per line. The last one must be followed by two
+    /// colors, foreground and background.
+    TextSelection(f32, f32, f32, f32),
+    /// Draw a line below text in the color of the text, such as below the text being composed with an input
+    /// method. The parameters provide the rectangle of the line as (x, y, width, height) tuple.
+    TextUnderline(f32, f32, f32, f32),
+    /// Select the frame of an animated image to render.
+    ImageFrame(u32),
 }
 
 impl RenderingVariable {
@@ -501,6 +679,7 @@ struct WindowProperties {
     scale_factor: Property<f32>,
     width: Property<f32>,
     height: Property<f32>,
+    layout_direction: Property<crate::layout::LayoutDirection>,
 }
 
 impl Default for WindowProperties {
@@ -509,6 +688,7 @@ impl Default for WindowProperties {
             scale_factor: Property::new(1.0),
             width: Property::new(800.),
             height: Property::new(600.),
+            layout_direction: Property::new(Default::default()),
         }
     }
 }
@@ -523,6 +703,9 @@ pub struct GraphicsWindow<Backend: GraphicsBackend + 'static> {
     keyboard_modifiers: std::cell::Cell<KeyboardModifiers>,
     component: std::cell::RefCell<ComponentWeak>,
     layout_listener: Pin<Rc<PropertyTracker>>,
+    clipboard: RefCell<Rc<dyn crate::clipboard::Clipboard>>,
+    /// The last position reported to the windowing system with [`Self::set_text_input_cursor_rect`]
+    input_method_position: std::cell::Cell<Option<Point>>,
 }
 
 impl<Backend: GraphicsBackend + 'static> GraphicsWindow<Backend> {
@@ -544,6 +727,8 @@ impl<Backend: GraphicsBackend + 'static> GraphicsWindow<Backend> {
             keyboard_modifiers: Default::default(),
             component: Default::default(),
             layout_listener: Rc::pin(Default::default()),
+            clipboard: RefCell::new(crate::clipboard::system_clipboard()),
+            input_method_position: Default::default(),
         })
     }
 
@@ -552,6 +737,56 @@ impl<Backend: GraphicsBackend + 'static> GraphicsWindow<Backend> {
         Some(self.map_state.borrow().as_mapped().backend.borrow().window().id())
     }
 
+    /// Tells the windowing system where the cursor of the text input with the focus is, so that
+    /// input methods can show their candidate window below it. This is called after the events
+    /// that may move the cursor or the focus.
+    fn update_input_method_position(self: &Rc<Self>) {
+        let component = match self.component.borrow().upgrade() {
+            Some(component) => component,
+            None => return,
+        };
+        let window = crate::eventloop::ComponentWindow::new(self.clone());
+        let mut cursor_rect = None;
+        crate::item_tree::visit_items(
+            ComponentRc::borrow_pin(&component),
+            crate::item_tree::TraversalOrder::FrontToBack,
+            |_, item, offset| {
+                if !crate::items::StackPage::are_children_visible(item) {
+                    return crate::item_tree::ItemVisitorResult::SkipChildren;
+                }
+                let offset = *offset + item.as_ref().geometry().origin.to_vector();
+                if let Some(text_input) = ItemRef::downcast_pin::<crate::items::TextInput>(item) {
+                    if crate::items::TextInput::FIELD_OFFSETS.has_focus.apply_pin(text_input).get()
+                    {
+                        cursor_rect = Some(text_input.cursor_rect(&window).translate(offset));
+                        return crate::item_tree::ItemVisitorResult::Abort;
+                    }
+                }
+                crate::item_tree::ItemVisitorResult::Continue(offset)
+            },
+            euclid::Vector2D::new(0., 0.),
+        );
+        if let Some(cursor_rect) = cursor_rect {
+            self.set_text_input_cursor_rect(cursor_rect);
+        }
+    }
+
+    /// Reports the position below the text cursor, in window coordinates, to the input method.
+    fn set_text_input_cursor_rect(&self, rect: Rect) {
+        let position = Point::new(rect.min_x(), rect.max_y());
+        if self.input_method_position.get() == Some(position) {
+            return;
+        }
+        if let GraphicsWindowBackendState::Mapped(window) = &*self.map_state.borrow() {
+            window
+                .backend
+                .borrow()
+                .window()
+                .set_ime_position(winit::dpi::PhysicalPosition::new(position.x, position.y));
+            self.input_method_position.set(Some(position));
+        }
+    }
+
     fn apply_geometry_constraint(&self, constraints: crate::layout::LayoutInfo) {
         match &*self.map_state.borrow() {
             GraphicsWindowBackendState::Unmapped => {}
@@ -673,13 +908,21 @@ impl<Backend: GraphicsBackend> crate::eventloop::GenericWindow for GraphicsWindo
             &crate::eventloop::ComponentWindow::new(self.clone()),
             &component,
         );
+        if what != MouseEventType::MouseMoved {
+            self.update_input_method_position();
+        }
     }
 
     fn process_key_input(self: Rc<Self>, event: &KeyEvent) {
         let component = self.component.borrow().upgrade().unwrap();
-        ComponentRc::borrow_pin(&component)
-            .as_ref()
-            .key_event(event, &crate::eventloop::ComponentWindow::new(self.clone()));
+        let component = ComponentRc::borrow_pin(&component);
+        let window = crate::eventloop::ComponentWindow::new(self.clone());
+        if crate::input::is_focus_item_hidden(component) {
+            // The page of the StackLayout that contains the focus item was hidden, so it loses the focus
+            component.as_ref().focus_event(&crate::input::FocusEvent::FocusOut, &window);
+        }
+        component.as_ref().key_event(event, &window);
+        self.update_input_method_position();
     }
 
     fn with_platform_window(&self, callback: &dyn Fn(&winit::window::Window)) {
@@ -803,6 +1046,14 @@ impl<Backend: GraphicsBackend> crate::eventloop::GenericWindow for GraphicsWindo
         self.properties.as_ref().scale_factor.set(factor);
     }
 
+    fn layout_direction(&self) -> crate::layout::LayoutDirection {
+        WindowProperties::FIELD_OFFSETS.layout_direction.apply_pin(self.properties.as_ref()).get()
+    }
+
+    fn set_layout_direction(&self, direction: crate::layout::LayoutDirection) {
+        self.properties.as_ref().layout_direction.set(direction);
+    }
+
     fn set_width(&self, width: f32) {
         self.properties.as_ref().width.set(width);
     }
@@ -863,6 +1114,7 @@ impl<Backend: GraphicsBackend> crate::eventloop::GenericWindow for GraphicsWindo
         let window = crate::eventloop::ComponentWindow::new(self.clone());
         component.as_ref().focus_event(&crate::input::FocusEvent::FocusOut, &window);
         component.as_ref().focus_event(&crate::input::FocusEvent::FocusIn(item_ptr), &window);
+        self.update_input_method_position();
     }
 
     fn set_focus(self: Rc<Self>, have_focus: bool) {
@@ -874,6 +1126,17 @@ impl<Backend: GraphicsBackend> crate::eventloop::GenericWindow for GraphicsWindo
         };
         let component = self.component.borrow().upgrade().unwrap();
         ComponentRc::borrow_pin(&component).as_ref().focus_event(&event, &window);
+        if have_focus {
+            self.update_input_method_position();
+        }
+    }
+
+    fn clipboard(&self) -> Rc<dyn crate::clipboard::Clipboard> {
+        self.clipboard.borrow().clone()
+    }
+
+    fn set_clipboard(&self, clipboard: Rc<dyn crate::clipboard::Clipboard>) {
+        *self.clipboard.borrow_mut() = clipboard;
     }
 }
 
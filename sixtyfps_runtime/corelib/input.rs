@@ -415,6 +415,22 @@ pub enum KeyEvent {
         /// The keyboard modifiers active at the time of the char input event.
         modifiers: KeyboardModifiers,
     },
+    /// An input method changed the text that is being composed and is not committed yet, also
+    /// called pre-edit text. An empty text ends the composition.
+    ///
+    /// The winit based event loop does not produce this event yet, as winit does not report the
+    /// composition. It is only sent by the testing functions for now.
+    PreeditChanged {
+        /// The text being composed.
+        text: crate::SharedString,
+        /// The position of the cursor within the composed text, as a byte offset.
+        cursor_position: i32,
+    },
+    /// An input method finished the composition and commits the given text.
+    PreeditCommitted {
+        /// The text to insert.
+        text: crate::SharedString,
+    },
 }
 
 impl TryFrom<(&winit::event::KeyboardInput, KeyboardModifiers)> for KeyEvent {
@@ -511,6 +527,31 @@ pub fn locate_and_activate_focus_item(
     )
 }
 
+/// Returns true if the item that has the focus is in a StackPage that is not the visible
+/// page of its StackLayout. Such an item must not receive key events anymore.
+pub fn is_focus_item_hidden(component: ComponentRefPin) -> bool {
+    let mut hidden_focus_item = false;
+    crate::item_tree::visit_items(
+        component,
+        crate::item_tree::TraversalOrder::FrontToBack,
+        |_, item, hidden| {
+            if let Some(text_input) =
+                crate::items::ItemRef::downcast_pin::<crate::items::TextInput>(item)
+            {
+                if crate::items::TextInput::FIELD_OFFSETS.has_focus.apply_pin(text_input).get() {
+                    hidden_focus_item = *hidden;
+                    return ItemVisitorResult::Abort;
+                }
+            }
+            ItemVisitorResult::Continue(
+                *hidden || !crate::items::StackPage::are_children_visible(item),
+            )
+        },
+        false,
+    );
+    hidden_focus_item
+}
+
 /// Feed the given mouse event into the tree of items that component holds. The
 /// event will be delivered to items in front first.
 ///
@@ -534,6 +575,9 @@ pub fn process_ungrabbed_mouse_event(
         component,
         crate::item_tree::TraversalOrder::FrontToBack,
         |_, item, offset| -> ItemVisitorResult<Vector2D<f32>> {
+            if !crate::items::StackPage::are_children_visible(item) {
+                return ItemVisitorResult::SkipChildren;
+            }
             let geom = item.as_ref().geometry();
             let geom = geom.translate(*offset);
 
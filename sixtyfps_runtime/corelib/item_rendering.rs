@@ -94,6 +94,9 @@ pub(crate) fn render_component_items<Backend: GraphicsBackend>(
         component,
         crate::item_tree::TraversalOrder::BackToFront,
         |_, item, transform| {
+            if !crate::items::StackPage::are_children_visible(item) {
+                return (ItemVisitorResult::SkipChildren, (*transform, Vec::new()));
+            }
             let origin = item.as_ref().geometry().origin;
             let transform =
                 transform * Matrix4::from_translation(Vector3::new(origin.x, origin.y, 0.));
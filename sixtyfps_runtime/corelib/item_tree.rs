@@ -131,6 +131,8 @@ impl<T: FnMut(crate::component::ComponentRefPin, isize, Pin<ItemRef>) -> VisitCh
 }
 pub enum ItemVisitorResult<State> {
     Continue(State),
+    /// Do not visit the children of this item, but continue with the next items
+    SkipChildren,
     Abort,
 }
 
@@ -195,6 +197,10 @@ fn visit_internal<State, PostVisitState>(
                     post_visitor(component, item, post_visit_state);
                     result
                 }
+                (ItemVisitorResult::SkipChildren, post_visit_state) => {
+                    post_visitor(component, item, post_visit_state);
+                    VisitChildrenResult::CONTINUE
+                }
                 (ItemVisitorResult::Abort, _) => VisitChildrenResult::abort(index as usize, 0),
             }
         };
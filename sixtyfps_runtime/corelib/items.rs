@@ -27,13 +27,17 @@ When adding an item or a property, it needs to be kept in sync with different pl
 
 use super::component::{ComponentRefPin, ComponentVTable};
 use super::eventloop::ComponentWindow;
-use super::graphics::{Color, HighLevelRenderingPrimitive, PathData, Rect, Resource};
+use super::font::{FontRequest, TextLayout, TextLine};
+use super::graphics::{
+    BorderRadii, Color, HighLevelRenderingPrimitive, IntSideOffsets, PathData, Point, Rect,
+    Resource, Size,
+};
 use super::input::{
     FocusEvent, InputEventResult, KeyEvent, KeyEventResult, KeyboardModifiers, MouseEvent,
     MouseEventType,
 };
 use super::item_rendering::CachedRenderingData;
-use super::layout::LayoutInfo;
+use super::layout::{LayoutDirection, LayoutInfo};
 #[cfg(feature = "rtti")]
 use crate::rtti::*;
 use crate::{Property, SharedString, Signal};
@@ -75,7 +79,9 @@ pub struct ItemVTable {
         window: &ComponentWindow,
     ) -> SharedArray<RenderingVariable>,
 
-    /// We would need max/min/preferred size, and all layout info
+    /// The constraints of the item for the layouts. The height constraints may depend on the
+    /// current width of the item: the layouts call this function again after setting the
+    /// width (see [`crate::layout::LayoutPass`]).
     pub layouting_info:
         extern "C" fn(core::pin::Pin<VRef<ItemVTable>>, window: &ComponentWindow) -> LayoutInfo,
 
@@ -136,7 +142,8 @@ impl Item for Rectangle {
                 width,
                 height,
                 border_width: 0.,
-                border_radius: 0.,
+                border_radius: BorderRadii::default(),
+                border_style: BorderStyle::solid,
             }
         } else {
             HighLevelRenderingPrimitive::NoContents
@@ -185,6 +192,21 @@ ItemVTable_static! {
     pub static RectangleVTable for Rectangle
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, strum_macros::EnumString, strum_macros::Display)]
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub enum BorderStyle {
+    solid,
+    dashed,
+    dotted,
+}
+
+impl Default for BorderStyle {
+    fn default() -> Self {
+        Self::solid
+    }
+}
+
 #[repr(C)]
 #[derive(FieldOffsets, Default, BuiltinItem)]
 #[pin]
@@ -197,10 +219,28 @@ pub struct BorderRectangle {
     pub height: Property<f32>,
     pub border_width: Property<f32>,
     pub border_radius: Property<f32>,
+    pub border_top_left_radius: Property<f32>,
+    pub border_top_right_radius: Property<f32>,
+    pub border_bottom_left_radius: Property<f32>,
+    pub border_bottom_right_radius: Property<f32>,
     pub border_color: Property<Color>,
+    pub border_style: Property<BorderStyle>,
     pub cached_rendering_data: CachedRenderingData,
 }
 
+impl BorderRectangle {
+    /// Returns the radius of each corner, in the order top left, top right, bottom right and
+    /// bottom left. The compiler binds the corners that are not set to the border_radius.
+    fn corner_radii(self: Pin<&Self>) -> [f32; 4] {
+        [
+            Self::FIELD_OFFSETS.border_top_left_radius.apply_pin(self).get(),
+            Self::FIELD_OFFSETS.border_top_right_radius.apply_pin(self).get(),
+            Self::FIELD_OFFSETS.border_bottom_right_radius.apply_pin(self).get(),
+            Self::FIELD_OFFSETS.border_bottom_left_radius.apply_pin(self).get(),
+        ]
+    }
+}
+
 impl Item for BorderRectangle {
     fn init(self: Pin<&Self>, _window: &ComponentWindow) {}
 
@@ -219,11 +259,13 @@ impl Item for BorderRectangle {
         let width = Self::FIELD_OFFSETS.width.apply_pin(self).get();
         let height = Self::FIELD_OFFSETS.height.apply_pin(self).get();
         if width > 0. && height > 0. {
+            let [top_left, top_right, bottom_right, bottom_left] = self.corner_radii();
             HighLevelRenderingPrimitive::Rectangle {
                 width,
                 height,
                 border_width: Self::FIELD_OFFSETS.border_width.apply_pin(self).get(),
-                border_radius: Self::FIELD_OFFSETS.border_radius.apply_pin(self).get(),
+                border_radius: BorderRadii { top_left, top_right, bottom_right, bottom_left },
+                border_style: Self::FIELD_OFFSETS.border_style.apply_pin(self).get(),
             }
         } else {
             HighLevelRenderingPrimitive::NoContents
@@ -273,6 +315,22 @@ ItemVTable_static! {
     pub static BorderRectangleVTable for BorderRectangle
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, strum_macros::EnumString, strum_macros::Display)]
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub enum ImageFit {
+    fill,
+    contain,
+    cover,
+    none,
+}
+
+impl Default for ImageFit {
+    fn default() -> Self {
+        Self::fill
+    }
+}
+
 #[repr(C)]
 #[derive(FieldOffsets, Default, BuiltinItem)]
 #[pin]
@@ -283,11 +341,33 @@ pub struct Image {
     pub y: Property<f32>,
     pub width: Property<f32>,
     pub height: Property<f32>,
+    pub image_fit: Property<ImageFit>,
+    pub source_clip_x: Property<i32>,
+    pub source_clip_y: Property<i32>,
+    pub source_clip_width: Property<i32>,
+    pub source_clip_height: Property<i32>,
+    pub nine_slice_top: Property<i32>,
+    pub nine_slice_right: Property<i32>,
+    pub nine_slice_bottom: Property<i32>,
+    pub nine_slice_left: Property<i32>,
+    pub playing: Property<bool>,
+    pub current_frame: Property<i32>,
+    pub frame_count: Property<i32>,
+    data: AnimatedImageDataBox,
     pub cached_rendering_data: CachedRenderingData,
 }
 
 impl Item for Image {
-    fn init(self: Pin<&Self>, _window: &ComponentWindow) {}
+    fn init(self: Pin<&Self>, _window: &ComponentWindow) {
+        let self_ptr = self.get_ref() as *const Self;
+        // Safety: the callback is owned by the data of the item and the animation driver only
+        // keeps a weak reference to it, so the item outlives it. And the item is pinned.
+        self.data.set_tick_callback(std::rc::Rc::new(move |tick| {
+            let this = unsafe { Pin::new_unchecked(&*self_ptr) };
+            this.data.advance(this, tick)
+        }));
+        self.data.advance(self, crate::animations::current_tick());
+    }
 
     fn geometry(self: Pin<&Self>) -> Rect {
         euclid::rect(
@@ -299,10 +379,38 @@ impl Item for Image {
     }
     fn rendering_primitive(
         self: Pin<&Self>,
-        _window: &ComponentWindow,
+        window: &ComponentWindow,
     ) -> HighLevelRenderingPrimitive {
+        let source = Self::FIELD_OFFSETS.source.apply_pin(self).get();
+        // Only vector images depend on the size, bitmaps are scaled with the rendering variables.
+        let (rasterization_size, scale_factor) = if source.is_svg() {
+            (
+                Size::new(
+                    Self::FIELD_OFFSETS.width.apply_pin(self).get(),
+                    Self::FIELD_OFFSETS.height.apply_pin(self).get(),
+                ),
+                window.scale_factor(),
+            )
+        } else {
+            (Size::default(), 1.)
+        };
         HighLevelRenderingPrimitive::Image {
-            source: Self::FIELD_OFFSETS.source.apply_pin(self).get(),
+            source,
+            source_clip_rect: euclid::rect(
+                Self::FIELD_OFFSETS.source_clip_x.apply_pin(self).get(),
+                Self::FIELD_OFFSETS.source_clip_y.apply_pin(self).get(),
+                Self::FIELD_OFFSETS.source_clip_width.apply_pin(self).get(),
+                Self::FIELD_OFFSETS.source_clip_height.apply_pin(self).get(),
+            ),
+            image_fit: Self::FIELD_OFFSETS.image_fit.apply_pin(self).get(),
+            nine_slice: IntSideOffsets::new(
+                Self::FIELD_OFFSETS.nine_slice_top.apply_pin(self).get(),
+                Self::FIELD_OFFSETS.nine_slice_right.apply_pin(self).get(),
+                Self::FIELD_OFFSETS.nine_slice_bottom.apply_pin(self).get(),
+                Self::FIELD_OFFSETS.nine_slice_left.apply_pin(self).get(),
+            ),
+            rasterization_size,
+            scale_factor,
         }
     }
 
@@ -312,6 +420,10 @@ impl Item for Image {
     ) -> SharedArray<RenderingVariable> {
         let mut vars = SharedArray::default();
 
+        if let Some(frame) = self.data.current_frame(self) {
+            vars.push(RenderingVariable::ImageFrame(frame));
+        }
+
         let width = Self::FIELD_OFFSETS.width.apply_pin(self).get();
         let height = Self::FIELD_OFFSETS.height.apply_pin(self).get();
 
@@ -326,8 +438,13 @@ impl Item for Image {
     }
 
     fn layouting_info(self: Pin<&Self>, _window: &crate::eventloop::ComponentWindow) -> LayoutInfo {
-        // FIXME: should we use the image size here
-        Default::default()
+        let natural_size =
+            Self::FIELD_OFFSETS.source.apply_pin(self).get().natural_size().unwrap_or_default();
+        LayoutInfo {
+            preferred_width: natural_size.width,
+            preferred_height: natural_size.height,
+            ..LayoutInfo::default()
+        }
     }
 
     fn input_event(
@@ -374,6 +491,18 @@ impl Default for TextHorizontalAlignment {
     }
 }
 
+impl TextHorizontalAlignment {
+    /// The alignment in the given layout direction: right to left, `align_left` and `align_right`
+    /// are swapped.
+    pub fn for_direction(self, direction: LayoutDirection) -> Self {
+        match (self, direction) {
+            (Self::align_left, LayoutDirection::rtl) => Self::align_right,
+            (Self::align_right, LayoutDirection::rtl) => Self::align_left,
+            (alignment, _) => alignment,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, strum_macros::EnumString, strum_macros::Display)]
 #[repr(C)]
 #[allow(non_camel_case_types)]
@@ -389,6 +518,53 @@ impl Default for TextVerticalAlignment {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, strum_macros::EnumString, strum_macros::Display)]
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub enum TextWrap {
+    no_wrap,
+    word_wrap,
+}
+
+impl Default for TextWrap {
+    fn default() -> Self {
+        Self::no_wrap
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, strum_macros::EnumString, strum_macros::Display)]
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub enum TextOverflow {
+    clip,
+    elide,
+}
+
+impl Default for TextOverflow {
+    fn default() -> Self {
+        Self::clip
+    }
+}
+
+/// The kind of content that a [`TextInput`] accepts, and how it is shown.
+#[derive(Copy, Clone, Debug, PartialEq, strum_macros::EnumString, strum_macros::Display)]
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub enum InputType {
+    /// Any text, shown as is
+    text,
+    /// Any text, shown as one bullet per character and that cannot be copied
+    password,
+    /// A decimal number, possibly negative
+    number,
+}
+
+impl Default for InputType {
+    fn default() -> Self {
+        Self::text
+    }
+}
+
 const DEFAULT_FONT_SIZE: f32 = 12.;
 
 /// The implementation of the `Text` element
@@ -399,9 +575,15 @@ pub struct Text {
     pub text: Property<SharedString>,
     pub font_family: Property<SharedString>,
     pub font_size: Property<f32>,
+    pub font_weight: Property<i32>,
+    pub font_italic: Property<bool>,
+    pub letter_spacing: Property<f32>,
     pub color: Property<Color>,
     pub horizontal_alignment: Property<TextHorizontalAlignment>,
     pub vertical_alignment: Property<TextVerticalAlignment>,
+    pub layout_direction: Property<LayoutDirection>,
+    pub wrap: Property<TextWrap>,
+    pub overflow: Property<TextOverflow>,
     pub x: Property<f32>,
     pub y: Property<f32>,
     pub width: Property<f32>,
@@ -425,50 +607,57 @@ impl Item for Text {
         self: Pin<&Self>,
         window: &ComponentWindow,
     ) -> HighLevelRenderingPrimitive {
+        let width = available_length(Self::FIELD_OFFSETS.width.apply_pin(self).get());
+        let height = available_length(Self::FIELD_OFFSETS.height.apply_pin(self).get());
+        let overflow = Self::FIELD_OFFSETS.overflow.apply_pin(self).get();
+
+        let (lines, line_height) = self.with_text_layout(window, width, height, |layout| {
+            (layout.lines(), layout.font.height())
+        });
+
+        // Only clip if some of the text is actually outside of the element
+        let text_width = lines.iter().fold(0., |w, line| (line.x + line.width).max(w));
+        let text_height = lines.last().map_or(0., |line| line.y + line_height);
+        let clip_size = Size::new(width.unwrap_or(text_width), height.unwrap_or(text_height));
+        let needs_clip = overflow == TextOverflow::clip
+            && lines.iter().any(|line| {
+                line.x < 0.
+                    || line.y < 0.
+                    || line.x + line.width > clip_size.width
+                    || line.y + line_height > clip_size.height
+            });
+
         HighLevelRenderingPrimitive::Text {
             text: Self::FIELD_OFFSETS.text.apply_pin(self).get(),
-            font_family: Self::FIELD_OFFSETS.font_family.apply_pin(self).get(),
-            font_size: Text::font_pixel_size(self, window),
+            font_request: Text::font_request(self, window),
+            lines,
+            clip: if needs_clip { Some(clip_size) } else { None },
         }
     }
 
     fn rendering_variables(
         self: Pin<&Self>,
-        window: &ComponentWindow,
+        _window: &ComponentWindow,
     ) -> SharedArray<RenderingVariable> {
-        let layout_info = self.layouting_info(window);
-        let rect = self.geometry();
-
-        let hor_alignment = Self::FIELD_OFFSETS.horizontal_alignment.apply_pin(self).get();
-        let translate_x = match hor_alignment {
-            TextHorizontalAlignment::align_left => 0.,
-            TextHorizontalAlignment::align_center => rect.width() / 2. - layout_info.min_width / 2.,
-            TextHorizontalAlignment::align_right => rect.width() - layout_info.min_width,
-        };
-
-        let ver_alignment = Self::FIELD_OFFSETS.vertical_alignment.apply_pin(self).get();
-        let translate_y = match ver_alignment {
-            TextVerticalAlignment::align_top => 0.,
-            TextVerticalAlignment::align_center => rect.height() / 2. - layout_info.min_height / 2.,
-            TextVerticalAlignment::align_bottom => rect.height() - layout_info.min_height,
-        };
-
-        SharedArray::from([
-            RenderingVariable::Translate(translate_x, translate_y),
-            RenderingVariable::Color(Self::FIELD_OFFSETS.color.apply_pin(self).get()),
-        ])
+        SharedArray::from([RenderingVariable::Color(
+            Self::FIELD_OFFSETS.color.apply_pin(self).get(),
+        )])
     }
 
     fn layouting_info(self: Pin<&Self>, window: &ComponentWindow) -> LayoutInfo {
-        let font_family = Self::FIELD_OFFSETS.font_family.apply_pin(self).get();
-        let font_size = Text::font_pixel_size(self, window);
-        let text = Self::FIELD_OFFSETS.text.apply_pin(self).get();
-
-        crate::font::FONT_CACHE.with(|fc| {
-            let font = fc.find_font(&font_family, font_size);
-            let width = font.text_width(&text);
-            let height = font.height();
-            LayoutInfo { min_width: width, min_height: height, ..LayoutInfo::default() }
+        // The height depends on the width when wrapping, so report the height needed for the
+        // width the element currently has.
+        let width = available_length(Self::FIELD_OFFSETS.width.apply_pin(self).get());
+
+        self.with_text_layout(window, width, None, |layout| {
+            let height = layout.height_for_width();
+            LayoutInfo {
+                min_width: layout.min_width(),
+                min_height: height,
+                preferred_width: layout.preferred_width(),
+                preferred_height: height,
+                ..LayoutInfo::default()
+            }
         })
     }
 
@@ -494,14 +683,60 @@ impl ItemConsts for Text {
 }
 
 impl Text {
-    fn font_pixel_size(self: Pin<&Self>, window: &ComponentWindow) -> f32 {
+    fn font_request(self: Pin<&Self>, window: &ComponentWindow) -> FontRequest {
         let font_size = Self::FIELD_OFFSETS.font_size.apply_pin(self).get();
-        if font_size == 0.0 {
-            DEFAULT_FONT_SIZE * window.scale_factor()
-        } else {
-            font_size
+        FontRequest {
+            family: Self::FIELD_OFFSETS.font_family.apply_pin(self).get(),
+            weight: Self::FIELD_OFFSETS.font_weight.apply_pin(self).get(),
+            italic: Self::FIELD_OFFSETS.font_italic.apply_pin(self).get(),
+            pixel_size: if font_size == 0.0 {
+                DEFAULT_FONT_SIZE * window.scale_factor()
+            } else {
+                font_size
+            },
+            letter_spacing: Self::FIELD_OFFSETS.letter_spacing.apply_pin(self).get(),
         }
     }
+
+    /// Calls `f` with the layout of the text in the given available size.
+    fn with_text_layout<R>(
+        self: Pin<&Self>,
+        window: &ComponentWindow,
+        width: Option<f32>,
+        height: Option<f32>,
+        f: impl FnOnce(&TextLayout) -> R,
+    ) -> R {
+        let font_request = Text::font_request(self, window);
+        let text = Self::FIELD_OFFSETS.text.apply_pin(self).get();
+
+        crate::font::FONT_CACHE.with(|fc| {
+            let font = fc.find_font(&font_request);
+            f(&TextLayout {
+                font: &font,
+                text: &text,
+                letter_spacing: font_request.letter_spacing,
+                width,
+                height,
+                wrap: Self::FIELD_OFFSETS.wrap.apply_pin(self).get(),
+                overflow: Self::FIELD_OFFSETS.overflow.apply_pin(self).get(),
+                horizontal_alignment: Self::FIELD_OFFSETS
+                    .horizontal_alignment
+                    .apply_pin(self)
+                    .get()
+                    .for_direction(Self::FIELD_OFFSETS.layout_direction.apply_pin(self).get()),
+                vertical_alignment: Self::FIELD_OFFSETS.vertical_alignment.apply_pin(self).get(),
+            })
+        })
+    }
+}
+
+/// A width or height of zero means that the element was not given a size.
+fn available_length(length: f32) -> Option<f32> {
+    if length > 0. {
+        Some(length)
+    } else {
+        None
+    }
 }
 
 ItemVTable_static! {
@@ -819,6 +1054,76 @@ pub unsafe extern "C" fn sixtyfps_flickable_data_free(data: *mut FlickableDataBo
     std::ptr::read(data);
 }
 
+#[repr(C)]
+/// Wraps the undo history and the mouse interaction state of the TextInput
+pub struct TextInputDataBox(core::ptr::NonNull<crate::text_input::TextInputData>);
+
+impl Default for TextInputDataBox {
+    fn default() -> Self {
+        TextInputDataBox(Box::leak(Box::new(crate::text_input::TextInputData::default())).into())
+    }
+}
+impl Drop for TextInputDataBox {
+    fn drop(&mut self) {
+        // Safety: the self.0 was constructed from a Box::leak in TextInputDataBox::default
+        unsafe {
+            Box::from_raw(self.0.as_ptr());
+        }
+    }
+}
+impl core::ops::Deref for TextInputDataBox {
+    type Target = crate::text_input::TextInputData;
+    fn deref(&self) -> &Self::Target {
+        // Safety: initialized in TextInputDataBox::default
+        unsafe { self.0.as_ref() }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn sixtyfps_text_input_data_init(data: *mut TextInputDataBox) {
+    std::ptr::write(data, TextInputDataBox::default());
+}
+#[no_mangle]
+pub unsafe extern "C" fn sixtyfps_text_input_data_free(data: *mut TextInputDataBox) {
+    std::ptr::read(data);
+}
+
+#[repr(C)]
+/// Wraps the internal playback state of animated images
+pub struct AnimatedImageDataBox(core::ptr::NonNull<crate::animated_image::AnimatedImageData>);
+
+impl Default for AnimatedImageDataBox {
+    fn default() -> Self {
+        AnimatedImageDataBox(
+            Box::leak(Box::new(crate::animated_image::AnimatedImageData::default())).into(),
+        )
+    }
+}
+impl Drop for AnimatedImageDataBox {
+    fn drop(&mut self) {
+        // Safety: the self.0 was constructed from a Box::leak in AnimatedImageDataBox::default
+        unsafe {
+            Box::from_raw(self.0.as_ptr());
+        }
+    }
+}
+impl core::ops::Deref for AnimatedImageDataBox {
+    type Target = crate::animated_image::AnimatedImageData;
+    fn deref(&self) -> &Self::Target {
+        // Safety: initialized in AnimatedImageDataBox::default
+        unsafe { self.0.as_ref() }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn sixtyfps_animated_image_data_init(data: *mut AnimatedImageDataBox) {
+    std::ptr::write(data, AnimatedImageDataBox::default());
+}
+#[no_mangle]
+pub unsafe extern "C" fn sixtyfps_animated_image_data_free(data: *mut AnimatedImageDataBox) {
+    std::ptr::read(data);
+}
+
 /// The implementation of the `PropertyAnimation` element
 #[repr(C)]
 #[derive(FieldOffsets, Default, BuiltinItem, Clone, Debug)]
@@ -898,6 +1203,85 @@ ItemVTable_static! {
     pub static WindowVTable for Window
 }
 
+/// The implementation of the `StackPage` element. The compiler puts each child of a
+/// `StackLayout` in such a page, which only shows its children when it is the current one.
+#[repr(C)]
+#[derive(FieldOffsets, Default, BuiltinItem)]
+#[pin]
+pub struct StackPage {
+    pub x: Property<f32>,
+    pub y: Property<f32>,
+    pub width: Property<f32>,
+    pub height: Property<f32>,
+    pub visible: Property<bool>,
+    pub cached_rendering_data: CachedRenderingData,
+}
+
+impl StackPage {
+    /// Returns false if the item is a page that currently hides its children, which are then
+    /// neither rendered nor do they receive mouse events.
+    pub fn are_children_visible(item: Pin<ItemRef>) -> bool {
+        ItemRef::downcast_pin::<StackPage>(item)
+            .map_or(true, |page| Self::FIELD_OFFSETS.visible.apply_pin(page).get())
+    }
+}
+
+impl Item for StackPage {
+    fn init(self: Pin<&Self>, _window: &ComponentWindow) {}
+
+    fn geometry(self: Pin<&Self>) -> Rect {
+        euclid::rect(
+            Self::FIELD_OFFSETS.x.apply_pin(self).get(),
+            Self::FIELD_OFFSETS.y.apply_pin(self).get(),
+            Self::FIELD_OFFSETS.width.apply_pin(self).get(),
+            Self::FIELD_OFFSETS.height.apply_pin(self).get(),
+        )
+    }
+    fn rendering_primitive(
+        self: Pin<&Self>,
+        _window: &ComponentWindow,
+    ) -> HighLevelRenderingPrimitive {
+        HighLevelRenderingPrimitive::NoContents
+    }
+
+    fn rendering_variables(
+        self: Pin<&Self>,
+        _window: &ComponentWindow,
+    ) -> SharedArray<RenderingVariable> {
+        SharedArray::default()
+    }
+
+    fn layouting_info(self: Pin<&Self>, _window: &ComponentWindow) -> LayoutInfo {
+        LayoutInfo::default()
+    }
+
+    fn input_event(
+        self: Pin<&Self>,
+        _event: MouseEvent,
+        _window: &ComponentWindow,
+        _app_component: ComponentRefPin,
+    ) -> InputEventResult {
+        InputEventResult::EventIgnored
+    }
+
+    fn key_event(self: Pin<&Self>, _: &KeyEvent, _window: &ComponentWindow) -> KeyEventResult {
+        KeyEventResult::EventIgnored
+    }
+
+    fn focus_event(self: Pin<&Self>, _: &FocusEvent, _window: &ComponentWindow) {}
+}
+
+impl ItemConsts for StackPage {
+    const cached_rendering_data_offset: const_field_offset::FieldOffset<Self, CachedRenderingData> =
+        Self::FIELD_OFFSETS.cached_rendering_data.as_unpinned_projection();
+}
+
+ItemVTable_static! {
+    /// The VTable for `StackPage`
+    #[no_mangle]
+    pub static StackPageVTable for StackPage
+}
+
 /// The implementation of the `TextInput` element
 #[repr(C)]
 #[derive(FieldOffsets, Default, BuiltinItem)]
@@ -906,11 +1290,17 @@ pub struct TextInput {
     pub text: Property<SharedString>,
     pub font_family: Property<SharedString>,
     pub font_size: Property<f32>,
+    pub font_weight: Property<i32>,
+    pub font_italic: Property<bool>,
+    pub letter_spacing: Property<f32>,
     pub color: Property<Color>,
     pub selection_foreground_color: Property<Color>,
     pub selection_background_color: Property<Color>,
     pub horizontal_alignment: Property<TextHorizontalAlignment>,
     pub vertical_alignment: Property<TextVerticalAlignment>,
+    pub layout_direction: Property<LayoutDirection>,
+    pub wrap: Property<TextWrap>,
+    pub single_line: Property<bool>,
     pub x: Property<f32>,
     pub y: Property<f32>,
     pub width: Property<f32>,
@@ -921,14 +1311,39 @@ pub struct TextInput {
     pub cursor_visible: Property<bool>,
     pub has_focus: Property<bool>,
     pub enabled: Property<bool>,
+    pub can_undo: Property<bool>,
+    pub can_redo: Property<bool>,
+    pub input_type: Property<InputType>,
+    pub max_length: Property<i32>,
+    pub validator: Property<SharedString>,
     pub accepted: Signal<()>,
     pub edited: Signal<()>,
     pub pressed: std::cell::Cell<bool>,
+    /// The vertical scroll position of a multi-line text that does not fit, not exposed to .60
+    pub scroll_offset: Property<f32>,
+    /// The text being composed with an input method, shown at the cursor position but not
+    /// part of the text yet, not exposed to .60
+    pub preedit_text: Property<SharedString>,
+    /// The byte offset of the cursor within `preedit_text`, not exposed to .60
+    pub preedit_cursor: Property<i32>,
+    data: TextInputDataBox,
     pub cached_rendering_data: CachedRenderingData,
 }
 
 impl Item for TextInput {
-    fn init(self: Pin<&Self>, _window: &ComponentWindow) {}
+    fn init(self: Pin<&Self>, _window: &ComponentWindow) {
+        let self_ptr = self.get_ref() as *const Self;
+        // Safety: the bindings are owned by properties of the item, so the item outlives them
+        // and it is pinned.
+        self.can_undo.set_binding(move || {
+            let this = unsafe { Pin::new_unchecked(&*self_ptr) };
+            this.data.undo_stack.can_undo(Self::FIELD_OFFSETS.text.apply_pin(this).get().as_str())
+        });
+        self.can_redo.set_binding(move || {
+            let this = unsafe { Pin::new_unchecked(&*self_ptr) };
+            this.data.undo_stack.can_redo(Self::FIELD_OFFSETS.text.apply_pin(this).get().as_str())
+        });
+    }
 
     // FIXME: width / height.  or maybe it doesn't matter?  (
     fn geometry(self: Pin<&Self>) -> Rect {
@@ -943,10 +1358,19 @@ impl Item for TextInput {
         self: Pin<&Self>,
         window: &ComponentWindow,
     ) -> HighLevelRenderingPrimitive {
+        let text = self.displayed_text();
+        let lines = TextInput::with_font(self, window, |font| self.visible_lines(font, &text));
+        // Multi-line text is scrolled, so lines may be partially visible
+        let clip = if Self::FIELD_OFFSETS.single_line.apply_pin(self).get() {
+            None
+        } else {
+            Some(self.geometry().size)
+        };
         HighLevelRenderingPrimitive::Text {
-            text: Self::FIELD_OFFSETS.text.apply_pin(self).get(),
-            font_family: Self::FIELD_OFFSETS.font_family.apply_pin(self).get(),
-            font_size: TextInput::font_pixel_size(self, window),
+            text,
+            font_request: TextInput::font_request(self, window),
+            lines,
+            clip,
         }
     }
 
@@ -954,78 +1378,103 @@ impl Item for TextInput {
         self: Pin<&Self>,
         window: &ComponentWindow,
     ) -> SharedArray<RenderingVariable> {
-        let layout_info = self.layouting_info(window);
-        let rect = self.geometry();
-
-        let hor_alignment = Self::FIELD_OFFSETS.horizontal_alignment.apply_pin(self).get();
-        let translate_x = match hor_alignment {
-            TextHorizontalAlignment::align_left => 0.,
-            TextHorizontalAlignment::align_center => rect.width() / 2. - layout_info.min_width / 2.,
-            TextHorizontalAlignment::align_right => rect.width() - layout_info.min_width,
-        };
-
-        let ver_alignment = Self::FIELD_OFFSETS.vertical_alignment.apply_pin(self).get();
-        let translate_y = match ver_alignment {
-            TextVerticalAlignment::align_top => 0.,
-            TextVerticalAlignment::align_center => rect.height() / 2. - layout_info.min_height / 2.,
-            TextVerticalAlignment::align_bottom => rect.height() - layout_info.min_height,
-        };
-
-        let mut variables = SharedArray::from([
-            RenderingVariable::Translate(translate_x, translate_y),
-            RenderingVariable::Color(Self::FIELD_OFFSETS.color.apply_pin(self).get()),
-        ]);
+        let mut variables = SharedArray::from([RenderingVariable::Color(
+            Self::FIELD_OFFSETS.color.apply_pin(self).get(),
+        )]);
 
-        if self.has_selection() {
-            let (anchor_pos, cursor_pos) = self.selection_anchor_and_cursor();
-            let text = Self::FIELD_OFFSETS.text.apply_pin(self).get();
-            let (selection_start_x, selection_end_x, font_height) =
-                TextInput::with_font(self, window, |font| {
-                    (
-                        font.text_width(text.split_at(anchor_pos as _).0),
-                        font.text_width(text.split_at(cursor_pos as _).0),
-                        font.height(),
-                    )
-                });
-
-            variables.push(RenderingVariable::TextSelection(
-                selection_start_x,
-                selection_end_x - selection_start_x,
-                font_height,
-            ));
-            let selection_foreground =
-                Self::FIELD_OFFSETS.selection_foreground_color.apply_pin(self).get();
-            let selection_background =
-                Self::FIELD_OFFSETS.selection_background_color.apply_pin(self).get();
-            variables.push(RenderingVariable::Color(selection_foreground));
-            variables.push(RenderingVariable::Color(selection_background));
+        let text = self.displayed_text();
+        let cursor_visible = Self::FIELD_OFFSETS.cursor_visible.apply_pin(self).get();
+        let preedit_length = self.displayed_preedit_length();
+        if !self.has_selection() && !cursor_visible && preedit_length == 0 {
+            return variables;
         }
 
-        if Self::FIELD_OFFSETS.cursor_visible.apply_pin(self).get() {
-            let cursor_pos = Self::FIELD_OFFSETS.cursor_position.apply_pin(self).get();
-            let text = Self::FIELD_OFFSETS.text.apply_pin(self).get();
-            let (cursor_x_pos, font_height) = TextInput::with_font(self, window, |font| {
-                (font.text_width(text.split_at(cursor_pos as _).0), font.height())
-            });
+        let letter_spacing = Self::FIELD_OFFSETS.letter_spacing.apply_pin(self).get();
+        TextInput::with_font(self, window, |font| {
+            let lines = self.visible_lines(font, &text);
+            let line_height = font.height();
+
+            if self.has_selection() {
+                let (anchor_pos, cursor_pos) = self.selection_anchor_and_cursor();
+                let (anchor_pos, cursor_pos) =
+                    (self.displayed_offset(anchor_pos), self.displayed_offset(cursor_pos));
+                for line in lines.iter() {
+                    let start = anchor_pos.max(line.start);
+                    let end = cursor_pos.min(line.end);
+                    if start >= end {
+                        continue;
+                    }
+                    let start_x = cursor_x_in_line(font, letter_spacing, &text, line, start);
+                    let end_x = cursor_x_in_line(font, letter_spacing, &text, line, end);
+                    // In right-to-left text, the start may be on the right of the end
+                    variables.push(RenderingVariable::TextSelection(
+                        start_x.min(end_x),
+                        line.y,
+                        (end_x - start_x).abs(),
+                        line_height,
+                    ));
+                }
+                let selection_foreground =
+                    Self::FIELD_OFFSETS.selection_foreground_color.apply_pin(self).get();
+                let selection_background =
+                    Self::FIELD_OFFSETS.selection_background_color.apply_pin(self).get();
+                variables.push(RenderingVariable::Color(selection_foreground));
+                variables.push(RenderingVariable::Color(selection_background));
+            }
 
-            let cursor_width =
-                Self::FIELD_OFFSETS.text_cursor_width.apply_pin(self).get() * window.scale_factor();
+            if preedit_length > 0 {
+                // The text being composed is underlined
+                let cursor_pos = Self::FIELD_OFFSETS.cursor_position.apply_pin(self).get().max(0);
+                let preedit_start = self.displayed_offset(cursor_pos as usize);
+                let preedit_end = preedit_start + preedit_length;
+                let thickness = window.scale_factor();
+                for line in lines.iter() {
+                    let start = preedit_start.max(line.start);
+                    let end = preedit_end.min(line.end);
+                    if start >= end {
+                        continue;
+                    }
+                    let start_x = cursor_x_in_line(font, letter_spacing, &text, line, start);
+                    let end_x = cursor_x_in_line(font, letter_spacing, &text, line, end);
+                    variables.push(RenderingVariable::TextUnderline(
+                        start_x.min(end_x),
+                        line.y + line_height - thickness,
+                        (end_x - start_x).abs(),
+                        thickness,
+                    ));
+                }
+            }
 
-            variables.push(RenderingVariable::TextCursor(cursor_x_pos, cursor_width, font_height));
-        }
+            if cursor_visible {
+                let cursor_rect =
+                    self.cursor_rect_in_lines(font, letter_spacing, &text, &lines, window);
+                variables.push(RenderingVariable::TextCursor(
+                    cursor_rect.origin.x,
+                    cursor_rect.origin.y,
+                    cursor_rect.size.width,
+                    cursor_rect.size.height,
+                ));
+            }
+        });
 
         variables
     }
 
     fn layouting_info(self: Pin<&Self>, window: &ComponentWindow) -> LayoutInfo {
+        let letter_spacing = Self::FIELD_OFFSETS.letter_spacing.apply_pin(self).get();
         let (width, height) = TextInput::with_font(self, window, |font| {
-            (font.text_width("********************"), font.height())
+            (font.text_width("********************", letter_spacing), font.height())
         });
 
         LayoutInfo {
             min_width: width,
             min_height: height,
             horizontal_stretch: 1.,
+            vertical_stretch: if Self::FIELD_OFFSETS.single_line.apply_pin(self).get() {
+                0.
+            } else {
+                1.
+            },
             ..LayoutInfo::default()
         }
     }
@@ -1040,31 +1489,60 @@ impl Item for TextInput {
             return InputEventResult::EventIgnored;
         }
 
-        let clicked_offset = TextInput::with_font(self, window, |font| {
-            let text = Self::FIELD_OFFSETS.text.apply_pin(self).get();
-            font.text_offset_for_x_position(&text, event.pos.x)
-        }) as i32;
-
-        if matches!(event.what, MouseEventType::MousePressed) {
-            self.as_ref().pressed.set(true);
-            self.as_ref().anchor_position.set(clicked_offset);
-            self.as_ref().cursor_position.set(clicked_offset);
-            if !Self::FIELD_OFFSETS.has_focus.apply_pin(self).get() {
-                window.set_focus_item(app_component, VRef::new_pin(self));
-            }
-        }
-
         match event.what {
+            MouseEventType::MousePressed => {
+                self.as_ref().pressed.set(true);
+                let clicked_offset = self.offset_for_position(event.pos, window);
+                match self.data.register_press(event.pos) {
+                    1 => {
+                        if !window.current_keyboard_modifiers().shift() {
+                            self.as_ref().anchor_position.set(clicked_offset);
+                        }
+                        self.as_ref().cursor_position.set(clicked_offset);
+                    }
+                    2 => self.select_word(clicked_offset as usize),
+                    _ => self.select_all(window),
+                }
+                if !Self::FIELD_OFFSETS.has_focus.apply_pin(self).get() {
+                    window.set_focus_item(app_component, VRef::new_pin(self));
+                }
+                InputEventResult::GrabMouse
+            }
             MouseEventType::MouseReleased => {
                 self.as_ref().pressed.set(false);
+                self.data.autoscroll_timer.stop();
+                InputEventResult::EventAccepted
             }
             MouseEventType::MouseMoved if self.as_ref().pressed.get() => {
-                self.as_ref().cursor_position.set(clicked_offset);
+                if self.data.click_count() == 1 {
+                    self.data.drag_position.set(event.pos);
+                    self.drag_selection(window);
+
+                    let height = Self::FIELD_OFFSETS.height.apply_pin(self).get();
+                    let beyond_edge = event.pos.y < 0. || event.pos.y > height;
+                    if beyond_edge && !Self::FIELD_OFFSETS.single_line.apply_pin(self).get() {
+                        if !self.data.autoscroll_timer.running() {
+                            let self_ptr = self.get_ref() as *const Self;
+                            let window = window.clone();
+                            self.data.autoscroll_timer.start(
+                                crate::timers::TimerMode::Repeated,
+                                std::time::Duration::from_millis(50),
+                                Box::new(move || {
+                                    // Safety: the timer is owned by the item, which is pinned, and
+                                    // it is stopped when it is dropped.
+                                    let this = unsafe { Pin::new_unchecked(&*self_ptr) };
+                                    this.drag_selection(&window);
+                                }),
+                            );
+                        }
+                    } else {
+                        self.data.autoscroll_timer.stop();
+                    }
+                }
+                InputEventResult::GrabMouse
             }
-            _ => {}
+            _ => InputEventResult::EventAccepted,
         }
-
-        InputEventResult::EventAccepted
     }
 
     fn key_event(self: Pin<&Self>, event: &KeyEvent, window: &ComponentWindow) -> KeyEventResult {
@@ -1075,27 +1553,42 @@ impl Item for TextInput {
         }
 
         match event {
+            KeyEvent::CharacterInput { unicode_scalar, .. }
+                if char::try_from(*unicode_scalar).map_or(false, |ch| ch == '\n' || ch == '\r') =>
+            {
+                if Self::FIELD_OFFSETS.single_line.apply_pin(self).get() {
+                    KeyEventResult::EventIgnored
+                } else {
+                    self.insert("\n");
+                    self.show_cursor(window);
+                    KeyEventResult::EventAccepted
+                }
+            }
             KeyEvent::CharacterInput { unicode_scalar, .. } => {
-                self.delete_selection();
-
-                let mut text: String = Self::FIELD_OFFSETS.text.apply_pin(self).get().into();
-
                 // FIXME: respect grapheme boundaries
-                let insert_pos = Self::FIELD_OFFSETS.cursor_position.apply_pin(self).get() as usize;
                 let ch = char::try_from(*unicode_scalar).unwrap().to_string();
-                text.insert_str(insert_pos, &ch);
-
-                self.as_ref().text.set(text.into());
-                let new_cursor_pos = (insert_pos + ch.len()) as i32;
-                self.as_ref().cursor_position.set(new_cursor_pos);
-                self.as_ref().anchor_position.set(new_cursor_pos);
+                self.replace_selection(&ch, true);
 
                 // Keep the cursor visible when inserting text. Blinking should only occur when
                 // nothing is entered or the cursor isn't moved.
                 self.as_ref().show_cursor(window);
 
-                Self::FIELD_OFFSETS.edited.apply_pin(self).emit(&());
-
+                KeyEventResult::EventAccepted
+            }
+            KeyEvent::PreeditChanged { text, cursor_position } => {
+                // The composed text replaces the selection
+                if !text.is_empty() {
+                    self.delete_selection();
+                }
+                self.preedit_text.set(text.clone());
+                self.preedit_cursor.set((*cursor_position).max(0).min(text.len() as i32));
+                self.show_cursor(window);
+                KeyEventResult::EventAccepted
+            }
+            KeyEvent::PreeditCommitted { text } => {
+                self.clear_preedit();
+                self.insert_external_text(text);
+                self.show_cursor(window);
                 KeyEventResult::EventAccepted
             }
             KeyEvent::KeyPressed { code, modifiers } if *code == crate::input::KeyCode::Right => {
@@ -1119,7 +1612,11 @@ impl Item for TextInput {
             KeyEvent::KeyPressed { code, modifiers } if *code == crate::input::KeyCode::Home => {
                 TextInput::move_cursor(
                     self,
-                    TextCursorDirection::StartOfLine,
+                    if modifiers.control() {
+                        TextCursorDirection::StartOfText
+                    } else {
+                        TextCursorDirection::StartOfLine
+                    },
                     (*modifiers).into(),
                     window,
                 );
@@ -1128,12 +1625,35 @@ impl Item for TextInput {
             KeyEvent::KeyPressed { code, modifiers } if *code == crate::input::KeyCode::End => {
                 TextInput::move_cursor(
                     self,
-                    TextCursorDirection::EndOfLine,
+                    if modifiers.control() {
+                        TextCursorDirection::EndOfText
+                    } else {
+                        TextCursorDirection::EndOfLine
+                    },
                     (*modifiers).into(),
                     window,
                 );
                 KeyEventResult::EventAccepted
             }
+            KeyEvent::KeyPressed { code, modifiers }
+                if !Self::FIELD_OFFSETS.single_line.apply_pin(self).get()
+                    && matches!(
+                        code,
+                        crate::input::KeyCode::Up
+                            | crate::input::KeyCode::Down
+                            | crate::input::KeyCode::PageUp
+                            | crate::input::KeyCode::PageDown
+                    ) =>
+            {
+                let direction = match code {
+                    crate::input::KeyCode::Up => TextCursorDirection::PreviousLine,
+                    crate::input::KeyCode::Down => TextCursorDirection::NextLine,
+                    crate::input::KeyCode::PageUp => TextCursorDirection::PreviousPage,
+                    _ => TextCursorDirection::NextPage,
+                };
+                TextInput::move_cursor(self, direction, (*modifiers).into(), window);
+                KeyEventResult::EventAccepted
+            }
             KeyEvent::KeyPressed { code, .. } if *code == crate::input::KeyCode::Back => {
                 TextInput::delete_previous(self, window);
                 KeyEventResult::EventAccepted
@@ -1143,21 +1663,48 @@ impl Item for TextInput {
                 KeyEventResult::EventAccepted
             }
             KeyEvent::KeyPressed { code, .. } if *code == crate::input::KeyCode::Return => {
-                Self::FIELD_OFFSETS.accepted.apply_pin(self).emit(&());
+                if Self::FIELD_OFFSETS.single_line.apply_pin(self).get() {
+                    Self::FIELD_OFFSETS.accepted.apply_pin(self).emit(&());
+                } else {
+                    self.insert("\n");
+                    self.show_cursor(window);
+                }
                 KeyEventResult::EventAccepted
             }
-            KeyEvent::KeyReleased { code, modifiers }
+            KeyEvent::KeyPressed { code, modifiers }
                 if modifiers.test_exclusive(crate::input::COPY_PASTE_MODIFIER)
-                    && *code == crate::input::KeyCode::C =>
+                    && matches!(
+                        code,
+                        crate::input::KeyCode::C
+                            | crate::input::KeyCode::X
+                            | crate::input::KeyCode::V
+                            | crate::input::KeyCode::A
+                    ) =>
             {
-                self.copy();
+                match code {
+                    crate::input::KeyCode::C => self.copy(window),
+                    crate::input::KeyCode::X => self.cut(window),
+                    crate::input::KeyCode::V => self.paste(window),
+                    _ => self.select_all(window),
+                }
                 KeyEventResult::EventAccepted
             }
-            KeyEvent::KeyReleased { code, modifiers }
+            KeyEvent::KeyPressed { code, modifiers }
                 if modifiers.test_exclusive(crate::input::COPY_PASTE_MODIFIER)
-                    && *code == crate::input::KeyCode::V =>
+                    && *code == crate::input::KeyCode::Z =>
             {
-                self.paste();
+                self.undo(window);
+                KeyEventResult::EventAccepted
+            }
+            KeyEvent::KeyPressed { code, modifiers }
+                if (modifiers.test(crate::input::COPY_PASTE_MODIFIER)
+                    && modifiers.shift()
+                    && !modifiers.alt()
+                    && *code == crate::input::KeyCode::Z)
+                    || (modifiers.test_exclusive(crate::input::COPY_PASTE_MODIFIER)
+                        && *code == crate::input::KeyCode::Y) =>
+            {
+                self.redo(window);
                 KeyEventResult::EventAccepted
             }
             _ => KeyEventResult::EventIgnored,
@@ -1172,6 +1719,7 @@ impl Item for TextInput {
             }
             FocusEvent::FocusOut | FocusEvent::WindowLostFocus => {
                 self.has_focus.set(false);
+                self.clear_preedit();
                 self.hide_cursor()
             }
         }
@@ -1179,12 +1727,18 @@ impl Item for TextInput {
 }
 
 impl TextInput {
-    fn font_pixel_size(self: Pin<&Self>, window: &ComponentWindow) -> f32 {
+    fn font_request(self: Pin<&Self>, window: &ComponentWindow) -> FontRequest {
         let font_size = Self::FIELD_OFFSETS.font_size.apply_pin(self).get();
-        if font_size == 0.0 {
-            DEFAULT_FONT_SIZE * window.scale_factor()
-        } else {
-            font_size
+        FontRequest {
+            family: Self::FIELD_OFFSETS.font_family.apply_pin(self).get(),
+            weight: Self::FIELD_OFFSETS.font_weight.apply_pin(self).get(),
+            italic: Self::FIELD_OFFSETS.font_italic.apply_pin(self).get(),
+            pixel_size: if font_size == 0.0 {
+                DEFAULT_FONT_SIZE * window.scale_factor()
+            } else {
+                font_size
+            },
+            letter_spacing: Self::FIELD_OFFSETS.letter_spacing.apply_pin(self).get(),
         }
     }
 }
@@ -1199,8 +1753,14 @@ impl ItemConsts for TextInput {
 enum TextCursorDirection {
     Forward,
     Backward,
+    PreviousLine,
+    NextLine,
+    PreviousPage,
+    NextPage,
     StartOfLine,
     EndOfLine,
+    StartOfText,
+    EndOfText,
 }
 
 enum AnchorMode {
@@ -1219,8 +1779,35 @@ impl From<KeyboardModifiers> for AnchorMode {
 }
 
 impl TextInput {
-    fn show_cursor(&self, window: &ComponentWindow) {
+    fn show_cursor(self: Pin<&Self>, window: &ComponentWindow) {
         window.set_cursor_blink_binding(&self.cursor_visible);
+        self.ensure_cursor_visible(window);
+    }
+
+    /// Scrolls multi-line text so that the line with the cursor is visible.
+    fn ensure_cursor_visible(self: Pin<&Self>, window: &ComponentWindow) {
+        if Self::FIELD_OFFSETS.single_line.apply_pin(self).get() {
+            return;
+        }
+        let text = self.displayed_text();
+        let cursor_pos = self.displayed_cursor_position();
+        let height = Self::FIELD_OFFSETS.height.apply_pin(self).get();
+        let scroll_offset = TextInput::with_font(self, window, |font| {
+            let lines = self.text_lines(font, &text);
+            let line_height = font.height();
+            let max_scroll_offset = (lines.len() as f32 * line_height - height).max(0.);
+            let cursor_y = line_index_for_offset(&lines, cursor_pos) as f32 * line_height;
+            let scroll_offset = self.scroll_offset.get();
+            let scroll_offset = if cursor_y < scroll_offset {
+                cursor_y
+            } else if cursor_y + line_height > scroll_offset + height {
+                cursor_y + line_height - height
+            } else {
+                scroll_offset
+            };
+            scroll_offset.max(0.).min(max_scroll_offset)
+        });
+        self.scroll_offset.set(scroll_offset);
     }
 
     fn hide_cursor(&self) {
@@ -1233,6 +1820,8 @@ impl TextInput {
         anchor_mode: AnchorMode,
         window: &ComponentWindow,
     ) -> bool {
+        self.data.undo_stack.stop_merging();
+
         let text = Self::FIELD_OFFSETS.text.apply_pin(self).get();
         if text.len() == 0 {
             return false;
@@ -1259,8 +1848,52 @@ impl TextInput {
                     }
                 }
             }
-            TextCursorDirection::StartOfLine => 0,
-            TextCursorDirection::EndOfLine => text.len(),
+            TextCursorDirection::StartOfText => 0,
+            TextCursorDirection::EndOfText => text.len(),
+            _ => TextInput::with_font(self, window, |font| {
+                let text = self.displayed_text();
+                let letter_spacing = Self::FIELD_OFFSETS.letter_spacing.apply_pin(self).get();
+                let last_cursor_pos = self.displayed_offset(last_cursor_pos);
+                let lines = self.text_lines(font, &text);
+                let line_index = line_index_for_offset(&lines, last_cursor_pos);
+                let line = &lines[line_index];
+                let cursor_x = cursor_x_in_line(font, letter_spacing, &text, line, last_cursor_pos);
+                // The offset in another line that is the closest to the cursor
+                let offset_in_line = |index: usize| {
+                    let line = &lines[index];
+                    line.start
+                        + font.text_offset_for_x_position(
+                            &text[line.start..line.end],
+                            cursor_x - line.x,
+                            letter_spacing,
+                        )
+                };
+                let lines_per_page = ((Self::FIELD_OFFSETS.height.apply_pin(self).get()
+                    / font.height())
+                .floor() as usize)
+                    .max(1);
+                let new_cursor_pos = match direction {
+                    TextCursorDirection::StartOfLine => line.start,
+                    TextCursorDirection::EndOfLine => line.end,
+                    TextCursorDirection::PreviousLine | TextCursorDirection::PreviousPage
+                        if line_index == 0 =>
+                    {
+                        0
+                    }
+                    TextCursorDirection::NextLine | TextCursorDirection::NextPage
+                        if line_index == lines.len() - 1 =>
+                    {
+                        text.len()
+                    }
+                    TextCursorDirection::PreviousLine => offset_in_line(line_index - 1),
+                    TextCursorDirection::NextLine => offset_in_line(line_index + 1),
+                    TextCursorDirection::PreviousPage => {
+                        offset_in_line(line_index.saturating_sub(lines_per_page))
+                    }
+                    _ => offset_in_line((line_index + lines_per_page).min(lines.len() - 1)),
+                };
+                self.text_offset(new_cursor_pos)
+            }),
         };
 
         self.as_ref().cursor_position.set(new_cursor_pos as i32);
@@ -1280,10 +1913,14 @@ impl TextInput {
     }
 
     fn delete_char(self: Pin<&Self>, window: &ComponentWindow) {
-        if !self.has_selection() {
-            self.move_cursor(TextCursorDirection::Forward, AnchorMode::KeepAnchor, window);
+        if self.has_selection() {
+            self.delete_selection();
+            return;
+        }
+        let positions = self.cursor_and_anchor();
+        if self.move_cursor(TextCursorDirection::Forward, AnchorMode::KeepAnchor, window) {
+            self.replace_selection_from_positions("", false, positions);
         }
-        self.delete_selection();
     }
 
     fn delete_previous(self: Pin<&Self>, window: &ComponentWindow) {
@@ -1291,29 +1928,185 @@ impl TextInput {
             self.delete_selection();
             return;
         }
-        if self.move_cursor(TextCursorDirection::Backward, AnchorMode::MoveAnchor, window) {
-            self.delete_char(window);
+        let positions = self.cursor_and_anchor();
+        if self.move_cursor(TextCursorDirection::Backward, AnchorMode::KeepAnchor, window) {
+            self.replace_selection_from_positions("", false, positions);
         }
     }
 
     fn delete_selection(self: Pin<&Self>) {
-        let text: String = Self::FIELD_OFFSETS.text.apply_pin(self).get().into();
-        if text.len() == 0 {
+        self.replace_selection("", false);
+    }
+
+    /// Replaces the selected text, records the change in the undo stack and emits `edited`.
+    /// Consecutive `typing` changes are undone together.
+    fn replace_selection(self: Pin<&Self>, replacement: &str, typing: bool) {
+        self.replace_selection_from_positions(replacement, typing, self.cursor_and_anchor())
+    }
+
+    /// Same as `replace_selection`, but undoing restores the given cursor and anchor positions,
+    /// which are the positions before the selection was extended to the text to delete.
+    /// Changes that make the text invalid according to `input_type`, `max_length` or
+    /// `validator` are rejected.
+    fn replace_selection_from_positions(
+        self: Pin<&Self>,
+        replacement: &str,
+        typing: bool,
+        (cursor_before, anchor_before): (i32, i32),
+    ) {
+        let shared_text = Self::FIELD_OFFSETS.text.apply_pin(self).get();
+        let text = shared_text.as_str();
+        let (start, end) = self.selection_anchor_and_cursor();
+        let end = end.min(text.len());
+        let start = start.min(end);
+        if text[start..end] == *replacement {
             return;
         }
 
-        let (anchor, cursor) = self.selection_anchor_and_cursor();
-        if anchor == cursor {
+        let new_text = [&text[..start], replacement, &text[end..]].concat();
+        if !self.accepts(text, &new_text) {
             return;
         }
-
-        let text = [text.split_at(anchor).0, text.split_at(cursor).1].concat();
-        self.cursor_position.set(anchor as i32);
-        self.anchor_position.set(anchor as i32);
-        self.text.set(text.into());
+        let new_cursor_pos = (start + replacement.len()) as i32;
+        self.data.undo_stack.record(
+            text,
+            crate::text_input::Edit {
+                position: start,
+                removed: text[start..end].into(),
+                inserted: replacement.into(),
+                cursor_before,
+                anchor_before,
+                cursor_after: new_cursor_pos,
+                anchor_after: new_cursor_pos,
+            },
+            typing,
+            &new_text,
+        );
+
+        self.cursor_position.set(new_cursor_pos);
+        self.anchor_position.set(new_cursor_pos);
+        self.text.set(new_text.into());
         Self::FIELD_OFFSETS.edited.apply_pin(self).emit(&());
     }
 
+    /// Returns true if `new_text` may replace `old_text`: a change is rejected if it makes a
+    /// valid text invalid. Changes to a text that is already invalid are accepted, so that it is
+    /// possible to fix it. No change is accepted while the `validator` is an invalid pattern.
+    fn accepts(self: Pin<&Self>, old_text: &str, new_text: &str) -> bool {
+        let validator = Self::FIELD_OFFSETS.validator.apply_pin(self).get();
+        if !self.data.is_valid_validator(&validator) {
+            return false;
+        }
+        !self.is_valid(old_text) || self.is_valid(new_text)
+    }
+
+    /// Returns true if the text satisfies `input_type`, `max_length` and `validator`.
+    fn is_valid(self: Pin<&Self>, text: &str) -> bool {
+        if Self::FIELD_OFFSETS.input_type.apply_pin(self).get() == InputType::number
+            && !crate::text_input::is_number_input(text)
+        {
+            return false;
+        }
+        let max_length = Self::FIELD_OFFSETS.max_length.apply_pin(self).get();
+        if max_length > 0 && text.chars().count() > max_length as usize {
+            return false;
+        }
+        let validator = Self::FIELD_OFFSETS.validator.apply_pin(self).get();
+        self.data.matches_validator(&validator, text)
+    }
+
+    fn is_password(self: Pin<&Self>) -> bool {
+        Self::FIELD_OFFSETS.input_type.apply_pin(self).get() == InputType::password
+    }
+
+    /// The text as it is rendered: masked if it is a password, and with the text being composed
+    /// with an input method inserted at the cursor position
+    fn displayed_text(self: Pin<&Self>) -> SharedString {
+        let text = Self::FIELD_OFFSETS.text.apply_pin(self).get();
+        let preedit = Self::FIELD_OFFSETS.preedit_text.apply_pin(self).get();
+        let password = self.is_password();
+        if !password && preedit.is_empty() {
+            return text;
+        }
+        let cursor_pos = Self::FIELD_OFFSETS.cursor_position.apply_pin(self).get().max(0);
+        let (before, after) = text.split_at((cursor_pos as usize).min(text.len()));
+        if password {
+            use crate::text_input::password_mask;
+            [password_mask(before), password_mask(preedit.as_str()), password_mask(after)]
+                .concat()
+                .into()
+        } else {
+            [before, preedit.as_str(), after].concat().into()
+        }
+    }
+
+    /// The length in bytes of the text being composed, in [`Self::displayed_text`]
+    fn displayed_preedit_length(self: Pin<&Self>) -> usize {
+        let preedit = Self::FIELD_OFFSETS.preedit_text.apply_pin(self).get();
+        if self.is_password() {
+            crate::text_input::masked_offset(preedit.as_str(), preedit.len())
+        } else {
+            preedit.len()
+        }
+    }
+
+    /// Converts a byte offset in the text to a byte offset in [`Self::displayed_text`]
+    fn displayed_offset(self: Pin<&Self>, offset: usize) -> usize {
+        let displayed_offset = if self.is_password() {
+            let text = Self::FIELD_OFFSETS.text.apply_pin(self).get();
+            crate::text_input::masked_offset(text.as_str(), offset)
+        } else {
+            offset
+        };
+        let cursor_pos = Self::FIELD_OFFSETS.cursor_position.apply_pin(self).get().max(0);
+        if offset > cursor_pos as usize {
+            displayed_offset + self.displayed_preedit_length()
+        } else {
+            displayed_offset
+        }
+    }
+
+    /// Converts a byte offset in [`Self::displayed_text`] to a byte offset in the text. The
+    /// offsets within the text being composed are converted to the cursor position.
+    fn text_offset(self: Pin<&Self>, displayed_offset: usize) -> usize {
+        let cursor_pos = Self::FIELD_OFFSETS.cursor_position.apply_pin(self).get().max(0);
+        let preedit_start = self.displayed_offset(cursor_pos as usize);
+        let preedit_length = self.displayed_preedit_length();
+        let displayed_offset = if displayed_offset > preedit_start + preedit_length {
+            displayed_offset - preedit_length
+        } else {
+            displayed_offset.min(preedit_start)
+        };
+        if self.is_password() {
+            let text = Self::FIELD_OFFSETS.text.apply_pin(self).get();
+            crate::text_input::unmasked_offset(text.as_str(), displayed_offset)
+        } else {
+            displayed_offset
+        }
+    }
+
+    /// The byte offset in [`Self::displayed_text`] where the cursor is shown, which is within the
+    /// text being composed, if any
+    fn displayed_cursor_position(self: Pin<&Self>) -> usize {
+        let cursor_pos = Self::FIELD_OFFSETS.cursor_position.apply_pin(self).get().max(0);
+        let preedit = Self::FIELD_OFFSETS.preedit_text.apply_pin(self).get();
+        let preedit_cursor = Self::FIELD_OFFSETS.preedit_cursor.apply_pin(self).get().max(0);
+        let preedit_cursor = (preedit_cursor as usize).min(preedit.len());
+        let preedit_cursor = if self.is_password() {
+            crate::text_input::masked_offset(preedit.as_str(), preedit_cursor)
+        } else {
+            preedit_cursor
+        };
+        self.displayed_offset(cursor_pos as usize) + preedit_cursor
+    }
+
+    fn cursor_and_anchor(self: Pin<&Self>) -> (i32, i32) {
+        (
+            Self::FIELD_OFFSETS.cursor_position.apply_pin(self).get(),
+            Self::FIELD_OFFSETS.anchor_position.apply_pin(self).get(),
+        )
+    }
+
     fn selection_anchor_and_cursor(self: Pin<&Self>) -> (usize, usize) {
         let cursor_pos = Self::FIELD_OFFSETS.cursor_position.apply_pin(self).get().max(0);
         let anchor_pos = Self::FIELD_OFFSETS.anchor_position.apply_pin(self).get().max(0);
@@ -1337,47 +2130,296 @@ impl TextInput {
     }
 
     fn insert(self: Pin<&Self>, text_to_insert: &str) {
+        self.replace_selection(text_to_insert, false);
+    }
+
+    /// Copies the selected text to the clipboard of the window. Passwords cannot be copied.
+    pub fn copy(self: Pin<&Self>, window: &ComponentWindow) {
+        if self.has_selection() && !self.is_password() {
+            window.clipboard().set_text(&self.selected_text());
+        }
+    }
+
+    /// Moves the selected text to the clipboard of the window. Passwords cannot be cut.
+    pub fn cut(self: Pin<&Self>, window: &ComponentWindow) {
+        if !Self::FIELD_OFFSETS.enabled.apply_pin(self).get()
+            || !self.has_selection()
+            || self.is_password()
+        {
+            return;
+        }
+        self.copy(window);
         self.delete_selection();
-        let mut text: String = Self::FIELD_OFFSETS.text.apply_pin(self).get().into();
-        let cursor_pos = self.selection_anchor_and_cursor().1;
-        text.insert_str(cursor_pos, text_to_insert);
-        let cursor_pos = cursor_pos + text_to_insert.len();
-        self.cursor_position.set(cursor_pos as i32);
-        self.anchor_position.set(cursor_pos as i32);
-        self.text.set(text.into());
-        Self::FIELD_OFFSETS.edited.apply_pin(self).emit(&());
+        self.show_cursor(window);
     }
 
-    fn copy(self: Pin<&Self>) {
-        use copypasta::ClipboardProvider;
-        CLIPBOARD.with(|clipboard| clipboard.borrow_mut().set_contents(self.selected_text()).ok());
+    /// Replaces the selection with the text of the clipboard of the window. Line breaks are
+    /// replaced by spaces in a single line text input.
+    pub fn paste(self: Pin<&Self>, window: &ComponentWindow) {
+        if !Self::FIELD_OFFSETS.enabled.apply_pin(self).get() {
+            return;
+        }
+        if let Some(text) = window.clipboard().text() {
+            self.insert_external_text(&text);
+            self.show_cursor(window);
+        }
     }
 
-    fn paste(self: Pin<&Self>) {
-        use copypasta::ClipboardProvider;
-        if let Some(text) = CLIPBOARD.with(|clipboard| clipboard.borrow_mut().get_contents().ok()) {
-            self.insert(&text);
+    /// Inserts text that was not typed, replacing line breaks by spaces in a single line text
+    /// input.
+    fn insert_external_text(self: Pin<&Self>, text: &str) {
+        if Self::FIELD_OFFSETS.single_line.apply_pin(self).get() {
+            self.insert(&text.replace(|ch: char| ch == '\n' || ch == '\r', " "));
+        } else {
+            self.insert(text);
         }
     }
 
+    fn clear_preedit(self: Pin<&Self>) {
+        self.preedit_text.set(Default::default());
+        self.preedit_cursor.set(0);
+    }
+
+    /// Selects the whole text.
+    pub fn select_all(self: Pin<&Self>, window: &ComponentWindow) {
+        self.move_cursor(TextCursorDirection::StartOfText, AnchorMode::MoveAnchor, window);
+        self.move_cursor(TextCursorDirection::EndOfText, AnchorMode::KeepAnchor, window);
+    }
+
+    /// Returns the byte offset in the text that is the closest to the given position.
+    fn offset_for_position(self: Pin<&Self>, pos: Point, window: &ComponentWindow) -> i32 {
+        TextInput::with_font(self, window, |font| {
+            let text = self.displayed_text();
+            let lines = self.visible_lines(font, &text);
+            let line_index = ((pos.y - lines[0].y) / font.height()).floor().max(0.) as usize;
+            let line = &lines[line_index.min(lines.len() - 1)];
+            self.text_offset(
+                line.start
+                    + font.text_offset_for_x_position(
+                        &text[line.start..line.end],
+                        pos.x - line.x,
+                        Self::FIELD_OFFSETS.letter_spacing.apply_pin(self).get(),
+                    ),
+            )
+        }) as i32
+    }
+
+    /// Moves the cursor to the position where the mouse is dragged, extending the selection, and
+    /// scrolls the text if that position is beyond the edges.
+    fn drag_selection(self: Pin<&Self>, window: &ComponentWindow) {
+        let offset = self.offset_for_position(self.data.drag_position.get(), window);
+        self.as_ref().cursor_position.set(offset);
+        self.ensure_cursor_visible(window);
+    }
+
+    /// Selects the word around the given byte offset, or the character after it if it is not
+    /// part of a word. The words of a password are not revealed, the whole text is selected.
+    fn select_word(self: Pin<&Self>, offset: usize) {
+        let text = Self::FIELD_OFFSETS.text.apply_pin(self).get();
+        if self.is_password() {
+            self.as_ref().anchor_position.set(0);
+            self.as_ref().cursor_position.set(text.len() as i32);
+            return;
+        }
+        let offset = offset.min(text.len());
+        let is_word_char = |ch: char| ch.is_alphanumeric() || ch == '_';
+        let start = text[..offset]
+            .char_indices()
+            .rev()
+            .take_while(|(_, ch)| is_word_char(*ch))
+            .last()
+            .map_or(offset, |(index, _)| index);
+        let mut end = text[offset..]
+            .char_indices()
+            .find(|(_, ch)| !is_word_char(*ch))
+            .map_or(text.len(), |(index, _)| offset + index);
+        if start == end {
+            end = text[offset..].chars().next().map_or(offset, |ch| offset + ch.len_utf8());
+        }
+        self.as_ref().anchor_position.set(start as i32);
+        self.as_ref().cursor_position.set(end as i32);
+    }
+
+    /// Reverts the last change made by the user, and restores the cursor and the selection as
+    /// they were before it.
+    pub fn undo(self: Pin<&Self>, window: &ComponentWindow) {
+        if !Self::FIELD_OFFSETS.enabled.apply_pin(self).get() {
+            return;
+        }
+        let text = Self::FIELD_OFFSETS.text.apply_pin(self).get();
+        if let Some(result) = self.data.undo_stack.undo(text.as_str()) {
+            self.apply_undo_result(result, window);
+        }
+    }
+
+    /// Applies again the last change that was reverted with `undo()`.
+    pub fn redo(self: Pin<&Self>, window: &ComponentWindow) {
+        if !Self::FIELD_OFFSETS.enabled.apply_pin(self).get() {
+            return;
+        }
+        let text = Self::FIELD_OFFSETS.text.apply_pin(self).get();
+        if let Some(result) = self.data.undo_stack.redo(text.as_str()) {
+            self.apply_undo_result(result, window);
+        }
+    }
+
+    fn apply_undo_result(
+        self: Pin<&Self>,
+        result: crate::text_input::UndoResult,
+        window: &ComponentWindow,
+    ) {
+        self.cursor_position.set(result.cursor);
+        self.anchor_position.set(result.anchor);
+        self.text.set(result.text.into());
+        Self::FIELD_OFFSETS.edited.apply_pin(self).emit(&());
+        self.show_cursor(window);
+    }
+
+    /// Lays out the text in the geometry of the element. Multi-line text wraps if requested.
+    fn text_lines(self: Pin<&Self>, font: &crate::font::Font, text: &str) -> Vec<TextLine> {
+        let single_line = Self::FIELD_OFFSETS.single_line.apply_pin(self).get();
+        TextLayout {
+            font,
+            text,
+            letter_spacing: Self::FIELD_OFFSETS.letter_spacing.apply_pin(self).get(),
+            width: available_length(Self::FIELD_OFFSETS.width.apply_pin(self).get()),
+            height: available_length(Self::FIELD_OFFSETS.height.apply_pin(self).get()),
+            wrap: if single_line {
+                TextWrap::no_wrap
+            } else {
+                Self::FIELD_OFFSETS.wrap.apply_pin(self).get()
+            },
+            overflow: TextOverflow::clip,
+            horizontal_alignment: Self::FIELD_OFFSETS
+                .horizontal_alignment
+                .apply_pin(self)
+                .get()
+                .for_direction(Self::FIELD_OFFSETS.layout_direction.apply_pin(self).get()),
+            vertical_alignment: Self::FIELD_OFFSETS.vertical_alignment.apply_pin(self).get(),
+        }
+        .lines()
+    }
+
+    /// The lines of [`Self::text_lines`], scrolled if multi-line text does not fit in the height
+    /// of the element.
+    fn visible_lines(self: Pin<&Self>, font: &crate::font::Font, text: &str) -> Vec<TextLine> {
+        let mut lines = self.text_lines(font, text);
+        let line_height = font.height();
+        let max_scroll_offset =
+            lines.len() as f32 * line_height - Self::FIELD_OFFSETS.height.apply_pin(self).get();
+        if !Self::FIELD_OFFSETS.single_line.apply_pin(self).get() && max_scroll_offset > 0. {
+            let scroll_offset = Self::FIELD_OFFSETS
+                .scroll_offset
+                .apply_pin(self)
+                .get()
+                .max(0.)
+                .min(max_scroll_offset);
+            for (index, line) in lines.iter_mut().enumerate() {
+                line.y = index as f32 * line_height - scroll_offset;
+            }
+        }
+        lines
+    }
+
+    /// Returns the rectangle of the text cursor, relative to the element, whether the cursor is
+    /// currently shown or not. It is reported to the input method of the windowing system.
+    pub fn cursor_rect(self: Pin<&Self>, window: &ComponentWindow) -> Rect {
+        let text = self.displayed_text();
+        let letter_spacing = Self::FIELD_OFFSETS.letter_spacing.apply_pin(self).get();
+        TextInput::with_font(self, window, |font| {
+            let lines = self.visible_lines(font, &text);
+            self.cursor_rect_in_lines(font, letter_spacing, &text, &lines, window)
+        })
+    }
+
+    fn cursor_rect_in_lines(
+        self: Pin<&Self>,
+        font: &crate::font::Font,
+        letter_spacing: f32,
+        text: &str,
+        lines: &[TextLine],
+        window: &ComponentWindow,
+    ) -> Rect {
+        let cursor_pos = self.displayed_cursor_position();
+        let line = &lines[line_index_for_offset(lines, cursor_pos)];
+        let cursor_x = cursor_x_in_line(font, letter_spacing, text, line, cursor_pos);
+        let cursor_width =
+            Self::FIELD_OFFSETS.text_cursor_width.apply_pin(self).get() * window.scale_factor();
+        euclid::rect(cursor_x, line.y, cursor_width, font.height())
+    }
+
     fn with_font<R>(
         self: Pin<&Self>,
         window: &ComponentWindow,
         callback: impl FnOnce(&crate::font::Font) -> R,
     ) -> R {
-        let font_family = Self::FIELD_OFFSETS.font_family.apply_pin(self).get();
-        let font_size = TextInput::font_pixel_size(self, window);
+        let font_request = TextInput::font_request(self, window);
         crate::font::FONT_CACHE.with(|fc| {
-            let font = fc.find_font(&font_family, font_size);
+            let font = fc.find_font(&font_request);
             callback(&font)
         })
     }
 }
 
+/// Returns the index of the line that contains the byte offset. An offset in the white space or the
+/// line break at the end of a line belongs to that line.
+fn line_index_for_offset(lines: &[TextLine], offset: usize) -> usize {
+    lines.iter().rposition(|line| line.start <= offset).unwrap_or(0)
+}
+
+/// Returns the x position of the cursor at the byte offset in the line.
+fn cursor_x_in_line(
+    font: &crate::font::Font,
+    letter_spacing: f32,
+    text: &str,
+    line: &TextLine,
+    offset: usize,
+) -> f32 {
+    let line_text = &text[line.start..line.end.max(offset)];
+    line.x + font.cursor_x_position(line_text, offset - line.start, letter_spacing)
+}
+
 ItemVTable_static! {
     /// The VTable for `TextInput`
     #[no_mangle]
     pub static TextInputVTable for TextInput
 }
 
-thread_local!(pub(crate) static CLIPBOARD : std::cell::RefCell<copypasta::ClipboardContext> = std::cell::RefCell::new(copypasta::ClipboardContext::new().unwrap()));
+/// Copies the selected text of the TextInput to the clipboard.
+#[no_mangle]
+pub extern "C" fn sixtyfps_textinput_copy(text_input: Pin<&TextInput>, window: &ComponentWindow) {
+    text_input.copy(window)
+}
+
+/// Moves the selected text of the TextInput to the clipboard.
+#[no_mangle]
+pub extern "C" fn sixtyfps_textinput_cut(text_input: Pin<&TextInput>, window: &ComponentWindow) {
+    text_input.cut(window)
+}
+
+/// Replaces the selection of the TextInput with the text of the clipboard.
+#[no_mangle]
+pub extern "C" fn sixtyfps_textinput_paste(text_input: Pin<&TextInput>, window: &ComponentWindow) {
+    text_input.paste(window)
+}
+
+/// Selects the whole text of the TextInput.
+#[no_mangle]
+pub extern "C" fn sixtyfps_textinput_select_all(
+    text_input: Pin<&TextInput>,
+    window: &ComponentWindow,
+) {
+    text_input.select_all(window)
+}
+
+/// Reverts the last change of the text of the TextInput.
+#[no_mangle]
+pub extern "C" fn sixtyfps_textinput_undo(text_input: Pin<&TextInput>, window: &ComponentWindow) {
+    text_input.undo(window)
+}
+
+/// Applies again the last change of the text of the TextInput that was reverted.
+#[no_mangle]
+pub extern "C" fn sixtyfps_textinput_redo(text_input: Pin<&TextInput>, window: &ComponentWindow) {
+    text_input.redo(window)
+}
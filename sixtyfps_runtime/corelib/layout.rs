@@ -27,11 +27,20 @@ pub struct LayoutInfo {
     pub min_height: f32,
     /// The maximum height for the item.
     pub max_height: f32,
+    /// The natural width of the item, from which the layouts grow or shrink it. 0 means that
+    /// the item has no preference.
+    pub preferred_width: f32,
+    /// The natural height of the item, from which the layouts grow or shrink it. 0 means that
+    /// the item has no preference.
+    pub preferred_height: f32,
 
     /// the horizontal stretch factor
     pub horizontal_stretch: f32,
     /// the vertical stretch factor
     pub vertical_stretch: f32,
+    /// The ratio between the width and the height that the item must keep. 0 means that the item
+    /// has no aspect ratio.
+    pub aspect_ratio: f32,
 }
 
 impl Default for LayoutInfo {
@@ -41,8 +50,11 @@ impl Default for LayoutInfo {
             max_width: f32::MAX,
             min_height: 0.,
             max_height: f32::MAX,
+            preferred_width: 0.,
+            preferred_height: 0.,
             horizontal_stretch: 0.,
             vertical_stretch: 0.,
+            aspect_ratio: 0.,
         }
     }
 }
@@ -55,9 +67,64 @@ impl LayoutInfo {
             max_width: self.max_width.min(other.max_width),
             min_height: self.min_height.max(other.min_height),
             max_height: self.max_height.min(other.max_height),
+            preferred_width: self.preferred_width.max(other.preferred_width),
+            preferred_height: self.preferred_height.max(other.preferred_height),
             horizontal_stretch: self.horizontal_stretch.min(other.horizontal_stretch),
             vertical_stretch: self.vertical_stretch.min(other.vertical_stretch),
+            aspect_ratio: if self.aspect_ratio > 0. {
+                self.aspect_ratio
+            } else {
+                other.aspect_ratio
+            },
+        }
+        .fit_aspect_ratio()
+    }
+
+    /// Restrict the width constraints by the height constraints and the other way around, so
+    /// that any size between the minimum and the maximum can keep the aspect ratio.
+    pub fn fit_aspect_ratio(mut self) -> Self {
+        let ratio = self.aspect_ratio;
+        if ratio <= 0. {
+            return self;
+        }
+        self.min_width = self.min_width.max(self.min_height * ratio);
+        self.min_height = self.min_height.max(self.min_width / ratio);
+        if self.max_height < f32::MAX {
+            self.max_width = self.max_width.min(self.max_height * ratio);
+        }
+        if self.max_width < f32::MAX {
+            self.max_height = self.max_height.min(self.max_width / ratio);
+        }
+        if self.preferred_width == 0. {
+            self.preferred_width = self.preferred_height * ratio;
+        } else {
+            self.preferred_height = self.preferred_width / ratio;
+        }
+        self
+    }
+
+    /// The constraint of an item that has the given width: with an aspect ratio, the height
+    /// follows from the width. The width is ignored if it is not positive, which is the case
+    /// before the item was laid out.
+    pub fn for_width(&self, width: f32) -> Self {
+        let info = self.fit_aspect_ratio();
+        if info.aspect_ratio <= 0. || width <= 0. {
+            return info;
         }
+        let height = (width / info.aspect_ratio).min(info.max_height).max(info.min_height);
+        Self { min_height: height, max_height: height, preferred_height: height, ..info }
+    }
+
+    /// The width from which the layouts grow or shrink the item: the preferred width, but not
+    /// less than the minimum width.
+    pub fn base_width(&self) -> f32 {
+        self.preferred_width.max(self.min_width)
+    }
+
+    /// The height from which the layouts grow or shrink the item: the preferred height, but not
+    /// less than the minimum height.
+    pub fn base_height(&self) -> f32 {
+        self.preferred_height.max(self.min_height)
     }
 }
 
@@ -210,11 +277,12 @@ pub struct GridLayoutData<'a> {
     pub y: Coord,
     pub spacing: Coord,
     pub padding: &'a Padding,
+    pub direction: LayoutDirection,
     pub cells: Slice<'a, GridLayoutCellData<'a>>,
 }
 
 #[repr(C)]
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct GridLayoutCellData<'a> {
     pub col: u16,
     pub row: u16,
@@ -227,94 +295,126 @@ pub struct GridLayoutCellData<'a> {
     pub height: Option<&'a Property<Coord>>,
 }
 
+/// Layouts are solved in two passes: the horizontal pass sets the x position and the width of the
+/// cells. Then the constraints of the cells are queried again, so that the items whose height
+/// depends on their width (such as wrapping text) can report the height they need for the width
+/// they got. The vertical pass then sets the y position and the height of the cells.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LayoutPass {
+    Horizontal,
+    Vertical,
+}
+
+/// The width that the horizontal pass gave to a cell, or 0 if it was not laid out yet
+fn current_width(width: Option<&Property<Coord>>) -> Coord {
+    width.map_or(0., |w| w.get())
+}
+
+/// The cells with their constraint adjusted to their current width, for the cells that have an
+/// aspect ratio.
+fn grid_cells_for_width<'a>(cells: &[GridLayoutCellData<'a>]) -> Vec<GridLayoutCellData<'a>> {
+    cells
+        .iter()
+        .map(|c| GridLayoutCellData {
+            constraint: c.constraint.for_width(current_width(c.width)),
+            ..c.clone()
+        })
+        .collect()
+}
+
+/// The cells with their constraint adjusted to their current width, for the cells that have an
+/// aspect ratio.
+fn box_cells_for_width<'a>(cells: &[BoxLayoutCellData<'a>]) -> Vec<BoxLayoutCellData<'a>> {
+    cells
+        .iter()
+        .map(|c| BoxLayoutCellData {
+            constraint: c.constraint.for_width(current_width(c.width)),
+            ..c.clone()
+        })
+        .collect()
+}
+
 /// FIXME: rename with sixstyfps prefix
 #[no_mangle]
-pub extern "C" fn solve_grid_layout(data: &GridLayoutData) {
-    let (mut num_col, mut num_row) = (0, 0);
-    for cell in data.cells.iter() {
-        num_row = num_row.max(cell.row + cell.rowspan);
-        num_col = num_col.max(cell.col + cell.colspan);
-    }
+pub extern "C" fn solve_grid_layout(data: &GridLayoutData, pass: LayoutPass) {
+    let is_horizontal = pass == LayoutPass::Horizontal;
 
-    if num_col < 1 || num_row < 1 {
-        return;
-    }
-
-    let mut row_layout_data = vec![grid_internal::LayoutData::default(); num_row as usize];
-    let mut col_layout_data = vec![grid_internal::LayoutData::default(); num_col as usize];
-    for cell in data.cells.iter() {
-        let row_max = cell.constraint.max_height / (cell.rowspan as f32);
-        let row_min = cell.constraint.min_height / (cell.rowspan as f32);
-        let row_pref = cell.constraint.min_height / (cell.rowspan as f32);
-
-        for r in 0..(cell.rowspan as usize) {
-            let rdata = &mut row_layout_data[cell.row as usize + r];
-            rdata.max = rdata.max.min(row_max);
-            rdata.min = rdata.min.max(row_min);
-            rdata.pref = rdata.pref.max(row_pref);
-            rdata.stretch = rdata.stretch.min(cell.constraint.vertical_stretch);
+    // The first column (or row) of a cell and how many it spans, in the direction of the pass
+    let cell_range = |cell: &GridLayoutCellData| {
+        if is_horizontal {
+            (cell.col as usize, cell.colspan as usize)
+        } else {
+            (cell.row as usize, cell.rowspan as usize)
         }
+    };
+
+    let cells = grid_cells_for_width(&data.cells);
+    let num = cells.iter().map(&cell_range).map(|(start, span)| start + span).max();
+    let num = match num {
+        Some(num) if num >= 1 => num,
+        _ => return,
+    };
 
-        let col_max = cell.constraint.max_width / (cell.colspan as f32);
-        let col_min = cell.constraint.min_width / (cell.colspan as f32);
-        let col_pref = cell.constraint.min_width / (cell.colspan as f32);
+    let mut layout_data = vec![grid_internal::LayoutData::default(); num];
+    for cell in cells.iter() {
+        let (start, span) = cell_range(cell);
+        let c = &cell.constraint;
+        let (max, min, pref, stretch) = if is_horizontal {
+            (c.max_width, c.min_width, c.base_width(), c.horizontal_stretch)
+        } else {
+            (c.max_height, c.min_height, c.base_height(), c.vertical_stretch)
+        };
 
-        for c in 0..(cell.colspan as usize) {
-            let cdata = &mut col_layout_data[cell.col as usize + c];
-            cdata.max = cdata.max.min(col_max);
-            cdata.min = cdata.min.max(col_min);
-            cdata.pref = cdata.pref.max(col_pref);
-            cdata.stretch = cdata.stretch.min(cell.constraint.horizontal_stretch);
+        for d in &mut layout_data[start..start + span] {
+            d.max = d.max.min(max / span as f32);
+            d.min = d.min.max(min / span as f32);
+            d.pref = d.pref.max(pref / span as f32);
+            d.stretch = d.stretch.min(stretch);
         }
     }
 
     // Normalize so that all the values are 1 or more
-    let normalize_stretch = |v: &mut Vec<grid_internal::LayoutData>| {
-        let mut small: Option<f32> = None;
-        v.iter().for_each(|x| {
-            if x.stretch > 0. {
-                small = Some(small.map(|y| y.min(x.stretch)).unwrap_or(x.stretch))
-            }
-        });
-        if small.unwrap_or(0.) < 1. {
-            v.iter_mut()
-                .for_each(|x| x.stretch = if let Some(s) = small { x.stretch / s } else { 1. })
+    let mut small: Option<f32> = None;
+    layout_data.iter().for_each(|x| {
+        if x.stretch > 0. {
+            small = Some(small.map(|y| y.min(x.stretch)).unwrap_or(x.stretch))
+        }
+    });
+    if small.unwrap_or(0.) < 1. {
+        layout_data
+            .iter_mut()
+            .for_each(|x| x.stretch = if let Some(s) = small { x.stretch / s } else { 1. })
+    }
+
+    if is_horizontal {
+        grid_internal::layout_items(
+            &mut layout_data,
+            data.x + data.padding.left,
+            data.width - (data.padding.left + data.padding.right),
+            data.spacing,
+        );
+    } else {
+        grid_internal::layout_items(
+            &mut layout_data,
+            data.y + data.padding.top,
+            data.height - (data.padding.top + data.padding.bottom),
+            data.spacing,
+        );
+    }
+
+    for cell in cells.iter() {
+        let (start, span) = cell_range(cell);
+        let first_cell = &layout_data[start];
+        let last_cell = &layout_data[start + span - 1];
+        let size = last_cell.pos + last_cell.size - first_cell.pos;
+        if is_horizontal {
+            cell.x.map(|p| p.set(data.direction.cell_x(data.x, data.width, first_cell.pos, size)));
+            cell.width.map(|p| p.set(size));
+        } else {
+            cell.y.map(|p| p.set(first_cell.pos));
+            cell.height.map(|p| p.set(size));
         }
-    };
-    normalize_stretch(&mut row_layout_data);
-    normalize_stretch(&mut col_layout_data);
-
-    grid_internal::layout_items(
-        &mut row_layout_data,
-        data.y + data.padding.top,
-        data.height - (data.padding.top + data.padding.bottom),
-        data.spacing,
-    );
-    grid_internal::layout_items(
-        &mut col_layout_data,
-        data.x + data.padding.left,
-        data.width - (data.padding.left + data.padding.right),
-        data.spacing,
-    );
-    for cell in data.cells.iter() {
-        let rdata = &row_layout_data[cell.row as usize];
-        let cdata = &col_layout_data[cell.col as usize];
-        cell.x.map(|p| p.set(cdata.pos));
-        cell.width.map(|p| {
-            p.set({
-                let first_cell = &col_layout_data[cell.col as usize];
-                let last_cell = &col_layout_data[cell.col as usize + cell.colspan as usize - 1];
-                last_cell.pos + last_cell.size - first_cell.pos
-            })
-        });
-        cell.y.map(|p| p.set(rdata.pos));
-        cell.height.map(|p| {
-            p.set({
-                let first_cell = &row_layout_data[cell.row as usize];
-                let last_cell = &row_layout_data[cell.row as usize + cell.rowspan as usize - 1];
-                last_cell.pos + last_cell.size - first_cell.pos
-            })
-        });
     }
 }
 
@@ -324,6 +424,7 @@ pub extern "C" fn grid_layout_info<'a>(
     spacing: Coord,
     padding: &Padding,
 ) -> LayoutInfo {
+    let cells = grid_cells_for_width(cells);
     let (mut num_col, mut num_row) = (0, 0);
     for cell in cells.iter() {
         num_row = num_row.max(cell.row + cell.rowspan);
@@ -343,8 +444,8 @@ pub extern "C" fn grid_layout_info<'a>(
         cdata.max = cdata.max.min(cell.constraint.max_width);
         rdata.min = rdata.min.max(cell.constraint.min_height);
         cdata.min = cdata.min.max(cell.constraint.min_width);
-        rdata.pref = rdata.pref.max(cell.constraint.min_height);
-        cdata.pref = cdata.pref.max(cell.constraint.min_width);
+        rdata.pref = rdata.pref.max(cell.constraint.base_height());
+        cdata.pref = cdata.pref.max(cell.constraint.base_width());
         rdata.stretch = rdata.stretch.min(cell.constraint.vertical_stretch);
         cdata.stretch = cdata.stretch.min(cell.constraint.horizontal_stretch);
     }
@@ -368,6 +469,14 @@ pub extern "C" fn grid_layout_info<'a>(
         + spacing_w
         + padding.left
         + padding.right;
+    let preferred_height = row_layout_data.iter().map(|data| data.pref).sum::<Coord>()
+        + spacing_h
+        + padding.top
+        + padding.bottom;
+    let preferred_width = col_layout_data.iter().map(|data| data.pref).sum::<Coord>()
+        + spacing_w
+        + padding.left
+        + padding.right;
 
     let horizontal_stretch = col_layout_data.iter().map(|data| data.stretch).sum::<Coord>();
     let vertical_stretch = row_layout_data.iter().map(|data| data.stretch).sum::<Coord>();
@@ -377,8 +486,11 @@ pub extern "C" fn grid_layout_info<'a>(
         max_width,
         min_height,
         max_height,
+        preferred_width,
+        preferred_height,
         horizontal_stretch,
         vertical_stretch,
+        aspect_ratio: 0.,
     }
 }
 
@@ -401,6 +513,62 @@ impl Default for LayoutAlignment {
     }
 }
 
+/// Enum representing the direction in which the layouts place their children horizontally
+#[derive(Copy, Clone, Debug, PartialEq, strum_macros::EnumString, strum_macros::Display)]
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub enum LayoutDirection {
+    /// From left to right
+    ltr,
+    /// From right to left, for languages such as Arabic or Hebrew
+    rtl,
+}
+
+impl Default for LayoutDirection {
+    fn default() -> Self {
+        Self::ltr
+    }
+}
+
+impl LayoutDirection {
+    /// Returns the direction of the language of the given locale, such as "he_IL.UTF-8" or "ar-EG"
+    pub fn from_locale(locale: &str) -> Self {
+        let language = locale.split(|c| c == '_' || c == '-' || c == '.').next().unwrap_or("");
+        match language.to_ascii_lowercase().as_str() {
+            "ar" | "arc" | "ckb" | "dv" | "fa" | "he" | "iw" | "ks" | "ps" | "sd" | "ug" | "ur"
+            | "yi" => Self::rtl,
+            _ => Self::ltr,
+        }
+    }
+
+    /// Returns the x position of a cell of the given `width` placed at `x` in a layout that starts
+    /// at `layout_x` and is `layout_width` wide: right to left, the cell is mirrored in the layout.
+    fn cell_x(self, layout_x: Coord, layout_width: Coord, x: Coord, width: Coord) -> Coord {
+        match self {
+            Self::ltr => x,
+            Self::rtl => layout_x + layout_width - (x - layout_x) - width,
+        }
+    }
+}
+
+/// Returns the direction of the language of the given locale, such as "he_IL.UTF-8" or "ar-EG"
+#[no_mangle]
+pub extern "C" fn sixtyfps_layout_direction_from_locale(
+    locale: &crate::SharedString,
+) -> LayoutDirection {
+    LayoutDirection::from_locale(locale.as_str())
+}
+
+#[test]
+fn test_layout_direction_from_locale() {
+    assert_eq!(LayoutDirection::from_locale("he_IL.UTF-8"), LayoutDirection::rtl);
+    assert_eq!(LayoutDirection::from_locale("ar-EG"), LayoutDirection::rtl);
+    assert_eq!(LayoutDirection::from_locale("fa"), LayoutDirection::rtl);
+    assert_eq!(LayoutDirection::from_locale("fr_FR.UTF-8"), LayoutDirection::ltr);
+    assert_eq!(LayoutDirection::from_locale("C"), LayoutDirection::ltr);
+    assert_eq!(LayoutDirection::from_locale(""), LayoutDirection::ltr);
+}
+
 impl From<LayoutAlignment> for stretch::style::JustifyContent {
     fn from(a: LayoutAlignment) -> Self {
         match a {
@@ -440,6 +608,7 @@ pub struct BoxLayoutData<'a> {
     pub spacing: Coord,
     pub padding: &'a Padding,
     pub alignment: LayoutAlignment,
+    pub direction: LayoutDirection,
     pub cells: Slice<'a, BoxLayoutCellData<'a>>,
 }
 
@@ -453,9 +622,9 @@ pub struct BoxLayoutCellData<'a> {
     pub height: Option<&'a Property<Coord>>,
 }
 
-/// Solve a BoxLayout
+/// Solve a BoxLayout. Only the geometry in the direction of the `pass` is set on the cells.
 #[no_mangle]
-pub extern "C" fn solve_box_layout(data: &BoxLayoutData, is_horizontal: bool) {
+pub extern "C" fn solve_box_layout(data: &BoxLayoutData, is_horizontal: bool, pass: LayoutPass) {
     use stretch::geometry::*;
     use stretch::number::*;
     use stretch::style::*;
@@ -471,6 +640,15 @@ pub extern "C" fn solve_box_layout(data: &BoxLayoutData, is_horizontal: bool) {
         ..Default::default()
     };
 
+    let mut cells = box_cells_for_width(&data.cells);
+    if is_horizontal {
+        // The cells with an aspect ratio cannot be wider than what the height of the layout allows
+        let height = data.height - (data.padding.top + data.padding.bottom);
+        for c in cells.iter_mut().filter(|c| c.constraint.aspect_ratio > 0.) {
+            c.constraint.max_width = c.constraint.max_width.min(height * c.constraint.aspect_ratio);
+        }
+    }
+
     let stretch_factor = |cell: &BoxLayoutCellData| {
         if is_horizontal {
             cell.constraint.horizontal_stretch
@@ -479,7 +657,7 @@ pub extern "C" fn solve_box_layout(data: &BoxLayoutData, is_horizontal: bool) {
         }
     };
     let mut smaller_strecth: Option<f32> = None;
-    data.cells.iter().map(stretch_factor).for_each(|x| {
+    cells.iter().map(stretch_factor).for_each(|x| {
         if x > 0. {
             smaller_strecth = Some(smaller_strecth.map(|y| y.min(x)).unwrap_or(x))
         }
@@ -489,20 +667,20 @@ pub extern "C" fn solve_box_layout(data: &BoxLayoutData, is_horizontal: bool) {
 
     let flex_box = stretch.new_node(box_style, vec![]).unwrap();
 
-    for (index, cell) in data.cells.iter().enumerate() {
+    for (index, cell) in cells.iter().enumerate() {
         let mut margin = Rect::default();
         if is_horizontal {
             if index != 0 {
                 margin.start = Dimension::Points(data.spacing / 2.);
             }
-            if index != data.cells.len() - 1 {
+            if index != cells.len() - 1 {
                 margin.end = Dimension::Points(data.spacing / 2.);
             }
         } else {
             if index != 0 {
                 margin.top = Dimension::Points(data.spacing / 2.);
             }
-            if index != data.cells.len() - 1 {
+            if index != cells.len() - 1 {
                 margin.bottom = Dimension::Points(data.spacing / 2.);
             }
         }
@@ -522,7 +700,11 @@ pub extern "C" fn solve_box_layout(data: &BoxLayoutData, is_horizontal: bool) {
             } else {
                 1.
             },
-            flex_basis: if is_horizontal { min_size.width } else { min_size.height },
+            flex_basis: min(if is_horizontal {
+                cell.constraint.base_width()
+            } else {
+                cell.constraint.base_height()
+            }),
             margin,
             align_self: AlignSelf::Stretch,
             ..Default::default()
@@ -545,13 +727,25 @@ pub extern "C" fn solve_box_layout(data: &BoxLayoutData, is_horizontal: bool) {
     let start_pos_x = data.x + data.padding.left;
     let start_pos_y = data.y + data.padding.top;
 
-    for (cell, layout) in data.cells.iter().zip(
+    for (cell, layout) in cells.iter().zip(
         stretch.children(flex_box).unwrap().iter().map(|child| stretch.layout(*child).unwrap()),
     ) {
-        cell.x.map(|p| p.set(start_pos_x + layout.location.x));
-        cell.y.map(|p| p.set(start_pos_y + layout.location.y));
-        cell.width.map(|p| p.set(layout.size.width));
-        cell.height.map(|p| p.set(layout.size.height));
+        match pass {
+            LayoutPass::Horizontal => {
+                let x = data.direction.cell_x(
+                    data.x,
+                    data.width,
+                    start_pos_x + layout.location.x,
+                    layout.size.width,
+                );
+                cell.x.map(|p| p.set(x));
+                cell.width.map(|p| p.set(layout.size.width));
+            }
+            LayoutPass::Vertical => {
+                cell.y.map(|p| p.set(start_pos_y + layout.location.y));
+                cell.height.map(|p| p.set(layout.size.height));
+            }
+        }
     }
 }
 
@@ -564,6 +758,7 @@ pub extern "C" fn box_layout_info<'a>(
     alignment: LayoutAlignment,
     is_horizontal: bool,
 ) -> LayoutInfo {
+    let cells = box_cells_for_width(cells);
     let count = cells.len();
     if count < 1 {
         return LayoutInfo { max_width: 0., max_height: 0., ..LayoutInfo::default() };
@@ -581,6 +776,12 @@ pub extern "C" fn box_layout_info<'a>(
             + padding.top
             + padding.bottom;
         let min_width = cells.iter().map(|c| c.constraint.min_width).sum::<Coord>() + extra_w;
+        let preferred_width =
+            cells.iter().map(|c| c.constraint.base_width()).sum::<Coord>() + extra_w;
+        let preferred_height =
+            cells.iter().map(|c| c.constraint.base_height()).max_by(order_float).unwrap()
+                + padding.top
+                + padding.bottom;
         let max_width = if is_stretch {
             cells.iter().map(|c| c.constraint.max_width).sum::<Coord>() + extra_w
         } else {
@@ -594,8 +795,11 @@ pub extern "C" fn box_layout_info<'a>(
             max_width,
             min_height,
             max_height,
+            preferred_width,
+            preferred_height,
             horizontal_stretch,
             vertical_stretch,
+            aspect_ratio: 0.,
         }
     } else {
         let extra_h = padding.top + padding.bottom + spacing * (count - 1) as Coord;
@@ -607,6 +811,12 @@ pub extern "C" fn box_layout_info<'a>(
             + padding.left
             + padding.right;
         let min_height = cells.iter().map(|c| c.constraint.min_height).sum::<Coord>() + extra_h;
+        let preferred_height =
+            cells.iter().map(|c| c.constraint.base_height()).sum::<Coord>() + extra_h;
+        let preferred_width =
+            cells.iter().map(|c| c.constraint.base_width()).max_by(order_float).unwrap()
+                + padding.left
+                + padding.right;
         let max_height = if is_stretch {
             cells.iter().map(|c| c.constraint.max_height).sum::<Coord>() + extra_h
         } else {
@@ -620,9 +830,257 @@ pub extern "C" fn box_layout_info<'a>(
             max_width,
             min_height,
             max_height,
+            preferred_width,
+            preferred_height,
             horizontal_stretch,
             vertical_stretch,
+            aspect_ratio: 0.,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug)]
+/// The FlowLayoutData places the cells next to each other from left to right, and continues on
+/// the next row when there is not enough space left in the width.
+pub struct FlowLayoutData<'a> {
+    pub width: Coord,
+    pub height: Coord,
+    pub x: Coord,
+    pub y: Coord,
+    pub spacing: Coord,
+    pub padding: &'a Padding,
+    pub alignment: LayoutAlignment,
+    pub direction: LayoutDirection,
+    pub cells: Slice<'a, BoxLayoutCellData<'a>>,
+}
+
+mod flow_internal {
+    use super::*;
+
+    /// The width that the cell takes in a row before the extra space is distributed
+    pub fn cell_width(cell: &BoxLayoutCellData) -> Coord {
+        cell.constraint.base_width().min(cell.constraint.max_width)
+    }
+
+    pub fn cell_height(cell: &BoxLayoutCellData) -> Coord {
+        cell.constraint.base_height().min(cell.constraint.max_height)
+    }
+
+    pub fn row_height(cells: &[BoxLayoutCellData]) -> Coord {
+        cells.iter().map(cell_height).fold(0., Coord::max)
+    }
+
+    /// Split the cells in rows that fit in the given width. Each row has at least one cell.
+    pub fn rows(
+        cells: &[BoxLayoutCellData],
+        width: Coord,
+        spacing: Coord,
+    ) -> Vec<core::ops::Range<usize>> {
+        let mut rows = Vec::new();
+        let mut start = 0;
+        let mut x = 0.;
+        for (i, cell) in cells.iter().enumerate() {
+            let w = cell_width(cell);
+            if i == start {
+                x = w;
+            } else if x + spacing + w > width {
+                rows.push(start..i);
+                start = i;
+                x = w;
+            } else {
+                x += spacing + w;
+            }
+        }
+        if start < cells.len() {
+            rows.push(start..cells.len());
+        }
+        rows
+    }
+
+    #[test]
+    fn test_rows() {
+        let cell = |w| BoxLayoutCellData {
+            constraint: LayoutInfo { preferred_width: w, ..Default::default() },
+            ..Default::default()
+        };
+        let cells = [cell(30.), cell(30.), cell(50.), cell(120.), cell(10.)];
+        assert_eq!(rows(&cells, 100., 10.), vec![0..2, 2..3, 3..4, 4..5]);
+        assert_eq!(rows(&cells, 120., 5.), vec![0..3, 3..4, 4..5]);
+        assert_eq!(rows(&cells, 1000., 10.), vec![0..5]);
+    }
+}
+
+/// Solve a FlowLayout. Only the geometry in the direction of the `pass` is set on the cells.
+#[no_mangle]
+pub extern "C" fn solve_flow_layout(data: &FlowLayoutData, pass: LayoutPass) {
+    let width = data.width - (data.padding.left + data.padding.right);
+    let mut y = data.y + data.padding.top;
+    for row in flow_internal::rows(&data.cells, width, data.spacing) {
+        let cells = &data.cells[row];
+        let row_height = flow_internal::row_height(cells);
+        match pass {
+            LayoutPass::Horizontal => {
+                let count = cells.len() as Coord;
+                let mut widths = cells.iter().map(flow_internal::cell_width).collect::<Vec<_>>();
+                let used = widths.iter().sum::<Coord>() + data.spacing * (count - 1.);
+                let extra = (width - used).max(0.);
+                let (mut x, gap) = match data.alignment {
+                    LayoutAlignment::stretch => {
+                        let stretch_sum =
+                            cells.iter().map(|c| c.constraint.horizontal_stretch).sum::<f32>();
+                        for (w, cell) in widths.iter_mut().zip(cells.iter()) {
+                            let share = if stretch_sum > 0. {
+                                cell.constraint.horizontal_stretch / stretch_sum
+                            } else {
+                                1. / count
+                            };
+                            *w = (*w + extra * share).min(cell.constraint.max_width);
+                        }
+                        (0., data.spacing)
+                    }
+                    LayoutAlignment::start => (0., data.spacing),
+                    LayoutAlignment::end => (extra, data.spacing),
+                    LayoutAlignment::center => (extra / 2., data.spacing),
+                    LayoutAlignment::space_between => {
+                        (0., data.spacing + if count > 1. { extra / (count - 1.) } else { 0. })
+                    }
+                    LayoutAlignment::space_around => {
+                        (extra / (2. * count), data.spacing + extra / count)
+                    }
+                };
+                for (cell, w) in cells.iter().zip(widths) {
+                    let cell_x = data.direction.cell_x(
+                        data.x,
+                        data.width,
+                        data.x + data.padding.left + x,
+                        w,
+                    );
+                    cell.x.map(|p| p.set(cell_x));
+                    cell.width.map(|p| p.set(w));
+                    x += w + gap;
+                }
+            }
+            LayoutPass::Vertical => {
+                for cell in cells {
+                    let height = if data.alignment == LayoutAlignment::stretch {
+                        row_height.min(cell.constraint.max_height)
+                    } else {
+                        flow_internal::cell_height(cell)
+                    };
+                    cell.y.map(|p| p.set(y));
+                    cell.height.map(|p| p.set(height));
+                }
+            }
         }
+        y += row_height + data.spacing;
+    }
+}
+
+#[no_mangle]
+/// Return the LayoutInfo for a FlowLayout with the given cells. The height is the one needed for
+/// the current `width` of the layout.
+pub extern "C" fn flow_layout_info<'a>(
+    cells: &Slice<'a, BoxLayoutCellData<'a>>,
+    spacing: Coord,
+    padding: &Padding,
+    width: Coord,
+) -> LayoutInfo {
+    let count = cells.len();
+    if count < 1 {
+        return LayoutInfo { max_width: 0., max_height: 0., ..LayoutInfo::default() };
+    };
+    let order_float = |a: &Coord, b: &Coord| a.partial_cmp(b).unwrap_or(core::cmp::Ordering::Equal);
+    let extra_w = padding.left + padding.right;
+
+    let min_width =
+        cells.iter().map(|c| c.constraint.min_width).max_by(order_float).unwrap() + extra_w;
+    let preferred_width = cells.iter().map(flow_internal::cell_width).sum::<Coord>()
+        + spacing * (count - 1) as Coord
+        + extra_w;
+    // Before the layout got a width, report the height of the cells placed on a single row
+    let available_width = if width > 0. { width } else { preferred_width } - extra_w;
+    let rows = flow_internal::rows(cells, available_width, spacing);
+    let rows_height =
+        rows.iter().map(|row| flow_internal::row_height(&cells[row.clone()])).sum::<Coord>();
+    let height = rows_height + spacing * (rows.len() - 1) as Coord + padding.top + padding.bottom;
+
+    LayoutInfo {
+        min_width,
+        min_height: height,
+        preferred_width,
+        preferred_height: height,
+        horizontal_stretch: cells.iter().map(|c| c.constraint.horizontal_stretch).sum::<f32>(),
+        vertical_stretch: cells
+            .iter()
+            .map(|c| c.constraint.vertical_stretch)
+            .min_by(order_float)
+            .unwrap(),
+        ..LayoutInfo::default()
+    }
+}
+
+#[repr(C)]
+#[derive(Debug)]
+/// The StackLayoutData places all the cells on top of each other, each of them taking the whole
+/// space of the layout.
+pub struct StackLayoutData<'a> {
+    pub width: Coord,
+    pub height: Coord,
+    pub x: Coord,
+    pub y: Coord,
+    pub padding: &'a Padding,
+    pub cells: Slice<'a, BoxLayoutCellData<'a>>,
+}
+
+/// Solve a StackLayout. Only the geometry in the direction of the `pass` is set on the cells.
+#[no_mangle]
+pub extern "C" fn solve_stack_layout(data: &StackLayoutData, pass: LayoutPass) {
+    for cell in data.cells.iter() {
+        match pass {
+            LayoutPass::Horizontal => {
+                let width = data.width - (data.padding.left + data.padding.right);
+                cell.x.map(|p| p.set(data.x + data.padding.left));
+                cell.width.map(|p| {
+                    p.set(width.min(cell.constraint.max_width).max(cell.constraint.min_width))
+                });
+            }
+            LayoutPass::Vertical => {
+                let height = data.height - (data.padding.top + data.padding.bottom);
+                cell.y.map(|p| p.set(data.y + data.padding.top));
+                cell.height.map(|p| {
+                    p.set(height.min(cell.constraint.max_height).max(cell.constraint.min_height))
+                });
+            }
+        }
+    }
+}
+
+#[no_mangle]
+/// Return the LayoutInfo for a StackLayout with the given cells: the layout is big enough
+/// for any of its cells.
+pub extern "C" fn stack_layout_info<'a>(
+    cells: &Slice<'a, BoxLayoutCellData<'a>>,
+    padding: &Padding,
+) -> LayoutInfo {
+    if cells.is_empty() {
+        return LayoutInfo { max_width: 0., max_height: 0., ..LayoutInfo::default() };
+    };
+    let max_of = |f: &dyn Fn(&LayoutInfo) -> f32| {
+        cells.iter().map(|c| f(&c.constraint)).fold(f32::MIN, f32::max)
+    };
+    let extra_w = padding.left + padding.right;
+    let extra_h = padding.top + padding.bottom;
+    LayoutInfo {
+        min_width: max_of(&|c| c.min_width) + extra_w,
+        max_width: max_of(&|c| c.max_width) + extra_w,
+        min_height: max_of(&|c| c.min_height) + extra_h,
+        max_height: max_of(&|c| c.max_height) + extra_h,
+        preferred_width: max_of(&|c| c.base_width()) + extra_w,
+        preferred_height: max_of(&|c| c.base_height()) + extra_h,
+        horizontal_stretch: max_of(&|c| c.horizontal_stretch),
+        vertical_stretch: max_of(&|c| c.vertical_stretch),
+        aspect_ratio: 0.,
     }
 }
 
@@ -18,8 +18,10 @@ You should use the `sixtyfps` crate instead
 
 #![deny(unsafe_code)]
 
+pub mod animated_image;
 /// The animation system
 pub mod animations;
+pub mod clipboard;
 pub(crate) mod flickable;
 pub mod font;
 pub mod graphics;
@@ -68,6 +70,7 @@ pub mod eventloop;
 pub mod item_rendering;
 pub mod tests;
 pub mod timers;
+pub(crate) mod text_input;
 
 /// One need to use at least one function in each module in order to get them
 /// exported in the final binary.
@@ -86,4 +89,6 @@ pub fn use_modules() -> usize {
         + eventloop::ffi::sixtyfps_component_window_drop as usize
         + input::ffi::sixtyfps_process_ungrabbed_mouse_event as usize
         + component::ffi::sixtyfps_component_init_items as usize
+        + font::ffi::sixtyfps_register_application_font_from_path as usize
+        + items::sixtyfps_textinput_copy as usize
 }
@@ -11,14 +11,11 @@ LICENSE END */
 //! Model and Repeater
 
 use core::cell::RefCell;
+use core::cmp::Ordering;
 use core::pin::Pin;
-use std::{
-    cell::Cell,
-    rc::{Rc, Weak},
-};
+use std::rc::{Rc, Weak};
 
 use crate::component::ComponentRefPin;
-use crate::items::ItemRef;
 use crate::Property;
 
 type ModelPeerInner = dyn ViewAbstraction;
@@ -56,6 +53,19 @@ impl ModelNotify {
             peer.borrow_mut().row_removed(index, count)
         }
     }
+    /// Notify the peers that `count` rows starting at `from` were moved, so that they now start
+    /// at the row `to`
+    pub fn row_moved(&self, from: usize, to: usize, count: usize) {
+        for peer in self.inner.borrow().iter() {
+            peer.borrow_mut().row_moved(from, to, count)
+        }
+    }
+    /// Notify the peers that the whole content of the model changed, including the amount of rows
+    pub fn reset(&self) {
+        for peer in self.inner.borrow().iter() {
+            peer.borrow_mut().reset()
+        }
+    }
     /// Attach one peer. The peer will be notified when the model changes
     pub fn attach(&self, peer: ModelPeer) {
         peer.inner.upgrade().map(|rc| self.inner.borrow_mut().insert(rc));
@@ -105,6 +115,12 @@ impl<T: 'static> VecModel<T> {
         self.array.borrow_mut().remove(index);
         self.notify.row_removed(index, 1)
     }
+
+    /// Replace all the rows of the model
+    pub fn set_vec(&self, new: impl Into<Vec<T>>) {
+        *self.array.borrow_mut() = new.into();
+        self.notify.reset()
+    }
 }
 
 impl<T> From<Vec<T>> for VecModel<T> {
@@ -168,6 +184,429 @@ impl Model for bool {
     }
 }
 
+/// Implemented by the model adapters to be notified of the changes in their source model
+trait SourceModelListener {
+    fn source_row_changed(&self, row: usize);
+    fn source_row_added(&self, index: usize, count: usize);
+    fn source_row_removed(&self, index: usize, count: usize);
+    fn source_row_moved(&self, from: usize, to: usize, count: usize);
+    fn source_reset(&self);
+}
+
+/// The new index of `row` after `count` rows starting at `from` were moved to start at `to`
+fn moved_row(row: usize, from: usize, to: usize, count: usize) -> usize {
+    if row >= from && row < from + count {
+        return to + (row - from);
+    }
+    let row = if row >= from + count { row - count } else { row };
+    if row >= to {
+        row + count
+    } else {
+        row
+    }
+}
+
+/// The peer that a model adapter attaches to its source model
+struct SourceModelPeer<L>(Weak<L>);
+
+impl<L: SourceModelListener> ViewAbstraction for SourceModelPeer<L> {
+    fn row_changed(&mut self, row: usize) {
+        if let Some(listener) = self.0.upgrade() {
+            listener.source_row_changed(row)
+        }
+    }
+    fn row_added(&mut self, index: usize, count: usize) {
+        if let Some(listener) = self.0.upgrade() {
+            listener.source_row_added(index, count)
+        }
+    }
+    fn row_removed(&mut self, index: usize, count: usize) {
+        if let Some(listener) = self.0.upgrade() {
+            listener.source_row_removed(index, count)
+        }
+    }
+    fn row_moved(&mut self, from: usize, to: usize, count: usize) {
+        if let Some(listener) = self.0.upgrade() {
+            listener.source_row_moved(from, to, count)
+        }
+    }
+    fn reset(&mut self) {
+        if let Some(listener) = self.0.upgrade() {
+            listener.source_reset()
+        }
+    }
+}
+
+/// Attach the listener to the source model. The returned peer must be kept alive for as long as
+/// the listener should receive the notifications.
+fn attach_source_listener<T, L: SourceModelListener + 'static>(
+    source: &dyn Model<Data = T>,
+    listener: &Rc<L>,
+) -> Rc<RefCell<ModelPeerInner>> {
+    let peer: Rc<RefCell<ModelPeerInner>> =
+        Rc::new(RefCell::new(SourceModelPeer(Rc::downgrade(listener))));
+    source.attach_peer(ModelPeer { inner: Rc::downgrade(&peer) });
+    peer
+}
+
+/// A model that maps each row of another model with a function.
+///
+/// The rows cannot be changed through this model.
+pub struct MapModel<T, U> {
+    source: Rc<dyn Model<Data = T>>,
+    map: Box<dyn Fn(T) -> U>,
+}
+
+impl<T, U> MapModel<T, U> {
+    /// Create a model with the rows of `source` mapped through `map`
+    pub fn new(source: Rc<dyn Model<Data = T>>, map: impl Fn(T) -> U + 'static) -> Self {
+        Self { source, map: Box::new(map) }
+    }
+}
+
+impl<T, U> Model for MapModel<T, U> {
+    type Data = U;
+
+    fn row_count(&self) -> usize {
+        self.source.row_count()
+    }
+
+    fn row_data(&self, row: usize) -> Self::Data {
+        (self.map)(self.source.row_data(row))
+    }
+
+    fn attach_peer(&self, peer: ModelPeer) {
+        // The rows are the same as the ones of the source
+        self.source.attach_peer(peer)
+    }
+}
+
+type FilterFn<T> = Box<dyn Fn(&T) -> bool>;
+
+struct FilterModelInner<T> {
+    source: Rc<dyn Model<Data = T>>,
+    filter: RefCell<FilterFn<T>>,
+    /// The row in the source model of each row of this model, in increasing order
+    mapping: RefCell<Vec<usize>>,
+    notify: ModelNotify,
+}
+
+impl<T> FilterModelInner<T> {
+    fn accepts(&self, source_row: usize) -> bool {
+        (self.filter.borrow())(&self.source.row_data(source_row))
+    }
+
+    /// The rows of the source model which are accepted by the filter
+    fn filtered_rows(&self) -> Vec<usize> {
+        (0..self.source.row_count()).filter(|r| self.accepts(*r)).collect()
+    }
+
+    /// Add and remove the rows for which the result of the filter changed
+    fn apply_filter(&self) {
+        let mut row = 0;
+        for source_row in 0..self.source.row_count() {
+            let was_accepted = self.mapping.borrow().get(row) == Some(&source_row);
+            match (was_accepted, self.accepts(source_row)) {
+                (true, true) => row += 1,
+                (true, false) => {
+                    self.mapping.borrow_mut().remove(row);
+                    self.notify.row_removed(row, 1);
+                }
+                (false, true) => {
+                    self.mapping.borrow_mut().insert(row, source_row);
+                    self.notify.row_added(row, 1);
+                    row += 1;
+                }
+                (false, false) => {}
+            }
+        }
+    }
+
+    /// The row of this model which is the given source row, or the one after it
+    fn row_for_source_row(&self, source_row: usize) -> usize {
+        match self.mapping.borrow().binary_search(&source_row) {
+            Ok(row) | Err(row) => row,
+        }
+    }
+}
+
+impl<T> SourceModelListener for FilterModelInner<T> {
+    fn source_row_changed(&self, source_row: usize) {
+        let position = self.mapping.borrow().binary_search(&source_row);
+        match (position, self.accepts(source_row)) {
+            (Ok(row), true) => self.notify.row_changed(row),
+            (Ok(row), false) => {
+                self.mapping.borrow_mut().remove(row);
+                self.notify.row_removed(row, 1);
+            }
+            (Err(row), true) => {
+                self.mapping.borrow_mut().insert(row, source_row);
+                self.notify.row_added(row, 1);
+            }
+            (Err(_), false) => {}
+        }
+    }
+
+    fn source_row_added(&self, index: usize, count: usize) {
+        let row = self.row_for_source_row(index);
+        let added: Vec<usize> = (index..index + count).filter(|r| self.accepts(*r)).collect();
+        {
+            let mut mapping = self.mapping.borrow_mut();
+            mapping[row..].iter_mut().for_each(|r| *r += count);
+            mapping.splice(row..row, added.iter().copied());
+        }
+        if !added.is_empty() {
+            self.notify.row_added(row, added.len());
+        }
+    }
+
+    fn source_row_removed(&self, index: usize, count: usize) {
+        let start = self.row_for_source_row(index);
+        let end = self.row_for_source_row(index + count);
+        {
+            let mut mapping = self.mapping.borrow_mut();
+            mapping.drain(start..end);
+            mapping[start..].iter_mut().for_each(|r| *r -= count);
+        }
+        if end > start {
+            self.notify.row_removed(start, end - start);
+        }
+    }
+
+    fn source_row_moved(&self, from: usize, to: usize, count: usize) {
+        let start = self.row_for_source_row(from);
+        let end = self.row_for_source_row(from + count);
+        let first_moved = self.mapping.borrow().get(start).map(|r| moved_row(*r, from, to, count));
+        let new_start = {
+            let mut mapping = self.mapping.borrow_mut();
+            mapping.iter_mut().for_each(|r| *r = moved_row(*r, from, to, count));
+            // The moved rows that are accepted by the filter stay next to each other
+            mapping.sort_unstable();
+            first_moved.and_then(|r| mapping.binary_search(&r).ok())
+        };
+        match new_start {
+            Some(new_start) if end > start && new_start != start => {
+                self.notify.row_moved(start, new_start, end - start)
+            }
+            _ => {}
+        }
+    }
+
+    fn source_reset(&self) {
+        *self.mapping.borrow_mut() = self.filtered_rows();
+        self.notify.reset();
+    }
+}
+
+/// A model that contains the rows of another model for which a filter function returns true.
+///
+/// The changes of the source model are forwarded: rows added to the source are only added
+/// if they are accepted by the filter, and changed rows are added or removed when the result
+/// of the filter changes.
+pub struct FilterModel<T> {
+    inner: Rc<FilterModelInner<T>>,
+    _source_peer: Rc<RefCell<ModelPeerInner>>,
+}
+
+impl<T: 'static> FilterModel<T> {
+    /// Create a model with the rows of `source` for which `filter` returns true
+    pub fn new(source: Rc<dyn Model<Data = T>>, filter: impl Fn(&T) -> bool + 'static) -> Self {
+        let inner = Rc::new(FilterModelInner {
+            source,
+            filter: RefCell::new(Box::new(filter)),
+            mapping: Default::default(),
+            notify: Default::default(),
+        });
+        *inner.mapping.borrow_mut() = inner.filtered_rows();
+        let source_peer = attach_source_listener(&*inner.source, &inner);
+        Self { inner, _source_peer: source_peer }
+    }
+
+    /// Change the filter function. Only the rows for which the result changed are added or removed.
+    pub fn set_filter(&self, filter: impl Fn(&T) -> bool + 'static) {
+        *self.inner.filter.borrow_mut() = Box::new(filter);
+        self.inner.apply_filter();
+    }
+}
+
+impl<T> Model for FilterModel<T> {
+    type Data = T;
+
+    fn row_count(&self) -> usize {
+        self.inner.mapping.borrow().len()
+    }
+
+    fn row_data(&self, row: usize) -> Self::Data {
+        let source_row = self.inner.mapping.borrow()[row];
+        self.inner.source.row_data(source_row)
+    }
+
+    fn set_row_data(&self, row: usize, data: Self::Data) {
+        let source_row = self.inner.mapping.borrow()[row];
+        self.inner.source.set_row_data(source_row, data)
+    }
+
+    fn attach_peer(&self, peer: ModelPeer) {
+        self.inner.notify.attach(peer)
+    }
+}
+
+type CompareFn<T> = Box<dyn Fn(&T, &T) -> Ordering>;
+
+struct SortModelInner<T> {
+    source: Rc<dyn Model<Data = T>>,
+    compare: RefCell<CompareFn<T>>,
+    /// The row in the source model of each row of this model
+    mapping: RefCell<Vec<usize>>,
+    notify: ModelNotify,
+}
+
+impl<T> SortModelInner<T> {
+    /// All the rows of the source, sorted. The rows that compare equal stay in the source order.
+    fn sorted_rows(&self) -> Vec<usize> {
+        let data: Vec<T> = (0..self.source.row_count()).map(|r| self.source.row_data(r)).collect();
+        let compare = self.compare.borrow();
+        let mut rows: Vec<usize> = (0..data.len()).collect();
+        rows.sort_by(|a, b| compare(&data[*a], &data[*b]));
+        rows
+    }
+
+    /// The row at which the given source row must be inserted to keep this model sorted
+    fn insertion_row(&self, source_row: usize) -> usize {
+        let data = self.source.row_data(source_row);
+        let compare = self.compare.borrow();
+        let mapping = self.mapping.borrow();
+        let (mut begin, mut end) = (0, mapping.len());
+        while begin < end {
+            let middle = (begin + end) / 2;
+            let order = compare(&self.source.row_data(mapping[middle]), &data)
+                .then(mapping[middle].cmp(&source_row));
+            if order == Ordering::Greater {
+                end = middle;
+            } else {
+                begin = middle + 1;
+            }
+        }
+        begin
+    }
+
+    /// Sort the rows again, and notify a reset if their order changed
+    fn sort(&self) {
+        let sorted = self.sorted_rows();
+        if *self.mapping.borrow() != sorted {
+            *self.mapping.borrow_mut() = sorted;
+            self.notify.reset();
+        }
+    }
+}
+
+impl<T> SourceModelListener for SortModelInner<T> {
+    fn source_row_changed(&self, source_row: usize) {
+        let row = match self.mapping.borrow().iter().position(|r| *r == source_row) {
+            Some(row) => row,
+            None => return,
+        };
+        self.mapping.borrow_mut().remove(row);
+        let new_row = self.insertion_row(source_row);
+        self.mapping.borrow_mut().insert(new_row, source_row);
+        if new_row != row {
+            self.notify.row_moved(row, new_row, 1);
+        }
+        self.notify.row_changed(new_row);
+    }
+
+    fn source_row_added(&self, index: usize, count: usize) {
+        self.mapping.borrow_mut().iter_mut().filter(|r| **r >= index).for_each(|r| *r += count);
+        for source_row in index..index + count {
+            let row = self.insertion_row(source_row);
+            self.mapping.borrow_mut().insert(row, source_row);
+            self.notify.row_added(row, 1);
+        }
+    }
+
+    fn source_row_removed(&self, index: usize, count: usize) {
+        let mut removed_rows = vec![];
+        for (row, source_row) in self.mapping.borrow_mut().iter_mut().enumerate() {
+            if *source_row >= index + count {
+                *source_row -= count;
+            } else if *source_row >= index {
+                removed_rows.push(row);
+            }
+        }
+        // Remove from the end so that the rows of the next ones are still valid
+        for row in removed_rows.into_iter().rev() {
+            self.mapping.borrow_mut().remove(row);
+            self.notify.row_removed(row, 1);
+        }
+    }
+
+    fn source_row_moved(&self, from: usize, to: usize, count: usize) {
+        self.mapping.borrow_mut().iter_mut().for_each(|r| *r = moved_row(*r, from, to, count));
+        // The order of the rows that compare equal follows the source order
+        self.sort();
+    }
+
+    fn source_reset(&self) {
+        *self.mapping.borrow_mut() = self.sorted_rows();
+        self.notify.reset();
+    }
+}
+
+/// A model that contains the rows of another model, sorted with a comparison function.
+///
+/// The changes of the source model are forwarded: added rows are inserted at their sorted
+/// position, and changed rows are moved if their position changed.
+pub struct SortModel<T> {
+    inner: Rc<SortModelInner<T>>,
+    _source_peer: Rc<RefCell<ModelPeerInner>>,
+}
+
+impl<T: 'static> SortModel<T> {
+    /// Create a model with the rows of `source` sorted according to `compare`
+    pub fn new(
+        source: Rc<dyn Model<Data = T>>,
+        compare: impl Fn(&T, &T) -> Ordering + 'static,
+    ) -> Self {
+        let inner = Rc::new(SortModelInner {
+            source,
+            compare: RefCell::new(Box::new(compare)),
+            mapping: Default::default(),
+            notify: Default::default(),
+        });
+        *inner.mapping.borrow_mut() = inner.sorted_rows();
+        let source_peer = attach_source_listener(&*inner.source, &inner);
+        Self { inner, _source_peer: source_peer }
+    }
+
+    /// Change the comparison function. The model is reset if the order of the rows changed.
+    pub fn set_comparator(&self, compare: impl Fn(&T, &T) -> Ordering + 'static) {
+        *self.inner.compare.borrow_mut() = Box::new(compare);
+        self.inner.sort();
+    }
+}
+
+impl<T> Model for SortModel<T> {
+    type Data = T;
+
+    fn row_count(&self) -> usize {
+        self.inner.mapping.borrow().len()
+    }
+
+    fn row_data(&self, row: usize) -> Self::Data {
+        let source_row = self.inner.mapping.borrow()[row];
+        self.inner.source.row_data(source_row)
+    }
+
+    fn set_row_data(&self, row: usize, data: Self::Data) {
+        let source_row = self.inner.mapping.borrow()[row];
+        self.inner.source.set_row_data(source_row, data)
+    }
+
+    fn attach_peer(&self, peer: ModelPeer) {
+        self.inner.notify.attach(peer)
+    }
+}
+
 /// Properties of type array in the .60 language are represented as
 /// an [`Option`] of an [`Rc`] of somthing implemented the [`Model`] trait
 #[derive(derive_more::Deref, derive_more::DerefMut, derive_more::From, derive_more::Into)]
@@ -233,6 +672,18 @@ pub trait RepeatedComponent: crate::component::Component {
     }
 }
 
+/// The maximum number of times the ListView instantiates the rows that became visible and lays
+/// them out again, because the estimated height of the other rows changed.
+const MAX_LISTVIEW_LAYOUT_PASSES: usize = 32;
+
+/// Returns the number of elements at the start of the sorted `positions` for which `is_before`
+/// returns true
+fn partition_point(positions: &[f32], is_before: impl Fn(f32) -> bool) -> usize {
+    positions
+        .binary_search_by(|y| if is_before(*y) { Ordering::Less } else { Ordering::Greater })
+        .unwrap_err()
+}
+
 #[derive(Clone, Copy, PartialEq, Debug)]
 enum RepeatedComponentState {
     /// The item is in a clean state
@@ -246,7 +697,13 @@ struct RepeaterInner<C: RepeatedComponent> {
     /// The model row (index) of the first component in the `components` vector.
     /// Only used for ListView
     offset: usize,
-    /// The average visible item_height. Only used for ListView
+    /// The `y` position of the first component in the viewport, when it was last laid out.
+    /// Only used for ListView
+    offset_y: f32,
+    /// The height of each row of the model, once it was laid out. Only used for ListView
+    row_heights: Vec<Option<f32>>,
+    /// The average height of the rows that were laid out, used as the height of the other rows.
+    /// Only used for ListView
     cached_item_height: f32,
 }
 
@@ -256,11 +713,50 @@ impl<C: RepeatedComponent> Default for RepeaterInner<C> {
             components: Default::default(),
             is_dirty: true,
             offset: 0,
+            offset_y: 0.,
+            row_heights: Default::default(),
             cached_item_height: 0.,
         }
     }
 }
 
+impl<C: RepeatedComponent> RepeaterInner<C> {
+    /// The height of the given row, or the estimated height if it was not laid out yet
+    fn row_height(&self, row: usize) -> f32 {
+        self.row_heights.get(row).copied().flatten().unwrap_or(self.cached_item_height)
+    }
+
+    /// The `y` position of the given row in the viewport
+    fn row_y(&self, row: usize) -> f32 {
+        (0..row).map(|r| self.row_height(r)).sum()
+    }
+
+    /// The `y` position of each of the `row_count` rows in the viewport, followed by the total
+    /// height of the rows
+    fn row_positions(&self, row_count: usize) -> Vec<f32> {
+        let mut y = 0.;
+        let mut positions = Vec::with_capacity(row_count + 1);
+        positions.push(y);
+        for row in 0..row_count {
+            y += self.row_height(row);
+            positions.push(y);
+        }
+        positions
+    }
+
+    /// Use the average height of the rows that were laid out as the estimated height of the others
+    fn update_cached_item_height(&mut self) {
+        let (total, known) = self
+            .row_heights
+            .iter()
+            .flatten()
+            .fold((0., 0), |(total, known), h| (total + h, known + 1));
+        if known > 0 {
+            self.cached_item_height = total / known as f32;
+        }
+    }
+}
+
 impl<C: RepeatedComponent> Clone for RepeaterInner<C> {
     fn clone(&self) -> Self {
         panic!("Clone is there so we can make_mut the RepeaterInner, to dissociate the weaks, but there should only be one inner")
@@ -271,31 +767,42 @@ trait ViewAbstraction {
     fn row_changed(&mut self, row: usize);
     fn row_added(&mut self, index: usize, count: usize);
     fn row_removed(&mut self, index: usize, count: usize);
+    fn row_moved(&mut self, from: usize, to: usize, count: usize);
+    fn reset(&mut self);
 }
 
 impl<C: RepeatedComponent> ViewAbstraction for RepeaterInner<C> {
     /// Notify the peers that a specific row was changed
     fn row_changed(&mut self, row: usize) {
         self.is_dirty = true;
+        if let Some(h) = self.row_heights.get_mut(row) {
+            // The new data may have a different height
+            *h = None;
+        }
         if let Some(c) = self.components.get_mut(row.wrapping_sub(self.offset)) {
             c.0 = RepeatedComponentState::Dirty;
         }
     }
     /// Notify the peers that rows were added
-    fn row_added(&mut self, mut index: usize, mut count: usize) {
+    fn row_added(&mut self, mut index: usize, count: usize) {
+        // Even if no component is affected, the ListView needs to update the viewport height
+        self.is_dirty = true;
+        if !self.row_heights.is_empty() && index <= self.row_heights.len() {
+            self.row_heights.splice(index..index, core::iter::repeat(None).take(count));
+        }
         if index < self.offset {
-            if index + count < self.offset {
-                return;
+            // The rows before the components moved them down
+            self.offset += count;
+            for c in self.components.iter_mut() {
+                // Because all the indexes are dirty
+                c.0 = RepeatedComponentState::Dirty;
             }
-            count -= self.offset - index;
-            index = 0;
-        } else {
-            index -= self.offset;
+            return;
         }
+        index -= self.offset;
         if count == 0 || index > self.components.len() {
             return;
         }
-        self.is_dirty = true;
         self.components.splice(
             index..index,
             core::iter::repeat((RepeatedComponentState::Dirty, None)).take(count),
@@ -303,28 +810,69 @@ impl<C: RepeatedComponent> ViewAbstraction for RepeaterInner<C> {
     }
     /// Notify the peers that rows were removed
     fn row_removed(&mut self, mut index: usize, mut count: usize) {
+        // Even if no component is affected, the ListView needs to update the viewport height
+        self.is_dirty = true;
+        if index < self.row_heights.len() {
+            let end = (index + count).min(self.row_heights.len());
+            self.row_heights.drain(index..end);
+        }
         if index < self.offset {
-            if index + count < self.offset {
-                return;
-            }
-            count -= self.offset - index;
+            // The rows removed before the components moved them up
+            let before = count.min(self.offset - index);
+            self.offset -= before;
+            count -= before;
             index = 0;
+            for c in self.components.iter_mut() {
+                // Because all the indexes are dirty
+                c.0 = RepeatedComponentState::Dirty;
+            }
         } else {
             index -= self.offset;
         }
-        if count == 0 {
+        if count == 0 || index >= self.components.len() {
             return;
         }
         if (index + count) > self.components.len() {
             count = self.components.len() - index;
         }
-        self.is_dirty = true;
         self.components.drain(index..(index + count));
         for c in self.components[index..].iter_mut() {
             // Because all the indexes are dirty
             c.0 = RepeatedComponentState::Dirty;
         }
     }
+    /// Notify the peers that rows were moved
+    fn row_moved(&mut self, from: usize, to: usize, count: usize) {
+        self.is_dirty = true;
+        if count == 0 || from == to {
+            return;
+        }
+        if from.max(to) + count <= self.row_heights.len() {
+            let heights: Vec<_> = self.row_heights.drain(from..from + count).collect();
+            self.row_heights.splice(to..to, heights);
+        }
+        // Keep the existing component instances, at their new row. Only their index and data
+        // need to be updated.
+        let offset = self.offset;
+        let mut components = Vec::new();
+        components.resize_with(self.components.len(), || (RepeatedComponentState::Dirty, None));
+        for (i, (state, component)) in core::mem::take(&mut self.components).into_iter().enumerate()
+        {
+            let row = moved_row(offset + i, from, to, count);
+            if let Some(c) = row.checked_sub(offset).and_then(|i| components.get_mut(i)) {
+                let state = if row == offset + i { state } else { RepeatedComponentState::Dirty };
+                *c = (state, component);
+            }
+        }
+        self.components = components;
+    }
+    /// Notify the peers that the whole model changed
+    fn reset(&mut self) {
+        self.is_dirty = true;
+        self.row_heights.clear();
+        // The whole content changed: the components are instantiated again
+        self.components.clear();
+    }
 }
 
 /// This field is put in a component when using the `for` syntax
@@ -432,66 +980,71 @@ impl<C: RepeatedComponent + 'static> Repeater<C> {
         // Safety: Repeater does not implement drop and never let access model as mutable
         let listview_geometry_tracker =
             unsafe { self.map_unchecked(|s| &s.listview_geometry_tracker) };
-        if listview_geometry_tracker.is_dirty() {
-            listview_geometry_tracker.evaluate_if_dirty(|| {
-                // Compute the element height
-                let total_height = Cell::new(0.);
-                let count = Cell::new(0);
-
-                let get_height_visitor = |item: Pin<ItemRef>| {
-                    count.set(count.get() + 1);
-                    total_height.set(total_height.get() + item.as_ref().geometry().height());
-                };
-                for c in self.inner.borrow().borrow().components.iter() {
-                    c.1.as_ref().map(|x| {
-                        x.as_pin_ref().apply_layout(Default::default());
-                        get_height_visitor(x.as_pin_ref().get_item_ref(0));
-                    });
-                }
-
-                let element_height = if count.get() > 0 {
-                    total_height.get() / (count.get() as f32)
-                } else {
-                    // There seems to be currently no items. Just instentiate one item.
+        if self.inner.borrow().borrow().is_dirty {
+            // Rows were added, removed or changed: their heights changed
+            listview_geometry_tracker.set_dirty();
+        }
+        listview_geometry_tracker.evaluate_if_dirty(|| {
+            // Lay out the rows that are currently instantiated, so that their height is known.
+            // Instantiate at least one row so that we have an estimation for the height of the others.
+            let (offset, count, mut previous_offset_y) = {
+                let inner = self.inner.borrow();
+                let mut inner = inner.borrow_mut();
+                inner.row_heights.resize(row_count, None);
+                let offset = inner.offset.min(row_count - 1);
+                let count = inner.components.len().min(row_count - offset).max(1);
+                (offset, count, inner.offset_y)
+            };
+            self.set_offset(offset, count);
+            self.ensure_updated_impl(&init, &model, count);
+            self.compute_layout_listview(viewport_width, listview_width);
+
+            let listview_height = listview_height.get();
+            for _ in 0..MAX_LISTVIEW_LAYOUT_PASSES {
+                let (offset, count) = {
+                    let inner = self.inner.borrow();
+                    let mut inner = inner.borrow_mut();
+                    inner.update_cached_item_height();
+                    let positions = inner.row_positions(row_count);
+
+                    // Keep the first instantiated row at the same position on the screen when the
+                    // rows above it were added or removed, or changed their (estimated) height
+                    let offset_y = positions[inner.offset];
+                    if offset_y != previous_offset_y {
+                        viewport_y.set(viewport_y.get() - (offset_y - previous_offset_y));
+                    }
 
-                    {
-                        let inner = self.inner.borrow();
-                        let mut inner = inner.borrow_mut();
-                        inner.offset = inner.offset.min(row_count - 1);
+                    let total_height = positions[row_count];
+                    viewport_height.set(total_height);
+                    if -viewport_y.get() > total_height - listview_height {
+                        viewport_y.set(-(total_height - listview_height).max(0.))
+                    } else if viewport_y.get() > 0. {
+                        viewport_y.set(0.)
                     }
 
-                    self.ensure_updated_impl(&init, &model, 1);
-                    if let Some(c) = self.inner.borrow().borrow().components.get(0) {
-                        c.1.as_ref().map(|x| {
-                            x.as_pin_ref().apply_layout(Default::default());
-                            get_height_visitor(x.as_pin_ref().get_item_ref(0));
-                        });
-                    } else {
-                        panic!("Could not determine size of items");
+                    // Find the rows that are visible: the first one is the last row that starts at
+                    // or above the top, and the following ones start above the bottom.
+                    let top = -viewport_y.get();
+                    let bottom = top + listview_height;
+                    let offset = partition_point(&positions[1..row_count], |y| y <= top);
+                    let count =
+                        1 + partition_point(&positions[offset + 1..row_count], |y| y < bottom);
+                    if offset == inner.offset
+                        && count == inner.components.len()
+                        && offset_y == inner.offset_y
+                    {
+                        // The layout is done with the height of all the visible rows, and the rows
+                        // are placed where the (estimated) height of the rows above them puts them
+                        break;
                     }
-                    total_height.get()
+                    (offset, count)
                 };
-
-                let listview_height = listview_height.get();
-                viewport_height.set(element_height * row_count as f32);
-                self.inner.borrow().borrow_mut().cached_item_height = element_height;
-                if -viewport_y.get() > element_height * row_count as f32 - listview_height {
-                    viewport_y.set(-(element_height * row_count as f32 - listview_height).max(0.))
-                }
-                let offset = (-viewport_y.get() / element_height).floor() as usize;
-                let count =
-                    ((listview_height / element_height).ceil() as usize).min(row_count - offset);
                 self.set_offset(offset, count);
-                self.ensure_updated_impl(init, &model, count);
-                self.compute_layout_listview(viewport_width, listview_width);
-            });
-        } else {
-            if self.inner.borrow().borrow().is_dirty {
-                let count = self.inner.borrow().borrow().components.len();
-                self.ensure_updated_impl(init, &model, count);
+                self.ensure_updated_impl(&init, &model, count);
                 self.compute_layout_listview(viewport_width, listview_width);
+                previous_offset_y = self.inner.borrow().borrow().offset_y;
             }
-        }
+        });
     }
 
     fn set_offset(&self, offset: usize, count: usize) {
@@ -618,11 +1171,20 @@ impl<C: RepeatedComponent> Repeater<C> {
         listview_width: f32,
     ) {
         let inner = self.inner.borrow();
-        let inner = inner.borrow();
-        let mut y_offset = inner.offset as f32 * inner.cached_item_height;
+        let mut inner = inner.borrow_mut();
+        let inner = &mut *inner;
+        let mut y_offset = inner.row_y(inner.offset);
+        inner.offset_y = y_offset;
         viewport_width.set(listview_width);
-        for c in self.inner.borrow().borrow().components.iter() {
-            c.1.as_ref().map(|x| x.as_pin_ref().listview_layout(&mut y_offset, viewport_width));
+        for (i, c) in inner.components.iter().enumerate() {
+            if let Some(x) = &c.1 {
+                let y = y_offset;
+                x.as_pin_ref().listview_layout(&mut y_offset, viewport_width);
+                // Remember the height of the row, now that it is known
+                if let Some(h) = inner.row_heights.get_mut(inner.offset + i) {
+                    *h = Some(y_offset - y);
+                }
+            }
         }
     }
 }
@@ -634,3 +1196,149 @@ pub struct StandardListViewItem {
     /// The text content of the item
     pub text: crate::SharedString,
 }
+
+#[cfg(test)]
+mod adapter_tests {
+    use super::*;
+
+    /// A view that keeps a copy of the rows of the model, only updated through the notifications
+    struct MirrorView {
+        model: Rc<dyn Model<Data = i32>>,
+        rows: Vec<i32>,
+        resets: usize,
+    }
+
+    impl ViewAbstraction for MirrorView {
+        fn row_changed(&mut self, row: usize) {
+            self.rows[row] = self.model.row_data(row);
+        }
+        fn row_added(&mut self, index: usize, count: usize) {
+            let added: Vec<i32> = (index..index + count).map(|r| self.model.row_data(r)).collect();
+            self.rows.splice(index..index, added);
+        }
+        fn row_removed(&mut self, index: usize, count: usize) {
+            self.rows.drain(index..index + count);
+        }
+        fn row_moved(&mut self, from: usize, to: usize, count: usize) {
+            let moved: Vec<i32> = self.rows.drain(from..from + count).collect();
+            self.rows.splice(to..to, moved);
+        }
+        fn reset(&mut self) {
+            self.rows = rows(&*self.model);
+            self.resets += 1;
+        }
+    }
+
+    fn mirror(model: Rc<dyn Model<Data = i32>>) -> Rc<RefCell<MirrorView>> {
+        let rows = (0..model.row_count()).map(|r| model.row_data(r)).collect();
+        let view = Rc::new(RefCell::new(MirrorView { model: model.clone(), rows, resets: 0 }));
+        let peer: Rc<RefCell<ModelPeerInner>> = view.clone();
+        model.attach_peer(ModelPeer { inner: Rc::downgrade(&peer) });
+        view
+    }
+
+    fn rows(model: &dyn Model<Data = i32>) -> Vec<i32> {
+        (0..model.row_count()).map(|r| model.row_data(r)).collect()
+    }
+
+    fn move_rows(model: &VecModel<i32>, from: usize, to: usize, count: usize) {
+        {
+            let mut array = model.array.borrow_mut();
+            let moved: Vec<i32> = array.drain(from..from + count).collect();
+            array.splice(to..to, moved);
+        }
+        model.notify.row_moved(from, to, count);
+    }
+
+    #[test]
+    fn moved_row() {
+        let move_all = |from, to, count| {
+            (0..6).map(|r| super::moved_row(r, from, to, count)).collect::<Vec<_>>()
+        };
+        assert_eq!(move_all(1, 3, 2), vec![0, 3, 4, 1, 2, 5]);
+        assert_eq!(move_all(3, 0, 2), vec![2, 3, 4, 0, 1, 5]);
+    }
+
+    #[test]
+    fn filter_model() {
+        let source = Rc::new(VecModel::from(vec![1, 2, 3, 4, 5, 6]));
+        let filtered: Rc<dyn Model<Data = i32>> =
+            Rc::new(FilterModel::new(source.clone(), |x: &i32| x % 2 == 0));
+        let view = mirror(filtered.clone());
+        assert_eq!(rows(&*filtered), vec![2, 4, 6]);
+
+        source.push(8);
+        source.push(9);
+        source.remove(1);
+        assert_eq!(rows(&*filtered), vec![4, 6, 8]);
+        source.set_row_data(0, 10);
+        source.set_row_data(2, 11);
+        assert_eq!(rows(&*filtered), vec![10, 6, 8]);
+        filtered.set_row_data(1, 12);
+        assert_eq!(source.row_data(4), 12);
+        assert_eq!(view.borrow().rows, rows(&*filtered));
+    }
+
+    #[test]
+    fn filter_model_set_filter() {
+        let source = Rc::new(VecModel::from(vec![1, 2, 3, 4, 5, 6]));
+        let filtered = Rc::new(FilterModel::new(source.clone(), |x: &i32| x % 2 == 0));
+        let view = mirror(filtered.clone());
+        filtered.set_filter(|x: &i32| *x > 3);
+        assert_eq!(rows(&*filtered), vec![4, 5, 6]);
+        assert_eq!(view.borrow().rows, rows(&*filtered));
+    }
+
+    #[test]
+    fn filter_model_moves_and_reset() {
+        let source = Rc::new(VecModel::from(vec![1, 2, 3, 4, 5, 6]));
+        let filtered = Rc::new(FilterModel::new(source.clone(), |x: &i32| x % 2 == 0));
+        let view = mirror(filtered.clone());
+        move_rows(&source, 3, 0, 2);
+        assert_eq!(rows(&*filtered), vec![4, 2, 6]);
+        move_rows(&source, 0, 4, 1);
+        assert_eq!(rows(&*filtered), vec![2, 4, 6]);
+        assert_eq!(view.borrow().rows, rows(&*filtered));
+        source.set_vec(vec![8, 7, 10]);
+        assert_eq!(rows(&*filtered), vec![8, 10]);
+        assert_eq!(view.borrow().rows, rows(&*filtered));
+    }
+
+    #[test]
+    fn sort_model() {
+        let source = Rc::new(VecModel::from(vec![3, 1, 2]));
+        let sorted = Rc::new(SortModel::new(source.clone(), |a: &i32, b: &i32| a.cmp(b)));
+        let view = mirror(sorted.clone());
+        assert_eq!(rows(&*sorted), vec![1, 2, 3]);
+
+        source.push(0);
+        source.push(5);
+        source.remove(0);
+        assert_eq!(rows(&*sorted), vec![0, 1, 2, 5]);
+        source.set_row_data(0, 4);
+        assert_eq!(rows(&*sorted), vec![0, 2, 4, 5]);
+        assert_eq!(view.borrow().rows, rows(&*sorted));
+
+        sorted.set_comparator(|a, b| b.cmp(a));
+        assert_eq!(rows(&*sorted), vec![5, 4, 2, 0]);
+        assert_eq!(view.borrow().rows, rows(&*sorted));
+        assert_eq!(view.borrow().resets, 1);
+
+        move_rows(&source, 0, 2, 2);
+        assert_eq!(rows(&*sorted), vec![5, 4, 2, 0]);
+        assert_eq!(view.borrow().resets, 1);
+        source.set_vec(vec![3, 9]);
+        assert_eq!(rows(&*sorted), vec![9, 3]);
+        assert_eq!(view.borrow().rows, rows(&*sorted));
+    }
+
+    #[test]
+    fn map_model() {
+        let source = Rc::new(VecModel::from(vec![1, 2]));
+        let mapped = Rc::new(MapModel::new(source.clone(), |x: i32| x * 10));
+        let view = mirror(mapped.clone());
+        source.push(3);
+        assert_eq!(rows(&*mapped), vec![10, 20, 30]);
+        assert_eq!(view.borrow().rows, rows(&*mapped));
+    }
+}
@@ -37,6 +37,12 @@ declare_ValueType![
     crate::animations::EasingCurve,
     crate::items::TextHorizontalAlignment,
     crate::items::TextVerticalAlignment,
+    crate::items::TextWrap,
+    crate::items::TextOverflow,
+    crate::items::InputType,
+    crate::items::BorderStyle,
+    crate::items::ImageFit,
+    crate::layout::LayoutDirection,
     crate::model::StandardListViewItem,
 ];
 
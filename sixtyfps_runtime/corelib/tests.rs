@@ -62,6 +62,13 @@ pub extern "C" fn sixtyfps_set_keyboard_modifiers(
     window.set_current_keyboard_modifiers(modifiers)
 }
 
+/// Makes the window use a clipboard that only exists within the process, instead of the
+/// clipboard of the system.
+#[no_mangle]
+pub extern "C" fn sixtyfps_use_in_process_clipboard(window: &crate::eventloop::ComponentWindow) {
+    window.set_clipboard(std::rc::Rc::new(crate::clipboard::InProcessClipboard::default()))
+}
+
 /// Simulate a key down event.
 #[no_mangle]
 pub extern "C" fn sixtyfps_send_key_clicks(
@@ -127,3 +134,25 @@ pub extern "C" fn send_keyboard_string_sequence(
         window.set_current_keyboard_modifiers(crate::input::NO_MODIFIER.into());
     }
 }
+
+/// Simulate an input method changing the text being composed. An empty text ends the composition.
+#[no_mangle]
+pub extern "C" fn sixtyfps_send_preedit(
+    text: &crate::SharedString,
+    cursor_position: i32,
+    window: &crate::eventloop::ComponentWindow,
+) {
+    window.process_key_input(&crate::input::KeyEvent::PreeditChanged {
+        text: text.clone(),
+        cursor_position,
+    });
+}
+
+/// Simulate an input method committing the composed text.
+#[no_mangle]
+pub extern "C" fn sixtyfps_commit_preedit(
+    text: &crate::SharedString,
+    window: &crate::eventloop::ComponentWindow,
+) {
+    window.process_key_input(&crate::input::KeyEvent::PreeditCommitted { text: text.clone() });
+}
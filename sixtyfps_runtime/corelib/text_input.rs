@@ -0,0 +1,331 @@
+/* LICENSE BEGIN
+    This file is part of the SixtyFPS Project -- https://sixtyfps.io
+    Copyright (c) 2020 Olivier Goffart <olivier.goffart@sixtyfps.io>
+    Copyright (c) 2020 Simon Hausmann <simon.hausmann@sixtyfps.io>
+
+    SPDX-License-Identifier: GPL-3.0-only
+    This file is also available under commercial licensing terms.
+    Please contact info@sixtyfps.io for more information.
+LICENSE END */
+//! The implementation details behind the TextInput: its undo history, the state of the mouse
+//! interaction and the validation and masking of the text
+
+use crate::graphics::Point;
+use crate::SharedString;
+use core::cell::{Cell, RefCell};
+use instant::{Duration, Instant};
+
+/// The longest time between two presses of a double or triple click
+const MULTI_CLICK_INTERVAL: Duration = Duration::from_millis(500);
+/// The largest distance between the positions of two presses of a double or triple click
+const MULTI_CLICK_DISTANCE: f32 = 5.;
+
+/// The state of the TextInput that is not exposed as properties
+#[derive(Default)]
+pub struct TextInputData {
+    pub undo_stack: UndoStack,
+    /// The time and position of the last mouse press, and how many presses it follows
+    last_press: Cell<Option<(Instant, Point, u32)>>,
+    /// The position of the mouse during a selection with the mouse
+    pub drag_position: Cell<Point>,
+    /// Scrolls the text while the mouse is dragged beyond the edges
+    pub autoscroll_timer: crate::timers::Timer,
+    /// The last `validator` pattern and its compiled regular expression, None if it is invalid
+    validator: RefCell<Option<(SharedString, Option<regex::Regex>)>>,
+}
+
+impl TextInputData {
+    /// Registers a mouse press and returns 1 for a single click, 2 for a double click and 3 for a
+    /// triple click. A fourth click counts as a single click again.
+    pub fn register_press(&self, pos: Point) -> u32 {
+        let now = crate::animations::current_tick();
+        let count = match self.last_press.get() {
+            Some((time, last_pos, count))
+                if now.duration_since(time) <= MULTI_CLICK_INTERVAL
+                    && (pos - last_pos).length() <= MULTI_CLICK_DISTANCE =>
+            {
+                count % 3 + 1
+            }
+            _ => 1,
+        };
+        self.last_press.set(Some((now, pos, count)));
+        count
+    }
+
+    /// The click count returned by the last call to [`Self::register_press`]
+    pub fn click_count(&self) -> u32 {
+        self.last_press.get().map_or(0, |(_, _, count)| count)
+    }
+
+    /// Returns true if the whole text matches the regular expression `pattern`. An empty pattern
+    /// accepts any text. An invalid pattern rejects any text.
+    pub fn matches_validator(&self, pattern: &SharedString, text: &str) -> bool {
+        pattern.is_empty()
+            || self.with_validator(pattern, |regex| regex.map_or(false, |r| r.is_match(text)))
+    }
+
+    /// Returns false if `pattern` is not a valid regular expression.
+    pub fn is_valid_validator(&self, pattern: &SharedString) -> bool {
+        pattern.is_empty() || self.with_validator(pattern, |regex| regex.is_some())
+    }
+
+    /// Calls `f` with the compiled regular expression of `pattern`, or None if it is invalid.
+    /// The last pattern is cached, and each invalid pattern is only reported once on the
+    /// standard error output.
+    fn with_validator<R>(
+        &self,
+        pattern: &SharedString,
+        f: impl FnOnce(Option<&regex::Regex>) -> R,
+    ) -> R {
+        let mut validator = self.validator.borrow_mut();
+        if validator.as_ref().map_or(true, |(last_pattern, _)| last_pattern != pattern) {
+            let regex = match regex::Regex::new(&format!("^(?:{})$", pattern.as_str())) {
+                Ok(regex) => Some(regex),
+                Err(err) => {
+                    REPORTED_INVALID_VALIDATORS.with(|reported| {
+                        if reported.borrow_mut().insert(pattern.as_str().to_owned()) {
+                            eprintln!(
+                                "Invalid TextInput validator {:?}: {}",
+                                pattern.as_str(),
+                                err
+                            );
+                        }
+                    });
+                    None
+                }
+            };
+            *validator = Some((pattern.clone(), regex));
+        }
+        f(validator.as_ref().and_then(|(_, regex)| regex.as_ref()))
+    }
+}
+
+thread_local! {
+    /// The invalid validator patterns that were already reported
+    static REPORTED_INVALID_VALIDATORS: RefCell<std::collections::HashSet<String>> =
+        Default::default();
+}
+
+/// The character shown instead of each character of a password
+pub const PASSWORD_CHARACTER: char = '●';
+
+/// Returns the text shown for a password: one [`PASSWORD_CHARACTER`] per character.
+pub fn password_mask(text: &str) -> String {
+    core::iter::repeat(PASSWORD_CHARACTER).take(text.chars().count()).collect()
+}
+
+/// Converts a byte offset in `text` to the byte offset in its [`password_mask`].
+pub fn masked_offset(text: &str, offset: usize) -> usize {
+    text[..offset.min(text.len())].chars().count() * PASSWORD_CHARACTER.len_utf8()
+}
+
+/// Converts a byte offset in the [`password_mask`] of `text` to the byte offset in `text`.
+pub fn unmasked_offset(text: &str, masked_offset: usize) -> usize {
+    let index = masked_offset / PASSWORD_CHARACTER.len_utf8();
+    text.char_indices().nth(index).map_or(text.len(), |(offset, _)| offset)
+}
+
+/// Returns true if the text is a decimal number, or could become one by typing more characters:
+/// an optional sign followed by digits with at most one decimal point.
+pub fn is_number_input(text: &str) -> bool {
+    let digits = text.strip_prefix(|ch| ch == '-' || ch == '+').unwrap_or(text);
+    digits.splitn(2, '.').all(|part| part.chars().all(|ch| ch.is_ascii_digit()))
+}
+
+/// A change of the text of a TextInput: `removed` was replaced by `inserted` at the byte offset
+/// `position`. The cursor and anchor positions before and after the change are restored when
+/// undoing or redoing it.
+#[derive(Debug, Clone)]
+pub struct Edit {
+    pub position: usize,
+    pub removed: String,
+    pub inserted: String,
+    pub cursor_before: i32,
+    pub anchor_before: i32,
+    pub cursor_after: i32,
+    pub anchor_after: i32,
+}
+
+#[derive(Default, Debug)]
+struct UndoStackInner {
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+    /// True when the last edit was typed and further typing can be merged into it
+    typing: bool,
+    /// The text after the last edit, undo or redo. When the text of the TextInput differs,
+    /// it was changed by something else and the history doesn't apply anymore.
+    text: String,
+}
+
+impl UndoStackInner {
+    fn sync(&mut self, current_text: &str) {
+        if self.text != current_text {
+            self.undo.clear();
+            self.redo.clear();
+            self.typing = false;
+            self.text = current_text.into();
+        }
+    }
+}
+
+/// The result of undoing or redoing an edit: the new text, cursor position and anchor position
+pub struct UndoResult {
+    pub text: String,
+    pub cursor: i32,
+    pub anchor: i32,
+}
+
+#[derive(Default, Debug)]
+pub struct UndoStack {
+    inner: RefCell<UndoStackInner>,
+}
+
+impl UndoStack {
+    /// Records an edit that changed `text_before` into `text_after`. Consecutive typed edits
+    /// are merged into a single edit.
+    pub fn record(&self, text_before: &str, edit: Edit, typing: bool, text_after: &str) {
+        let mut inner = self.inner.borrow_mut();
+        inner.sync(text_before);
+        inner.redo.clear();
+        inner.text = text_after.into();
+
+        let merge = typing && inner.typing && edit.removed.is_empty();
+        inner.typing = typing;
+        if merge {
+            if let Some(last) = inner.undo.last_mut() {
+                if last.position + last.inserted.len() == edit.position {
+                    last.inserted.push_str(&edit.inserted);
+                    last.cursor_after = edit.cursor_after;
+                    last.anchor_after = edit.anchor_after;
+                    return;
+                }
+            }
+        }
+        inner.undo.push(edit);
+    }
+
+    /// Prevents the next typed edit from being merged with the previous one.
+    pub fn stop_merging(&self) {
+        self.inner.borrow_mut().typing = false;
+    }
+
+    /// Reverts the last edit of `current_text`, if any.
+    pub fn undo(&self, current_text: &str) -> Option<UndoResult> {
+        let mut inner = self.inner.borrow_mut();
+        inner.sync(current_text);
+        inner.typing = false;
+        let edit = inner.undo.pop()?;
+        let text = [
+            &current_text[..edit.position],
+            edit.removed.as_str(),
+            &current_text[edit.position + edit.inserted.len()..],
+        ]
+        .concat();
+        let result = UndoResult { text, cursor: edit.cursor_before, anchor: edit.anchor_before };
+        inner.text = result.text.clone();
+        inner.redo.push(edit);
+        Some(result)
+    }
+
+    /// Applies again the last undone edit of `current_text`, if any.
+    pub fn redo(&self, current_text: &str) -> Option<UndoResult> {
+        let mut inner = self.inner.borrow_mut();
+        inner.sync(current_text);
+        inner.typing = false;
+        let edit = inner.redo.pop()?;
+        let text = [
+            &current_text[..edit.position],
+            edit.inserted.as_str(),
+            &current_text[edit.position + edit.removed.len()..],
+        ]
+        .concat();
+        let result = UndoResult { text, cursor: edit.cursor_after, anchor: edit.anchor_after };
+        inner.text = result.text.clone();
+        inner.undo.push(edit);
+        Some(result)
+    }
+
+    pub fn can_undo(&self, current_text: &str) -> bool {
+        let inner = self.inner.borrow();
+        inner.text == current_text && !inner.undo.is_empty()
+    }
+
+    pub fn can_redo(&self, current_text: &str) -> bool {
+        let inner = self.inner.borrow();
+        inner.text == current_text && !inner.redo.is_empty()
+    }
+}
+
+#[test]
+fn merge_typing() {
+    let stack = UndoStack::default();
+    let typed = |text: &str, position: usize, ch: &str| {
+        let cursor = (position + ch.len()) as i32;
+        let edit = Edit {
+            position,
+            removed: String::new(),
+            inserted: ch.into(),
+            cursor_before: position as i32,
+            anchor_before: position as i32,
+            cursor_after: cursor,
+            anchor_after: cursor,
+        };
+        let text_after = [&text[..position], ch, &text[position..]].concat();
+        stack.record(text, edit, true, &text_after);
+        text_after
+    };
+    let text = typed("", 0, "a");
+    let text = typed(&text, 1, "b");
+    assert!(stack.can_undo(&text));
+    let undone = stack.undo(&text).unwrap();
+    assert_eq!(undone.text, "");
+    assert_eq!(undone.cursor, 0);
+    assert!(!stack.can_undo(&undone.text));
+    assert!(stack.can_redo(&undone.text));
+    let redone = stack.redo(&undone.text).unwrap();
+    assert_eq!(redone.text, "ab");
+    assert_eq!(redone.cursor, 2);
+
+    stack.stop_merging();
+    let text = typed(&redone.text, 2, "c");
+    assert_eq!(stack.undo(&text).unwrap().text, "ab");
+
+    // The history is forgotten when the text was changed by something else
+    assert!(!stack.can_redo("xyz"));
+    assert!(stack.undo("xyz").is_none());
+}
+
+#[test]
+fn password_and_number() {
+    let text = "aé€b";
+    assert_eq!(password_mask(text), "●●●●");
+    assert_eq!(masked_offset(text, 3), 2 * PASSWORD_CHARACTER.len_utf8());
+    assert_eq!(unmasked_offset(text, 2 * PASSWORD_CHARACTER.len_utf8()), 3);
+    assert_eq!(unmasked_offset(text, 4 * PASSWORD_CHARACTER.len_utf8()), text.len());
+
+    assert!(is_number_input(""));
+    assert!(is_number_input("-"));
+    assert!(is_number_input("-12.5"));
+    assert!(is_number_input(".5"));
+    assert!(!is_number_input("1.2.3"));
+    assert!(!is_number_input("1e3"));
+    assert!(!is_number_input("1-"));
+}
+
+#[test]
+fn validator() {
+    let data = TextInputData::default();
+    let pattern = SharedString::from("[a-z]*");
+    assert!(data.matches_validator(&pattern, "abc"));
+    assert!(data.matches_validator(&pattern, ""));
+    assert!(!data.matches_validator(&pattern, "abC"));
+    // The whole text must match
+    assert!(!data.matches_validator(&"[a-z]".into(), "ab"));
+    assert!(data.matches_validator(&"".into(), "anything"));
+    assert!(data.is_valid_validator(&"[a-z]*".into()));
+    assert!(data.is_valid_validator(&"".into()));
+    // An invalid pattern rejects everything
+    assert!(!data.matches_validator(&"[a-z".into(), "abc"));
+    assert!(!data.matches_validator(&"[a-z".into(), ""));
+    assert!(!data.is_valid_validator(&"[a-z".into()));
+}
@@ -16,7 +16,9 @@ use expression_tree::NamedReference;
 use object_tree::{Element, ElementRc};
 use sixtyfps_compilerlib::expression_tree::Expression;
 use sixtyfps_compilerlib::langtype::Type;
-use sixtyfps_compilerlib::layout::{Layout, LayoutConstraints, LayoutItem, PathLayout};
+use sixtyfps_compilerlib::layout::{
+    Layout, LayoutConstraints, LayoutGeometry, LayoutItem, LayoutRect, PathLayout,
+};
 use sixtyfps_compilerlib::*;
 use sixtyfps_corelib::component::{Component, ComponentRefPin, ComponentVTable};
 use sixtyfps_corelib::graphics::{Rect, Resource};
@@ -160,24 +162,21 @@ impl RepeatedComponent for ErasedComponentBox {
         generativity::make_guard!(guard);
         let s = self.unerase(guard);
 
-        let root_item = &s.component_type.original.root_element;
-        let get_prop = |name: &str| {
-            if root_item.borrow().lookup_property(name) == Type::Length {
-                let nr = NamedReference::new(root_item, name);
-                let p = get_property_ptr(&nr, s.borrow_instance());
+        let rect = LayoutRect::for_element(&s.component_type.original.root_element);
+        let get_prop = |nr: &Option<NamedReference>| {
+            nr.as_ref().map(|nr| {
+                let p = get_property_ptr(nr, s.borrow_instance());
                 // Safety: assuming get_property_ptr returned a valid pointer,
-                // we know that `Type::Length` is a property of type `f32`
-                Some(unsafe { &*(p as *const Property<f32>) })
-            } else {
-                None
-            }
+                // we know that the geometry properties are of type `f32`
+                unsafe { &*(p as *const Property<f32>) }
+            })
         };
         BoxLayoutCellData {
             constraint: self.borrow().as_ref().layout_info(),
-            x: get_prop("x"),
-            y: get_prop("y"),
-            width: get_prop("width"),
-            height: get_prop("height"),
+            x: get_prop(&rect.x_reference),
+            y: get_prop(&rect.y_reference),
+            width: get_prop(&rect.width_reference),
+            height: get_prop(&rect.height_reference),
         }
     }
 }
@@ -500,6 +499,7 @@ fn generate_component<'id>(
                 rtti_for_flickable(),
                 rtti_for::<Window>(),
                 rtti_for::<TextInput>(),
+                rtti_for::<StackPage>(),
             ]
             .iter()
             .cloned(),
@@ -635,12 +635,18 @@ fn generate_component<'id>(
             Type::Percent => property_info::<f32>(),
             Type::Enumeration(e) => match e.name.as_ref() {
                 "LayoutAlignment" => property_info::<sixtyfps_corelib::layout::LayoutAlignment>(),
+                "LayoutDirection" => property_info::<sixtyfps_corelib::layout::LayoutDirection>(),
                 "TextHorizontalAlignment" => {
                     property_info::<sixtyfps_corelib::items::TextHorizontalAlignment>()
                 }
                 "TextVerticalAlignment" => {
                     property_info::<sixtyfps_corelib::items::TextVerticalAlignment>()
                 }
+                "TextWrap" => property_info::<sixtyfps_corelib::items::TextWrap>(),
+                "TextOverflow" => property_info::<sixtyfps_corelib::items::TextOverflow>(),
+                "InputType" => property_info::<sixtyfps_corelib::items::InputType>(),
+                "BorderStyle" => property_info::<sixtyfps_corelib::items::BorderStyle>(),
+                "ImageFit" => property_info::<sixtyfps_corelib::items::ImageFit>(),
                 _ => panic!("unkown enum"),
             },
             _ => panic!("bad type"),
@@ -1043,6 +1049,13 @@ enum LayoutTreeItem<'a> {
         bool,
         sixtyfps_corelib::layout::LayoutAlignment,
     ),
+    /// The cells, the alignment, and the current width of the layout
+    FlowLayout(
+        LayoutWithCells<'a, BoxLayoutCellTmpData<'a>>,
+        sixtyfps_corelib::layout::LayoutAlignment,
+        f32,
+    ),
+    StackLayout(LayoutWithCells<'a, BoxLayoutCellTmpData<'a>>),
     PathLayout(&'a PathLayout),
 }
 
@@ -1064,6 +1077,19 @@ impl<'a> LayoutTreeItem<'a> {
                     *is_horizontal,
                 )
             }
+            LayoutTreeItem::FlowLayout(flow_layout, _, width) => {
+                let cells = BoxLayoutCellTmpData::into_cells(&flow_layout.cells);
+                flow_layout_info(
+                    &Slice::from(cells.as_slice()),
+                    flow_layout.spacing,
+                    &flow_layout.padding,
+                    *width,
+                )
+            }
+            LayoutTreeItem::StackLayout(stack_layout) => {
+                let cells = BoxLayoutCellTmpData::into_cells(&stack_layout.cells);
+                stack_layout_info(&Slice::from(cells.as_slice()), &stack_layout.padding)
+            }
             LayoutTreeItem::PathLayout(_) => todo!(),
         }
     }
@@ -1105,8 +1131,11 @@ fn fill_layout_info_constraints(
     constraints.maximum_width.as_ref().map(|e| layout_info.max_width = expr_eval(e));
     constraints.minimum_height.as_ref().map(|e| layout_info.min_height = expr_eval(e));
     constraints.maximum_height.as_ref().map(|e| layout_info.max_height = expr_eval(e));
+    constraints.preferred_width.as_ref().map(|e| layout_info.preferred_width = expr_eval(e));
+    constraints.preferred_height.as_ref().map(|e| layout_info.preferred_height = expr_eval(e));
     constraints.horizontal_stretch.as_ref().map(|e| layout_info.horizontal_stretch = expr_eval(e));
     constraints.vertical_stretch.as_ref().map(|e| layout_info.vertical_stretch = expr_eval(e));
+    constraints.aspect_ratio.as_ref().map(|e| layout_info.aspect_ratio = expr_eval(e));
 }
 
 fn collect_layouts_recursively<'a, 'b>(
@@ -1127,6 +1156,66 @@ fn collect_layouts_recursively<'a, 'b>(
             .try_into()
             .unwrap()
     };
+    let layout_padding = |geometry: &LayoutGeometry| Padding {
+        left: geometry.padding.left.as_ref().map_or(0., expr_eval),
+        right: geometry.padding.right.as_ref().map_or(0., expr_eval),
+        top: geometry.padding.top.as_ref().map_or(0., expr_eval),
+        bottom: geometry.padding.bottom.as_ref().map_or(0., expr_eval),
+    };
+    let layout_alignment = |geometry: &LayoutGeometry| -> LayoutAlignment {
+        geometry
+            .alignment
+            .as_ref()
+            .map(|nr| {
+                eval::load_property(component, &nr.element.upgrade().unwrap(), &nr.name)
+                    .unwrap()
+                    .try_into()
+                    .unwrap_or_default()
+            })
+            .unwrap_or_default()
+    };
+    // The cells of a BoxLayout, a FlowLayout or a StackLayout
+    let collect_box_layout_cells =
+        |elems: &'a [LayoutItem], layout_tree: &mut Vec<LayoutTreeItem<'a>>| {
+            elems
+                .iter()
+                .map(|item| match &item.element {
+                    Some(elem) if elem.borrow().repeated.is_some() => {
+                        generativity::make_guard!(guard);
+                        let rep = get_repeater_by_name(component, elem.borrow().id.as_str(), guard);
+                        rep.0.as_ref().ensure_updated(|| {
+                            vtable::VRc::new(ErasedComponentBox::from(instantiate(
+                                rep.1.clone(),
+                                Some(component.borrow()),
+                                #[cfg(target_arch = "wasm32")]
+                                String::new(),
+                            )))
+                        });
+
+                        BoxLayoutCellTmpData::Repeater(
+                            rep.0.as_ref().components_vec().into_iter().collect(),
+                        )
+                    }
+                    _ => {
+                        let mut layout_info = get_layout_info(item, component, layout_tree, window);
+                        fill_layout_info_constraints(
+                            &mut layout_info,
+                            &item.constraints,
+                            &expr_eval,
+                        );
+                        let rect = item.rect();
+
+                        BoxLayoutCellTmpData::Item(BoxLayoutCellData {
+                            x: assume_property_f32(&rect.x_reference),
+                            y: assume_property_f32(&rect.y_reference),
+                            width: assume_property_f32(&rect.width_reference),
+                            height: assume_property_f32(&rect.height_reference),
+                            constraint: layout_info,
+                        })
+                    }
+                })
+                .collect::<Vec<_>>()
+        };
 
     match layout {
         Layout::GridLayout(grid_layout) => {
@@ -1157,86 +1246,51 @@ fn collect_layouts_recursively<'a, 'b>(
                 })
                 .collect();
             let spacing = grid_layout.geometry.spacing.as_ref().map_or(0., expr_eval);
-            let padding = Padding {
-                left: grid_layout.geometry.padding.left.as_ref().map_or(0., expr_eval),
-                right: grid_layout.geometry.padding.right.as_ref().map_or(0., expr_eval),
-                top: grid_layout.geometry.padding.top.as_ref().map_or(0., expr_eval),
-                bottom: grid_layout.geometry.padding.bottom.as_ref().map_or(0., expr_eval),
-            };
+            let padding = layout_padding(&grid_layout.geometry);
             layout_tree.push(
                 LayoutWithCells { geometry: &grid_layout.geometry, cells, spacing, padding }.into(),
             );
         }
         Layout::BoxLayout(box_layout) => {
-            let mut make_box_layout_cell_data = |cell| {
-                let mut layout_info = get_layout_info(cell, component, layout_tree, window);
-                fill_layout_info_constraints(&mut layout_info, &cell.constraints, &expr_eval);
-                let rect = cell.rect();
-
-                BoxLayoutCellData {
-                    x: assume_property_f32(&rect.x_reference),
-                    y: assume_property_f32(&rect.y_reference),
-                    width: assume_property_f32(&rect.width_reference),
-                    height: assume_property_f32(&rect.height_reference),
-                    constraint: layout_info,
-                }
-            };
-
-            let cells = box_layout
-                .elems
-                .iter()
-                .map(|item| match &item.element {
-                    Some(elem) if elem.borrow().repeated.is_some() => {
-                        generativity::make_guard!(guard);
-                        let rep = get_repeater_by_name(component, elem.borrow().id.as_str(), guard);
-                        rep.0.as_ref().ensure_updated(|| {
-                            vtable::VRc::new(ErasedComponentBox::from(instantiate(
-                                rep.1.clone(),
-                                Some(component.borrow()),
-                                #[cfg(target_arch = "wasm32")]
-                                String::new(),
-                            )))
-                        });
-
-                        BoxLayoutCellTmpData::Repeater(
-                            rep.0.as_ref().components_vec().into_iter().collect(),
-                        )
-                    }
-                    _ => BoxLayoutCellTmpData::Item(make_box_layout_cell_data(item)),
-                })
-                .collect::<Vec<_>>();
-
+            let cells = collect_box_layout_cells(&box_layout.elems, layout_tree);
             let spacing = box_layout.geometry.spacing.as_ref().map_or(0., expr_eval);
-            let padding = Padding {
-                left: box_layout.geometry.padding.left.as_ref().map_or(0., expr_eval),
-                right: box_layout.geometry.padding.right.as_ref().map_or(0., expr_eval),
-                top: box_layout.geometry.padding.top.as_ref().map_or(0., expr_eval),
-                bottom: box_layout.geometry.padding.bottom.as_ref().map_or(0., expr_eval),
-            };
-            let alignment = box_layout
-                .geometry
-                .alignment
-                .as_ref()
-                .map(|nr| {
-                    eval::load_property(component, &nr.element.upgrade().unwrap(), &nr.name)
-                        .unwrap()
-                        .try_into()
-                        .unwrap_or_default()
-                })
-                .unwrap_or_default();
+            let padding = layout_padding(&box_layout.geometry);
+            let alignment = layout_alignment(&box_layout.geometry);
             layout_tree.push(LayoutTreeItem::BoxLayout(
                 LayoutWithCells { geometry: &box_layout.geometry, cells, spacing, padding },
                 box_layout.is_horizontal,
                 alignment,
             ));
         }
+        Layout::FlowLayout(flow_layout) => {
+            let cells = collect_box_layout_cells(&flow_layout.elems, layout_tree);
+            let spacing = flow_layout.geometry.spacing.as_ref().map_or(0., expr_eval);
+            let padding = layout_padding(&flow_layout.geometry);
+            let alignment = layout_alignment(&flow_layout.geometry);
+            let width = flow_layout.geometry.rect.width_reference.as_ref().map_or(0., expr_eval);
+            layout_tree.push(LayoutTreeItem::FlowLayout(
+                LayoutWithCells { geometry: &flow_layout.geometry, cells, spacing, padding },
+                alignment,
+                width,
+            ));
+        }
+        Layout::StackLayout(stack_layout) => {
+            let cells = collect_box_layout_cells(&stack_layout.elems, layout_tree);
+            let padding = layout_padding(&stack_layout.geometry);
+            layout_tree.push(LayoutTreeItem::StackLayout(LayoutWithCells {
+                geometry: &stack_layout.geometry,
+                cells,
+                spacing: 0.,
+                padding,
+            }));
+        }
         Layout::PathLayout(layout) => layout_tree.push(layout.into()),
     }
     layout_tree.last().unwrap()
 }
 
 impl<'a> LayoutTreeItem<'a> {
-    fn solve(&self, instance_ref: InstanceRef) {
+    fn solve(&self, instance_ref: InstanceRef, pass: LayoutPass) {
         let resolve_prop_ref = |prop_ref: &Option<NamedReference>| {
             prop_ref.as_ref().map_or(0., |nr| {
                 eval::load_property(instance_ref, &nr.element.upgrade().unwrap(), &nr.name)
@@ -1245,18 +1299,34 @@ impl<'a> LayoutTreeItem<'a> {
                     .unwrap_or(0.)
             })
         };
+        let resolve_direction = |geometry: &LayoutGeometry| -> LayoutDirection {
+            geometry
+                .direction
+                .as_ref()
+                .map(|nr| {
+                    eval::load_property(instance_ref, &nr.element.upgrade().unwrap(), &nr.name)
+                        .unwrap()
+                        .try_into()
+                        .unwrap_or_default()
+                })
+                .unwrap_or_default()
+        };
 
         match self {
             Self::GridLayout(grid_layout) => {
-                solve_grid_layout(&GridLayoutData {
-                    width: resolve_prop_ref(&grid_layout.geometry.rect.width_reference),
-                    height: resolve_prop_ref(&grid_layout.geometry.rect.height_reference),
-                    x: resolve_prop_ref(&grid_layout.geometry.rect.x_reference),
-                    y: resolve_prop_ref(&grid_layout.geometry.rect.y_reference),
-                    spacing: grid_layout.spacing,
-                    padding: &grid_layout.padding,
-                    cells: Slice::from(grid_layout.cells.as_slice()),
-                });
+                solve_grid_layout(
+                    &GridLayoutData {
+                        width: resolve_prop_ref(&grid_layout.geometry.rect.width_reference),
+                        height: resolve_prop_ref(&grid_layout.geometry.rect.height_reference),
+                        x: resolve_prop_ref(&grid_layout.geometry.rect.x_reference),
+                        y: resolve_prop_ref(&grid_layout.geometry.rect.y_reference),
+                        spacing: grid_layout.spacing,
+                        padding: &grid_layout.padding,
+                        direction: resolve_direction(&grid_layout.geometry),
+                        cells: Slice::from(grid_layout.cells.as_slice()),
+                    },
+                    pass,
+                );
             }
             Self::BoxLayout(box_layout, is_horizontal, alignment) => {
                 let cells = BoxLayoutCellTmpData::into_cells(&box_layout.cells);
@@ -1270,10 +1340,45 @@ impl<'a> LayoutTreeItem<'a> {
                         padding: &box_layout.padding,
                         cells: Slice::from(cells.as_slice()),
                         alignment: *alignment,
+                        direction: resolve_direction(&box_layout.geometry),
                     },
                     *is_horizontal,
+                    pass,
                 );
             }
+            Self::FlowLayout(flow_layout, alignment, _) => {
+                let cells = BoxLayoutCellTmpData::into_cells(&flow_layout.cells);
+                solve_flow_layout(
+                    &FlowLayoutData {
+                        width: resolve_prop_ref(&flow_layout.geometry.rect.width_reference),
+                        height: resolve_prop_ref(&flow_layout.geometry.rect.height_reference),
+                        x: resolve_prop_ref(&flow_layout.geometry.rect.x_reference),
+                        y: resolve_prop_ref(&flow_layout.geometry.rect.y_reference),
+                        spacing: flow_layout.spacing,
+                        padding: &flow_layout.padding,
+                        cells: Slice::from(cells.as_slice()),
+                        alignment: *alignment,
+                        direction: resolve_direction(&flow_layout.geometry),
+                    },
+                    pass,
+                );
+            }
+            Self::StackLayout(stack_layout) => {
+                let cells = BoxLayoutCellTmpData::into_cells(&stack_layout.cells);
+                solve_stack_layout(
+                    &StackLayoutData {
+                        width: resolve_prop_ref(&stack_layout.geometry.rect.width_reference),
+                        height: resolve_prop_ref(&stack_layout.geometry.rect.height_reference),
+                        x: resolve_prop_ref(&stack_layout.geometry.rect.x_reference),
+                        y: resolve_prop_ref(&stack_layout.geometry.rect.y_reference),
+                        padding: &stack_layout.padding,
+                        cells: Slice::from(cells.as_slice()),
+                    },
+                    pass,
+                );
+            }
+            // The items are placed on the path once, in the last pass
+            Self::PathLayout(_) if pass == LayoutPass::Horizontal => {}
             Self::PathLayout(path_layout) => {
                 use sixtyfps_corelib::layout::*;
 
@@ -1501,13 +1606,17 @@ extern "C" fn apply_layout(component: ComponentRefPin, _r: sixtyfps_corelib::gra
     let window = eval::window_ref(instance_ref).unwrap();
 
     instance_ref.component_type.original.layouts.borrow().iter().for_each(|layout| {
-        let mut inverse_layout_tree = Vec::new();
+        // The layout tree is collected again before the vertical pass, so that the height
+        // constraints of the cells are queried for the width set by the horizontal pass.
+        for pass in [LayoutPass::Horizontal, LayoutPass::Vertical].iter() {
+            let mut inverse_layout_tree = Vec::new();
 
-        collect_layouts_recursively(&mut inverse_layout_tree, &layout, instance_ref, &window);
+            collect_layouts_recursively(&mut inverse_layout_tree, &layout, instance_ref, &window);
 
-        inverse_layout_tree.iter().rev().for_each(|layout| {
-            layout.solve(instance_ref);
-        });
+            inverse_layout_tree.iter().rev().for_each(|layout| {
+                layout.solve(instance_ref, *pass);
+            });
+        }
     });
 
     for rep_in_comp in &instance_ref.component_type.repeater {
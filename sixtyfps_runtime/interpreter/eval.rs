@@ -83,6 +83,29 @@ impl PartialEq for ModelPtr {
     }
 }
 
+impl ModelPtr {
+    /// Returns a model with the rows of this model sorted according to `compare`.
+    /// See [`SortModel`](corelib::model::SortModel)
+    pub fn sorted(
+        &self,
+        compare: impl Fn(&Value, &Value) -> core::cmp::Ordering + 'static,
+    ) -> Self {
+        Self(Rc::new(corelib::model::SortModel::new(self.0.clone(), compare)))
+    }
+
+    /// Returns a model with the rows of this model for which `filter` returns true.
+    /// See [`FilterModel`](corelib::model::FilterModel)
+    pub fn filtered(&self, filter: impl Fn(&Value) -> bool + 'static) -> Self {
+        Self(Rc::new(corelib::model::FilterModel::new(self.0.clone(), filter)))
+    }
+
+    /// Returns a model with the rows of this model converted by `map`.
+    /// See [`MapModel`](corelib::model::MapModel)
+    pub fn mapped(&self, map: impl Fn(Value) -> Value + 'static) -> Self {
+        Self(Rc::new(corelib::model::MapModel::new(self.0.clone(), map)))
+    }
+}
+
 #[derive(Clone, PartialEq, Debug)]
 /// This is a dynamically typed Value used in the interpreter, it need to be able
 /// to be converted from and to anything that can be stored in a Property
@@ -210,7 +233,13 @@ macro_rules! declare_value_enum_conversion {
 
 declare_value_enum_conversion!(corelib::items::TextHorizontalAlignment, TextHorizontalAlignment);
 declare_value_enum_conversion!(corelib::items::TextVerticalAlignment, TextVerticalAlignment);
+declare_value_enum_conversion!(corelib::items::TextWrap, TextWrap);
+declare_value_enum_conversion!(corelib::items::TextOverflow, TextOverflow);
+declare_value_enum_conversion!(corelib::items::InputType, InputType);
 declare_value_enum_conversion!(corelib::layout::LayoutAlignment, LayoutAlignment);
+declare_value_enum_conversion!(corelib::layout::LayoutDirection, LayoutDirection);
+declare_value_enum_conversion!(corelib::items::BorderStyle, BorderStyle);
+declare_value_enum_conversion!(corelib::items::ImageFit, ImageFit);
 
 #[derive(Copy, Clone)]
 enum ComponentInstance<'a, 'id> {
@@ -348,6 +377,12 @@ pub fn eval_expression(e: &Expression, local_context: &mut EvalLocalContext) ->
                     ComponentInstance::GlobalComponent(_) => panic!("Cannot get the window from a global component"),
                 }
             }
+            Expression::BuiltinFunctionReference(BuiltinFunction::GetWindowLayoutDirection) => {
+                match local_context.component_instance {
+                    ComponentInstance::InstanceRef(component) => window_ref(component).unwrap().layout_direction().try_into().unwrap(),
+                    ComponentInstance::GlobalComponent(_) => panic!("Cannot get the window from a global component"),
+                }
+            }
             Expression::BuiltinFunctionReference(BuiltinFunction::Debug) => {
                 let a = arguments.iter().map(|e| eval_expression(e, local_context));
                 println!("{:?}", a.collect::<Vec<_>>());
@@ -409,6 +444,51 @@ pub fn eval_expression(e: &Expression, local_context: &mut EvalLocalContext) ->
                     panic!("Argument not a string");
                 }
             }
+            Expression::BuiltinFunctionReference(BuiltinFunction::RegisterCustomFont) => {
+                if arguments.len() != 1 {
+                    panic!("internal error: incorrect argument count to RegisterCustomFont")
+                }
+                if let Value::Resource(resource) = eval_expression(&arguments[0], local_context) {
+                    if let Err(err) =
+                        corelib::font::register_application_font_from_resource(&resource)
+                    {
+                        eprintln!("Error registering font {:?}: {}", resource, err);
+                    }
+                    Value::Void
+                } else {
+                    panic!("Argument not a resource");
+                }
+            }
+            Expression::BuiltinFunctionReference(BuiltinFunction::ItemMemberFunction(name)) => {
+                let component = match local_context.component_instance {
+                    ComponentInstance::InstanceRef(c) => c,
+                    ComponentInstance::GlobalComponent(_) => panic!("Cannot access items from a global component")
+                };
+                if let [Expression::ElementReference(item)] = arguments.as_slice() {
+                    generativity::make_guard!(guard);
+                    let item = item.upgrade().unwrap();
+                    let enclosing_component = enclosing_component_for_element(&item, component, guard);
+                    let item_info = &enclosing_component.component_type.items[item.borrow().id.as_str()];
+                    let item = unsafe { item_info.item_from_component(enclosing_component.as_ptr()) };
+                    let window = window_ref(component).unwrap();
+                    if let Some(text_input) = ItemRef::downcast_pin::<corelib::items::TextInput>(item) {
+                        match name.as_str() {
+                            "copy" => text_input.copy(&window),
+                            "cut" => text_input.cut(&window),
+                            "paste" => text_input.paste(&window),
+                            "select_all" => text_input.select_all(&window),
+                            "undo" => text_input.undo(&window),
+                            "redo" => text_input.redo(&window),
+                            _ => panic!("internal error: unknown TextInput function {}", name),
+                        }
+                    } else {
+                        panic!("internal error: {} called on an element which is not a TextInput", name)
+                    }
+                    Value::Void
+                } else {
+                    panic!("internal error: argument to ItemMemberFunction must be an element")
+                }
+            }
             _ => panic!("call of something not a signal"),
         }
         Expression::SelfAssignment { lhs, rhs, op } => {
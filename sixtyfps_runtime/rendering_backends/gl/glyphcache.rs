@@ -14,6 +14,7 @@ use collections::hash_map::HashMap;
 use itertools::Itertools;
 use sixtyfps_corelib::font::Font;
 use sixtyfps_corelib::font::FontHandle;
+use sixtyfps_corelib::font::{FontRequest, ShapedGlyph, TextLine, ELLIPSIS};
 use std::cell::RefCell;
 use std::{collections, rc::Rc};
 
@@ -27,9 +28,8 @@ pub(crate) struct GlyphCache {
 }
 
 impl GlyphCache {
-    pub fn find_font(&self, font_family: &str, pixel_size: f32) -> Rc<RefCell<CachedFontGlyphs>> {
-        let font =
-            sixtyfps_corelib::font::FONT_CACHE.with(|fc| fc.find_font(font_family, pixel_size));
+    pub fn find_font(&self, font_request: &FontRequest) -> Rc<RefCell<CachedFontGlyphs>> {
+        let font = sixtyfps_corelib::font::FONT_CACHE.with(|fc| fc.find_font(font_request));
 
         let font_handle = font.handle();
 
@@ -56,14 +56,14 @@ impl GlyphCache {
 
 pub struct PreRenderedGlyph {
     pub glyph_allocation: Option<AtlasAllocation>,
-    pub advance: f32,
     pub x: f32,
     pub y: f32,
 }
 
 pub struct CachedFontGlyphs {
     pub font: Rc<Font>,
-    glyphs: HashMap<u32, PreRenderedGlyph>,
+    /// The glyphs are keyed by the font index (for fallback fonts) and the glyph id.
+    glyphs: HashMap<(usize, u32), PreRenderedGlyph>,
 }
 
 impl CachedFontGlyphs {
@@ -72,40 +72,29 @@ impl CachedFontGlyphs {
         Self { font, glyphs }
     }
 
-    pub fn layout_glyphs<'a>(
-        &'a mut self,
-        gl: &'a Rc<glow::Context>,
-        atlas: &'a mut TextureAtlas,
-        text: &'a str,
-    ) -> impl Iterator<Item = &PreRenderedGlyph> + 'a {
-        let glyphs = self
-            .font
-            .clone()
-            .string_to_glyphs(text)
-            .map(|(_, ch, glyph_id)| (ch, glyph_id))
-            .collect::<smallvec::SmallVec<[(_, _); 32]>>();
-
-        glyphs.iter().for_each(|(ch, glyph)| {
-            if !self.glyphs.contains_key(&glyph) {
-                // ensure the glyph is cached
-                self.glyphs.insert(*glyph, self.render_glyph(gl, atlas, *ch, *glyph));
-            }
-        });
-
-        GlyphIter { gl_font: self, glyph_it: glyphs.into_iter().map(|(_, g)| g) }
+    fn cached_glyph(
+        &mut self,
+        gl: &Rc<glow::Context>,
+        atlas: &mut TextureAtlas,
+        text: &str,
+        glyph: &ShapedGlyph,
+    ) -> &PreRenderedGlyph {
+        let font = &self.font;
+        self.glyphs.entry((glyph.font_index, glyph.glyph_id)).or_insert_with(|| {
+            let is_whitespace = text[glyph.cluster.clone()].trim().is_empty();
+            Self::render_glyph(font, gl, atlas, glyph, is_whitespace)
+        })
     }
 
     fn render_glyph(
-        &self,
+        font: &Font,
         gl: &Rc<glow::Context>,
         atlas: &mut TextureAtlas,
-        ch: char,
-        glyph_id: u32,
+        glyph: &ShapedGlyph,
+        is_whitespace: bool,
     ) -> PreRenderedGlyph {
-        let advance = self.font.glyph_metrics(glyph_id).advance;
-
-        let (x, y, glyph_allocation) = if !ch.is_whitespace() {
-            let (x, y, glyph_image) = self.font.rasterize_glyph(glyph_id);
+        let (x, y, glyph_allocation) = if !is_whitespace {
+            let (x, y, glyph_image) = font.rasterize_glyph(glyph.font_index, glyph.glyph_id);
 
             (
                 x,
@@ -126,7 +115,7 @@ impl CachedFontGlyphs {
             (0., 0., None)
         };
 
-        PreRenderedGlyph { glyph_allocation, advance, x, y }
+        PreRenderedGlyph { glyph_allocation, x, y }
     }
 
     pub fn render_glyphs(
@@ -134,35 +123,47 @@ impl CachedFontGlyphs {
         context: &Rc<glow::Context>,
         texture_atlas: &mut TextureAtlas,
         text: &str,
+        letter_spacing: f32,
+        lines: &[TextLine],
     ) -> Vec<GlyphRun> {
-        let mut x = 0.;
         let ascent = self.font.ascent();
+        let mut glyph_quads = Vec::new();
 
-        self.layout_glyphs(&context, texture_atlas, text)
-            .filter_map(|cached_glyph| {
-                let glyph_x = x;
-                x += cached_glyph.advance;
+        for line in lines {
+            let mut x = line.x;
+            let ellipsis = if line.elided { ELLIPSIS } else { "" };
 
-                if let Some(glyph_allocation) = &cached_glyph.glyph_allocation {
-                    let glyph_width = glyph_allocation.texture_coordinates.width() as f32;
-                    let glyph_height = glyph_allocation.texture_coordinates.height() as f32;
+            for run in [&text[line.start..line.end], ellipsis].iter() {
+                let shaped_text = self.font.shape_text(run, letter_spacing);
 
-                    let pen_x = glyph_x + cached_glyph.x;
-                    let pen_y = cached_glyph.y + ascent;
+                for glyph in &shaped_text.glyphs {
+                    let cached_glyph = self.cached_glyph(context, texture_atlas, run, glyph);
 
-                    let vertex1 = Vertex { _pos: [pen_x, pen_y] };
-                    let vertex2 = Vertex { _pos: [pen_x + glyph_width, pen_y] };
-                    let vertex3 = Vertex { _pos: [pen_x + glyph_width, pen_y + glyph_height] };
-                    let vertex4 = Vertex { _pos: [pen_x, pen_y + glyph_height] };
+                    if let Some(glyph_allocation) = &cached_glyph.glyph_allocation {
+                        let glyph_width = glyph_allocation.texture_coordinates.width() as f32;
+                        let glyph_height = glyph_allocation.texture_coordinates.height() as f32;
 
-                    let vertices = [vertex1, vertex2, vertex3, vertex1, vertex3, vertex4];
-                    let texture_vertices = glyph_allocation.normalized_texture_coordinates();
+                        let pen_x = x + glyph.x + glyph.x_offset + cached_glyph.x;
+                        let pen_y = line.y + ascent + cached_glyph.y - glyph.y_offset;
 
-                    Some((vertices, texture_vertices, glyph_allocation.clone()))
-                } else {
-                    None
+                        let vertex1 = Vertex { _pos: [pen_x, pen_y] };
+                        let vertex2 = Vertex { _pos: [pen_x + glyph_width, pen_y] };
+                        let vertex3 = Vertex { _pos: [pen_x + glyph_width, pen_y + glyph_height] };
+                        let vertex4 = Vertex { _pos: [pen_x, pen_y + glyph_height] };
+
+                        let vertices = [vertex1, vertex2, vertex3, vertex1, vertex3, vertex4];
+                        let texture_vertices = glyph_allocation.normalized_texture_coordinates();
+
+                        glyph_quads.push((vertices, texture_vertices, glyph_allocation.clone()));
+                    }
                 }
-            })
+
+                x += shaped_text.width;
+            }
+        }
+
+        glyph_quads
+            .into_iter()
             .group_by(|(_, _, allocation)| allocation.atlas.texture.clone())
             .into_iter()
             .map(|(texture, glyph_it)| {
@@ -188,22 +189,3 @@ impl CachedFontGlyphs {
             .collect()
     }
 }
-
-pub struct GlyphIter<'a, GlyphIterator> {
-    gl_font: &'a CachedFontGlyphs,
-    glyph_it: GlyphIterator,
-}
-
-impl<'a, GlyphIterator> Iterator for GlyphIter<'a, GlyphIterator>
-where
-    GlyphIterator: std::iter::Iterator<Item = u32>,
-{
-    type Item = &'a PreRenderedGlyph;
-    fn next(&mut self) -> Option<Self::Item> {
-        if let Some(glyph_id) = self.glyph_it.next() {
-            Some(&self.gl_font.glyphs[&glyph_id])
-        } else {
-            None
-        }
-    }
-}
@@ -16,11 +16,14 @@ use lyon::tessellation::{
 };
 use sixtyfps_corelib::eventloop::ComponentWindow;
 use sixtyfps_corelib::{
+    animated_image::{load_animated_image, AnimatedImage},
+    font::{FontRequest, TextLine},
     graphics::{
-        ARGBColor, Color, Frame as GraphicsFrame, GraphicsBackend, GraphicsWindow,
-        HighLevelRenderingPrimitive, Point, Rect, RenderingPrimitivesBuilder, RenderingVariable,
-        Resource, Size,
+        ARGBColor, BorderRadii, Color, Frame as GraphicsFrame, GraphicsBackend, GraphicsWindow,
+        HighLevelRenderingPrimitive, IntRect, IntSideOffsets, Point, Rect,
+        RenderingPrimitivesBuilder, RenderingVariable, Resource, Size,
     },
+    items::{BorderStyle, ImageFit},
     SharedArray,
 };
 use smallvec::{smallvec, SmallVec};
@@ -38,6 +41,8 @@ use shader::{GlyphShader, ImageShader, PathShader, RectShader};
 mod buffers;
 use buffers::{GLArrayBuffer, GLIndexBuffer};
 
+mod svg;
+
 #[cfg(not(target_arch = "wasm32"))]
 mod glyphcache;
 #[cfg(not(target_arch = "wasm32"))]
@@ -77,7 +82,17 @@ enum GLRenderingPrimitive {
         vertices: GLArrayBuffer<Vertex>,
         texture_vertices: GLArrayBuffer<Vertex>,
         texture: texture::AtlasAllocation,
-        image_size: Size,
+        /// The part of the allocation to render, in pixels relative to the allocation's origin.
+        source_rect: Rect,
+        image_fit: ImageFit,
+        nine_slice: IntSideOffsets,
+        /// The quads for the last target size, when the image is not simply stretched
+        fitted_quads: RefCell<Option<FittedImageQuads>>,
+    },
+    AnimatedTexture {
+        /// One Texture primitive per frame
+        frames: Vec<GLRenderingPrimitive>,
+        _animation: Rc<AnimatedImage>,
     },
     #[cfg(target_arch = "wasm32")]
     DynamicPrimitive {
@@ -98,6 +113,14 @@ enum GLRenderingPrimitive {
     },
 }
 
+/// The vertices of an image that is fitted or nine-sliced into a target size
+struct FittedImageQuads {
+    target_size: Size,
+    vertices: GLArrayBuffer<Vertex>,
+    texture_vertices: GLArrayBuffer<Vertex>,
+    vertex_count: i32,
+}
+
 struct NormalRectangle {
     vertices: GLArrayBuffer<Vertex>,
     indices: GLIndexBuffer<u16>,
@@ -408,14 +431,84 @@ impl RenderingPrimitivesBuilder for GLRenderingPrimitivesBuilder {
                     height,
                     border_width,
                     border_radius,
+                    border_style,
                 } => {
                     use lyon::math::Point;
 
                     let rect = Rect::new(Point::default(), Size::new(*width, *height));
-                    smallvec![self.fill_rectangle(&rect, *border_radius, *border_width)]
+                    match (border_radius.as_uniform(), border_style) {
+                        (Some(radius), BorderStyle::solid) => {
+                            smallvec![self.fill_rectangle(&rect, radius, *border_width)]
+                        }
+                        _ => self.fill_rectangle_path(
+                            &rect,
+                            border_radius,
+                            *border_width,
+                            *border_style,
+                        ),
+                    }
                 }
-                HighLevelRenderingPrimitive::Image { source } => {
+                HighLevelRenderingPrimitive::Image {
+                    source,
+                    source_clip_rect,
+                    image_fit,
+                    nine_slice,
+                    rasterization_size,
+                    scale_factor,
+                } => {
+                    if let Some(animation) = load_animated_image(source) {
+                        let mut atlas = self.texture_atlas.borrow_mut();
+                        let frames = animation
+                            .frames
+                            .iter()
+                            .map(|frame| {
+                                let image = image::ImageBuffer::<image::Rgba<u8>, &[u8]>::from_raw(
+                                    frame.image.width(),
+                                    frame.image.height(),
+                                    &frame.image,
+                                )
+                                .unwrap();
+                                GLRenderingPrimitivesBuilder::create_image(
+                                    &self.context,
+                                    &mut *atlas,
+                                    image,
+                                    source_clip_rect,
+                                    *image_fit,
+                                    *nine_slice,
+                                )
+                            })
+                            .collect();
+                        return OpaqueRenderingPrimitive {
+                            gl_primitives: smallvec![GLRenderingPrimitive::AnimatedTexture {
+                                frames,
+                                _animation: animation
+                            }],
+                        };
+                    }
                     match source {
+                        #[cfg(not(target_arch = "wasm32"))]
+                        Resource::AbsoluteFilePath(path) if source.is_svg() => {
+                            let mut image_path = std::env::current_exe().unwrap();
+                            image_path.pop(); // pop of executable name
+                            image_path.push(&*path.clone());
+                            let data = std::fs::read(image_path.as_path()).unwrap();
+                            self.create_svg_image(
+                                &data,
+                                *rasterization_size,
+                                *scale_factor,
+                                source_clip_rect,
+                                *image_fit,
+                                *nine_slice,
+                            )
+                        }
+                        Resource::EmbeddedData(slice) if source.is_svg() => self.create_svg_image(
+                            slice.as_slice(),
+                            *rasterization_size,
+                            *scale_factor,
+                            source_clip_rect,
+                            *image_fit,
+                            *nine_slice,
+                        ),
                         #[cfg(not(target_arch = "wasm32"))]
                         Resource::AbsoluteFilePath(path) => {
                             let mut image_path = std::env::current_exe().unwrap();
@@ -431,7 +524,10 @@ impl RenderingPrimitivesBuilder for GLRenderingPrimitivesBuilder {
                             smallvec![GLRenderingPrimitivesBuilder::create_image(
                                 &self.context,
                                 &mut *self.texture_atlas.borrow_mut(),
-                                image
+                                image,
+                                source_clip_rect,
+                                *image_fit,
+                                *nine_slice,
                             )]
                         }
                         #[cfg(target_arch = "wasm32")]
@@ -448,12 +544,18 @@ impl RenderingPrimitivesBuilder for GLRenderingPrimitivesBuilder {
                                     let shared_primitive = shared_primitive.clone();
                                     let window = self.window.clone();
                                     let event_loop_proxy = self.event_loop_proxy.clone();
+                                    let source_clip_rect = *source_clip_rect;
+                                    let image_fit = *image_fit;
+                                    let nine_slice = *nine_slice;
                                     move || {
                                         let texture_primitive =
                                             GLRenderingPrimitivesBuilder::create_image(
                                                 &context,
                                                 &mut *atlas.borrow_mut(),
                                                 &html_image,
+                                                &source_clip_rect,
+                                                image_fit,
+                                                nine_slice,
                                             );
 
                                         *shared_primitive.borrow_mut() = Some(texture_primitive);
@@ -486,7 +588,10 @@ impl RenderingPrimitivesBuilder for GLRenderingPrimitivesBuilder {
                             smallvec![GLRenderingPrimitivesBuilder::create_image(
                                 &self.context,
                                 &mut *self.texture_atlas.borrow_mut(),
-                                image
+                                image,
+                                source_clip_rect,
+                                *image_fit,
+                                *nine_slice,
                             )]
                         }
                         Resource::EmbeddedRgbaImage { width, height, data } => {
@@ -499,14 +604,22 @@ impl RenderingPrimitivesBuilder for GLRenderingPrimitivesBuilder {
                             smallvec![GLRenderingPrimitivesBuilder::create_image(
                                 &self.context,
                                 &mut *self.texture_atlas.borrow_mut(),
-                                image
+                                image,
+                                source_clip_rect,
+                                *image_fit,
+                                *nine_slice,
                             )]
                         }
                         Resource::None => SmallVec::new(),
                     }
                 }
-                HighLevelRenderingPrimitive::Text { text, font_family, font_size } => {
-                    smallvec![self.create_glyph_runs(text, font_family, *font_size)]
+                HighLevelRenderingPrimitive::Text { text, font_request, lines, clip } => {
+                    let mut primitives = SmallVec::new();
+                    if let Some(clip) = clip {
+                        primitives.push(self.create_clip(clip.width, clip.height));
+                    }
+                    primitives.push(self.create_glyph_runs(text, font_request, lines));
+                    primitives
                 }
                 HighLevelRenderingPrimitive::Path { width, height, elements, stroke_width } => {
                     let mut primitives = SmallVec::new();
@@ -521,15 +634,7 @@ impl RenderingPrimitivesBuilder for GLRenderingPrimitivesBuilder {
                     primitives
                 }
                 HighLevelRenderingPrimitive::ClipRect { width, height } => {
-                    use lyon::math::Point;
-
-                    let rect = Rect::new(Point::default(), Size::new(*width, *height));
-                    smallvec![match self.fill_rectangle(&rect, 0., 0.) {
-                        GLRenderingPrimitive::Rectangle { vertices, indices, radius: _, border_width: _, rect_size } => {
-                            GLRenderingPrimitive::ApplyClip{vertices: Rc::new(vertices), indices: Rc::new(indices), rect_size}
-                        }
-                        _ => panic!("internal error: unsupported clipping primitive returned by fill_rectangle")
-                    }]
+                    smallvec![self.create_clip(*width, *height)]
                 }
             },
         }
@@ -537,6 +642,28 @@ impl RenderingPrimitivesBuilder for GLRenderingPrimitivesBuilder {
 }
 
 impl GLRenderingPrimitivesBuilder {
+    fn create_clip(&self, width: f32, height: f32) -> GLRenderingPrimitive {
+        use lyon::math::Point;
+
+        let rect = Rect::new(Point::default(), Size::new(width, height));
+        match self.fill_rectangle(&rect, 0., 0.) {
+            GLRenderingPrimitive::Rectangle {
+                vertices,
+                indices,
+                radius: _,
+                border_width: _,
+                rect_size,
+            } => GLRenderingPrimitive::ApplyClip {
+                vertices: Rc::new(vertices),
+                indices: Rc::new(indices),
+                rect_size,
+            },
+            _ => {
+                panic!("internal error: unsupported clipping primitive returned by fill_rectangle")
+            }
+        }
+    }
+
     fn fill_path_from_geometry(
         &self,
         geometry: &VertexBuffers<Vertex, u16>,
@@ -624,14 +751,77 @@ impl GLRenderingPrimitivesBuilder {
         .into()
     }
 
+    /// Tessellates a rectangle with individual corner radii and a solid, dashed or dotted border.
+    /// This is the fallback for what the rectangle shader cannot render on its own.
+    fn fill_rectangle_path(
+        &mut self,
+        rect: &Rect,
+        radii: &BorderRadii,
+        border_width: f32,
+        border_style: BorderStyle,
+    ) -> GLRenderingPrimitives {
+        let mut primitives = SmallVec::new();
+
+        let fill_path = rounded_rectangle_path(rect, &radii.adjusted(0., rect.size));
+        primitives.extend(self.fill_path(fill_path.iter()).into_iter());
+
+        if border_width > 0. {
+            // The border is drawn inside of the rectangle, so the outline runs through the middle of it.
+            let half_width = border_width / 2.;
+            let outline_rect = rect.inflate(-half_width, -half_width);
+            let outline = rounded_rectangle_path(
+                &outline_rect,
+                &radii.adjusted(half_width, outline_rect.size),
+            );
+            let outline = match border_style {
+                BorderStyle::solid => outline,
+                BorderStyle::dashed => {
+                    dash_path(outline.iter(), border_width * 3., border_width * 2.)
+                }
+                BorderStyle::dotted => dash_path(outline.iter(), border_width, border_width),
+            };
+            primitives.extend(self.stroke_path(outline.iter(), border_width).into_iter());
+        }
+
+        primitives
+    }
+
+    fn create_svg_image(
+        &mut self,
+        data: &[u8],
+        rasterization_size: Size,
+        scale_factor: f32,
+        source_clip_rect: &IntRect,
+        image_fit: ImageFit,
+        nine_slice: IntSideOffsets,
+    ) -> GLRenderingPrimitives {
+        match svg::rasterize(data, rasterization_size, scale_factor, image_fit) {
+            Some(image) => smallvec![GLRenderingPrimitivesBuilder::create_image(
+                &self.context,
+                &mut *self.texture_atlas.borrow_mut(),
+                image,
+                source_clip_rect,
+                image_fit,
+                nine_slice,
+            )],
+            None => SmallVec::new(),
+        }
+    }
+
     fn create_image(
         context: &Rc<glow::Context>,
         atlas: &mut TextureAtlas,
         image: impl texture::UploadableAtlasImage,
+        source_clip_rect: &IntRect,
+        image_fit: ImageFit,
+        nine_slice: IntSideOffsets,
     ) -> GLRenderingPrimitive {
-        let image_size = Size::new(image.width() as _, image.height() as _);
-        let rect =
-            Rect::new(Point::new(0.0, 0.0), Size::new(image.width() as f32, image.height() as f32));
+        let source_rect = image_source_rect(
+            euclid::size2(image.width() as i32, image.height() as i32),
+            source_clip_rect,
+        );
+        let image_size = source_rect.size;
+        let rect = Rect::new(Point::new(0.0, 0.0), image_size);
 
         let vertex1 = Vertex { _pos: [rect.min_x(), rect.min_y()] };
         let vertex2 = Vertex { _pos: [rect.max_x(), rect.min_y()] };
@@ -644,14 +834,19 @@ impl GLRenderingPrimitivesBuilder {
             &context,
             &vec![vertex1, vertex2, vertex3, vertex1, vertex3, vertex4],
         );
-        let texture_vertices =
-            GLArrayBuffer::new(&context, &atlas_allocation.normalized_texture_coordinates());
+        let texture_vertices = GLArrayBuffer::new(
+            &context,
+            &atlas_allocation.normalized_texture_coordinates_for_rect(&source_rect),
+        );
 
         GLRenderingPrimitive::Texture {
             vertices,
             texture_vertices,
             texture: atlas_allocation,
-            image_size,
+            source_rect,
+            image_fit,
+            nine_slice,
+            fitted_quads: Default::default(),
         }
     }
 
@@ -659,13 +854,19 @@ impl GLRenderingPrimitivesBuilder {
     fn create_glyph_runs(
         &mut self,
         text: &str,
-        font_family: &str,
-        pixel_size: f32,
+        font_request: &FontRequest,
+        lines: &[TextLine],
     ) -> GLRenderingPrimitive {
-        let cached_glyphs = self.platform_data.glyph_cache.find_font(font_family, pixel_size);
+        let cached_glyphs = self.platform_data.glyph_cache.find_font(font_request);
         let mut cached_glyphs = cached_glyphs.borrow_mut();
         let mut atlas = self.texture_atlas.borrow_mut();
-        let glyphs_runs = cached_glyphs.render_glyphs(&self.context, &mut atlas, text);
+        let glyphs_runs = cached_glyphs.render_glyphs(
+            &self.context,
+            &mut atlas,
+            text,
+            font_request.letter_spacing,
+            lines,
+        );
         GLRenderingPrimitive::GlyphRuns { glyph_runs: glyphs_runs }
     }
 
@@ -673,46 +874,313 @@ impl GLRenderingPrimitivesBuilder {
     fn create_glyph_runs(
         &mut self,
         text: &str,
-        font_family: &str,
-        pixel_size: f32,
+        font_request: &FontRequest,
+        lines: &[TextLine],
     ) -> GLRenderingPrimitive {
-        let font =
-            sixtyfps_corelib::font::FONT_CACHE.with(|fc| fc.find_font(font_family, pixel_size));
-        let text_canvas = font.render_text(text);
+        let font = sixtyfps_corelib::font::FONT_CACHE.with(|fc| fc.find_font(font_request));
+
+        // The canvas renders one line at a time, so create one texture per line.
+        let glyph_runs = lines
+            .iter()
+            .map(|line| {
+                let mut line_text = text[line.start..line.end].to_owned();
+                if line.elided {
+                    line_text.push_str(sixtyfps_corelib::font::ELLIPSIS);
+                }
+                let text_canvas = font.render_text(&line_text, font_request.letter_spacing);
 
-        let texture = Rc::new(GLTexture::new_from_canvas(&self.context, &text_canvas));
+                let texture = Rc::new(GLTexture::new_from_canvas(&self.context, &text_canvas));
 
-        let rect = Rect::new(
-            Point::new(0.0, 0.0),
-            Size::new(text_canvas.width() as f32, text_canvas.height() as f32),
-        );
+                let rect = Rect::new(
+                    Point::new(line.x, line.y),
+                    Size::new(text_canvas.width() as f32, text_canvas.height() as f32),
+                );
 
-        let vertex1 = Vertex { _pos: [rect.min_x(), rect.min_y()] };
-        let vertex2 = Vertex { _pos: [rect.max_x(), rect.min_y()] };
-        let vertex3 = Vertex { _pos: [rect.max_x(), rect.max_y()] };
-        let vertex4 = Vertex { _pos: [rect.min_x(), rect.max_y()] };
+                let vertex1 = Vertex { _pos: [rect.min_x(), rect.min_y()] };
+                let vertex2 = Vertex { _pos: [rect.max_x(), rect.min_y()] };
+                let vertex3 = Vertex { _pos: [rect.max_x(), rect.max_y()] };
+                let vertex4 = Vertex { _pos: [rect.min_x(), rect.max_y()] };
 
-        let tex_vertex1 = Vertex { _pos: [0., 0.] };
-        let tex_vertex2 = Vertex { _pos: [1., 0.] };
-        let tex_vertex3 = Vertex { _pos: [1., 1.] };
-        let tex_vertex4 = Vertex { _pos: [0., 1.] };
+                let tex_vertex1 = Vertex { _pos: [0., 0.] };
+                let tex_vertex2 = Vertex { _pos: [1., 0.] };
+                let tex_vertex3 = Vertex { _pos: [1., 1.] };
+                let tex_vertex4 = Vertex { _pos: [0., 1.] };
 
-        let normalized_coordinates: [Vertex; 6] =
-            [tex_vertex1, tex_vertex2, tex_vertex3, tex_vertex1, tex_vertex3, tex_vertex4];
+                let normalized_coordinates: [Vertex; 6] =
+                    [tex_vertex1, tex_vertex2, tex_vertex3, tex_vertex1, tex_vertex3, tex_vertex4];
 
-        let vertices = GLArrayBuffer::new(
-            &self.context,
-            &vec![vertex1, vertex2, vertex3, vertex1, vertex3, vertex4],
-        );
-        let texture_vertices = GLArrayBuffer::new(&self.context, &normalized_coordinates);
-        let vertex_count = 6;
+                let vertices = GLArrayBuffer::new(
+                    &self.context,
+                    &vec![vertex1, vertex2, vertex3, vertex1, vertex3, vertex4],
+                );
+                let texture_vertices = GLArrayBuffer::new(&self.context, &normalized_coordinates);
+                let vertex_count = 6;
 
-        let glyph_runs = vec![GlyphRun { vertices, texture_vertices, texture, vertex_count }];
+                GlyphRun { vertices, texture_vertices, texture, vertex_count }
+            })
+            .collect();
 
         GLRenderingPrimitive::GlyphRuns { glyph_runs }
     }
 }
 
+/// Returns the two triangles covering the given rectangle, in the same order as the
+/// texture coordinates of [`texture::AtlasAllocation::normalized_texture_coordinates_for_rect`].
+fn rect_vertices(rect: &Rect) -> [Vertex; 6] {
+    let vertex1 = Vertex { _pos: [rect.min_x(), rect.min_y()] };
+    let vertex2 = Vertex { _pos: [rect.max_x(), rect.min_y()] };
+    let vertex3 = Vertex { _pos: [rect.max_x(), rect.max_y()] };
+    let vertex4 = Vertex { _pos: [rect.min_x(), rect.max_y()] };
+    [vertex1, vertex2, vertex3, vertex1, vertex3, vertex4]
+}
+
+/// Returns the part of an image of the given size that is shown: the whole image if the
+/// source_clip_rect is empty, and otherwise the part of the source_clip_rect within the image.
+fn image_source_rect(image_size: euclid::default::Size2D<i32>, source_clip_rect: &IntRect) -> Rect {
+    let full_rect = IntRect::new(Default::default(), image_size);
+    let source_rect = if source_clip_rect.is_empty() {
+        full_rect
+    } else {
+        source_clip_rect.intersection(&full_rect).unwrap_or_default()
+    };
+    source_rect.to_f32()
+}
+
+/// Computes how an image of the given size is mapped onto the target size. Returns a list
+/// of (target rectangle, image rectangle) pairs, where each image rectangle is in pixels of
+/// the image and is stretched to cover the corresponding target rectangle.
+fn image_quads(
+    image_size: Size,
+    target_size: Size,
+    image_fit: ImageFit,
+    nine_slice: IntSideOffsets,
+) -> Vec<(Rect, Rect)> {
+    if nine_slice != IntSideOffsets::zero() {
+        let insets = euclid::default::SideOffsets2D::new(
+            nine_slice.top as f32,
+            nine_slice.right as f32,
+            nine_slice.bottom as f32,
+            nine_slice.left as f32,
+        );
+        return nine_slice_quads(image_size, target_size, insets);
+    }
+
+    let (scale_x, scale_y) = match image_fit {
+        ImageFit::fill => {
+            (target_size.width / image_size.width, target_size.height / image_size.height)
+        }
+        ImageFit::contain => {
+            let scale =
+                (target_size.width / image_size.width).min(target_size.height / image_size.height);
+            (scale, scale)
+        }
+        ImageFit::cover => {
+            let scale =
+                (target_size.width / image_size.width).max(target_size.height / image_size.height);
+            (scale, scale)
+        }
+        ImageFit::none => (1., 1.),
+    };
+
+    // For each axis, returns the (target offset, target length, image offset, image length),
+    // centering the image and cropping what does not fit into the target.
+    let fit_axis = |image_length: f32, target_length: f32, scale: f32| {
+        let scaled_length = image_length * scale;
+        if scaled_length <= target_length {
+            ((target_length - scaled_length) / 2., scaled_length, 0., image_length)
+        } else {
+            let visible_length = target_length / scale;
+            (0., target_length, (image_length - visible_length) / 2., visible_length)
+        }
+    };
+
+    let (x, width, source_x, source_width) = fit_axis(image_size.width, target_size.width, scale_x);
+    let (y, height, source_y, source_height) =
+        fit_axis(image_size.height, target_size.height, scale_y);
+
+    vec![(
+        euclid::rect(x, y, width, height),
+        euclid::rect(source_x, source_y, source_width, source_height),
+    )]
+}
+
+/// Splits the image into nine parts along the given insets: the corners keep their size, the
+/// edges are stretched along their side and the center is stretched in both directions.
+fn nine_slice_quads(
+    image_size: Size,
+    target_size: Size,
+    insets: euclid::default::SideOffsets2D<f32>,
+) -> Vec<(Rect, Rect)> {
+    // Shrinks the corners proportionally if the target is too small to contain them.
+    let shrink = |start: f32, end: f32, length: f32| {
+        if start + end > length && start + end > 0. {
+            let factor = length / (start + end);
+            (start * factor, end * factor)
+        } else {
+            (start, end)
+        }
+    };
+    let (target_left, target_right) = shrink(insets.left, insets.right, target_size.width);
+    let (target_top, target_bottom) = shrink(insets.top, insets.bottom, target_size.height);
+
+    let image_columns = [
+        (0., insets.left),
+        (insets.left, image_size.width - insets.right),
+        (image_size.width - insets.right, image_size.width),
+    ];
+    let target_columns = [
+        (0., target_left),
+        (target_left, target_size.width - target_right),
+        (target_size.width - target_right, target_size.width),
+    ];
+    let image_rows = [
+        (0., insets.top),
+        (insets.top, image_size.height - insets.bottom),
+        (image_size.height - insets.bottom, image_size.height),
+    ];
+    let target_rows = [
+        (0., target_top),
+        (target_top, target_size.height - target_bottom),
+        (target_size.height - target_bottom, target_size.height),
+    ];
+
+    let mut quads = Vec::with_capacity(9);
+    for (image_row, target_row) in image_rows.iter().zip(target_rows.iter()) {
+        for (image_column, target_column) in image_columns.iter().zip(target_columns.iter()) {
+            let image_rect = Rect::new(
+                Point::new(image_column.0, image_row.0),
+                Size::new(image_column.1 - image_column.0, image_row.1 - image_row.0),
+            );
+            let target_rect = Rect::new(
+                Point::new(target_column.0, target_row.0),
+                Size::new(target_column.1 - target_column.0, target_row.1 - target_row.0),
+            );
+            if image_rect.size.width > 0.
+                && image_rect.size.height > 0.
+                && target_rect.size.width > 0.
+                && target_rect.size.height > 0.
+            {
+                quads.push((target_rect, image_rect));
+            }
+        }
+    }
+    quads
+}
+
+/// Builds the outline of the given rectangle, where each corner is rounded off with the given radius.
+fn rounded_rectangle_path(rect: &Rect, radii: &BorderRadii) -> lyon::path::Path {
+    use lyon::math::Point;
+    use lyon::path::builder::{Build, FlatPathBuilder, SvgBuilder};
+
+    // Distance of the control points for approximating a quarter circle with a cubic bezier curve
+    const KAPPA: f32 = 0.552_284_8;
+
+    let (left, top, right, bottom) = (rect.min_x(), rect.min_y(), rect.max_x(), rect.max_y());
+
+    let mut builder = lyon::path::Path::builder().with_svg();
+    builder.move_to(Point::new(left + radii.top_left, top));
+    builder.line_to(Point::new(right - radii.top_right, top));
+    let r = radii.top_right;
+    builder.cubic_bezier_to(
+        Point::new(right - r + r * KAPPA, top),
+        Point::new(right, top + r - r * KAPPA),
+        Point::new(right, top + r),
+    );
+    builder.line_to(Point::new(right, bottom - radii.bottom_right));
+    let r = radii.bottom_right;
+    builder.cubic_bezier_to(
+        Point::new(right, bottom - r + r * KAPPA),
+        Point::new(right - r + r * KAPPA, bottom),
+        Point::new(right - r, bottom),
+    );
+    builder.line_to(Point::new(left + radii.bottom_left, bottom));
+    let r = radii.bottom_left;
+    builder.cubic_bezier_to(
+        Point::new(left + r - r * KAPPA, bottom),
+        Point::new(left, bottom - r + r * KAPPA),
+        Point::new(left, bottom - r),
+    );
+    builder.line_to(Point::new(left, top + radii.top_left));
+    let r = radii.top_left;
+    builder.cubic_bezier_to(
+        Point::new(left, top + r - r * KAPPA),
+        Point::new(left + r - r * KAPPA, top),
+        Point::new(left + r, top),
+    );
+    builder.close();
+    builder.build()
+}
+
+/// Splits the outline described by the path events into dashes of `dash_length`, separated
+/// by gaps of `gap_length`. The resulting path consists of one open sub-path per dash.
+fn dash_path(
+    path: impl Iterator<Item = lyon::path::PathEvent>,
+    dash_length: f32,
+    gap_length: f32,
+) -> lyon::path::Path {
+    use lyon::math::Point;
+    use lyon::path::builder::{Build, FlatPathBuilder};
+    use lyon::path::iterator::PathIterator;
+    use lyon::path::PathEvent;
+
+    struct Dasher {
+        builder: lyon::path::path::Builder,
+        dash_length: f32,
+        period: f32,
+        /// The position within the current period of a dash and a gap, in `[0, period)`
+        phase: f32,
+        /// Whether the previous segment ended within a dash
+        in_dash: bool,
+    }
+
+    impl Dasher {
+        fn add_segment(&mut self, from: Point, to: Point) {
+            let length = (to - from).length();
+            if length <= 0. {
+                return;
+            }
+            // The dashes are computed from the start of the current period relative to the start
+            // of the segment, so that rounding errors do not accumulate along the segment.
+            let period_start = -self.phase;
+            let period_count = ((length - period_start) / self.period).ceil().max(1.) as usize;
+            let point_at = |pos: f32| from.lerp(to, pos / length);
+            for i in 0..period_count {
+                let start = period_start + i as f32 * self.period;
+                let dash_start = start.max(0.);
+                let dash_end = (start + self.dash_length).min(length);
+                if dash_end <= dash_start {
+                    continue;
+                }
+                if !(self.in_dash && start < 0.) {
+                    self.builder.move_to(point_at(dash_start));
+                }
+                self.builder.line_to(point_at(dash_end));
+            }
+            let phase = length - (period_start + (period_count - 1) as f32 * self.period);
+            self.phase = if phase > 0. && phase < self.period { phase } else { 0. };
+            self.in_dash = self.phase > 0. && self.phase < self.dash_length;
+        }
+    }
+
+    let mut dasher = Dasher {
+        builder: lyon::path::Path::builder(),
+        dash_length,
+        period: dash_length + gap_length,
+        phase: 0.,
+        in_dash: false,
+    };
+
+    for event in path.flattened(StrokeOptions::DEFAULT_TOLERANCE) {
+        match event {
+            PathEvent::Line { from, to } => dasher.add_segment(from, to),
+            PathEvent::End { last, first, close: true } => dasher.add_segment(last, first),
+            _ => {}
+        }
+    }
+
+    dasher.builder.build()
+}
+
 fn to_gl_matrix(matrix: &Matrix4<f32>) -> [f32; 16] {
     [
         matrix.x[0],
@@ -803,30 +1271,92 @@ impl GLFrame {
                 );
                 None
             }
-            GLRenderingPrimitive::Texture { vertices, texture_vertices, texture, image_size } => {
-                let matrix = if let Some(scaled_width) = rendering_var.next() {
-                    matrix
-                        * Matrix4::from_nonuniform_scale(
-                            scaled_width.as_scaled_width() / image_size.width,
-                            1.,
-                            1.,
-                        )
-                } else {
-                    matrix
+            GLRenderingPrimitive::AnimatedTexture { frames, .. } => {
+                let frame = match rendering_var.peek() {
+                    Some(RenderingVariable::ImageFrame(frame)) => *frame as usize,
+                    _ => 0,
                 };
+                frames.get(frame).and_then(|frame_primitive| {
+                    self.render_one_low_level_primitive(frame_primitive, rendering_var, matrix)
+                })
+            }
+            GLRenderingPrimitive::Texture {
+                vertices,
+                texture_vertices,
+                texture,
+                source_rect,
+                image_fit,
+                nine_slice,
+                fitted_quads,
+            } => {
+                let image_size = source_rect.size;
 
-                let matrix = if let Some(scaled_height) = rendering_var.next() {
-                    matrix
+                if let Some(RenderingVariable::ImageFrame(_)) = rendering_var.peek() {
+                    rendering_var.next();
+                }
+
+                let scaled_width = match rendering_var.peek() {
+                    Some(RenderingVariable::ScaledWidth(width)) => {
+                        rendering_var.next();
+                        *width
+                    }
+                    _ => image_size.width,
+                };
+                let scaled_height = match rendering_var.peek() {
+                    Some(RenderingVariable::ScaledHeight(height)) => {
+                        rendering_var.next();
+                        *height
+                    }
+                    _ => image_size.height,
+                };
+                let target_size = Size::new(scaled_width, scaled_height);
+                if image_size.is_empty() || target_size.is_empty() {
+                    // Nothing to draw, and the scale factors would be a division by zero
+                    return None;
+                }
+
+                if *image_fit == ImageFit::fill && *nine_slice == IntSideOffsets::zero() {
+                    let matrix = matrix
                         * Matrix4::from_nonuniform_scale(
+                            target_size.width / image_size.width,
+                            target_size.height / image_size.height,
                             1.,
-                            scaled_height.as_scaled_height() / image_size.height,
-                            1.,
-                        )
+                        );
+                    self.render_texture(&matrix, vertices, texture_vertices, texture, 6);
                 } else {
-                    matrix
-                };
-
-                self.render_texture(&matrix, vertices, texture_vertices, texture);
+                    let mut fitted_quads = fitted_quads.borrow_mut();
+                    if fitted_quads.as_ref().map_or(true, |q| q.target_size != target_size) {
+                        let mut quad_vertices = Vec::new();
+                        let mut quad_texture_vertices = Vec::new();
+                        for (target_rect, image_rect) in
+                            image_quads(image_size, target_size, *image_fit, *nine_slice)
+                        {
+                            quad_vertices.extend_from_slice(&rect_vertices(&target_rect));
+                            quad_texture_vertices.extend_from_slice(
+                                &texture.normalized_texture_coordinates_for_rect(
+                                    &image_rect.translate(source_rect.origin.to_vector()),
+                                ),
+                            );
+                        }
+                        *fitted_quads = Some(FittedImageQuads {
+                            target_size,
+                            vertices: GLArrayBuffer::new(&self.context, &quad_vertices),
+                            texture_vertices: GLArrayBuffer::new(
+                                &self.context,
+                                &quad_texture_vertices,
+                            ),
+                            vertex_count: quad_vertices.len() as i32,
+                        });
+                    }
+                    let quads = fitted_quads.as_ref().unwrap();
+                    self.render_texture(
+                        &matrix,
+                        &quads.vertices,
+                        &quads.texture_vertices,
+                        texture,
+                        quads.vertex_count,
+                    );
+                }
                 None
             }
             GLRenderingPrimitive::GlyphRuns { glyph_runs } => {
@@ -847,20 +1377,29 @@ impl GLFrame {
                 };
 
                 // Text selection is drawn in three phases:
-                // 1. Draw the selection background rectangle, use regular stencil testing, write into the stencil buffer with GL_INCR
+                // 1. Draw the selection background rectangles, use regular stencil testing, write into the stencil buffer with GL_INCR
                 // 2. Draw the glyphs, use regular stencil testing against current_stencil clip value + 1, don't write into the stencil buffer. This clips
                 //    and draws only the glyphs of the selected text.
                 // 3. Draw the glyphs, use regular stencil testing against current stencil clip value, don't write into the stencil buffer. This clips
                 //    away the selected text and draws the non-selected part.
-                // 4. We draw the selection background rectangle, use regular stencil testing, write into the stencil buffer with GL_DECR, use false color mask.
-                //    This "removes" the selection rectangle from the stencil buffer again.
+                // 4. We draw the selection background rectangles, use regular stencil testing, write into the stencil buffer with GL_DECR, use false color mask.
+                //    This "removes" the selection rectangles from the stencil buffer again.
+                // The selection rectangles of the different lines do not overlap.
 
-                let reset_stencil = match (rendering_var.peek(), &self.normal_rectangle) {
-                    (
-                        Some(RenderingVariable::TextSelection(x, width, height)),
-                        Some(text_cursor),
-                    ) => {
-                        rendering_var.next();
+                let mut selection_matrices = Vec::new();
+                while let Some(RenderingVariable::TextSelection(x, y, width, height)) =
+                    rendering_var.peek()
+                {
+                    selection_matrices.push(
+                        matrix
+                            * Matrix4::from_translation(cgmath::Vector3::new(*x, *y, 0.))
+                            * Matrix4::from_nonuniform_scale(*width, *height, 1.),
+                    );
+                    rendering_var.next();
+                }
+
+                let reset_stencil = match (selection_matrices.is_empty(), &self.normal_rectangle) {
+                    (false, Some(text_cursor)) => {
                         let foreground_color: ARGBColor<f32> =
                             (*rendering_var.next().unwrap().as_color()).into();
                         let background_color: ARGBColor<f32> =
@@ -868,21 +1407,19 @@ impl GLFrame {
 
                         // Phase 1
 
-                        let matrix = matrix
-                            * Matrix4::from_translation(cgmath::Vector3::new(*x, 0., 0.))
-                            * Matrix4::from_nonuniform_scale(*width, *height, 1.);
-
                         unsafe {
                             self.context.stencil_mask(0xff);
                             self.context.stencil_op(glow::KEEP, glow::KEEP, glow::INCR);
                         }
 
-                        self.fill_path(
-                            &matrix,
-                            &text_cursor.vertices,
-                            &text_cursor.indices,
-                            background_color,
-                        );
+                        for matrix in &selection_matrices {
+                            self.fill_path(
+                                matrix,
+                                &text_cursor.vertices,
+                                &text_cursor.indices,
+                                background_color,
+                            );
+                        }
 
                         unsafe {
                             self.context.stencil_mask(0);
@@ -909,18 +1446,22 @@ impl GLFrame {
                             );
                         }
 
-                        Some(matrix)
+                        true
+                    }
+                    (false, None) => {
+                        // Skip the selection colors
+                        rendering_var.next();
+                        rendering_var.next();
+                        false
                     }
-                    _ => None, // no stencil to reset
+                    _ => false, // no stencil to reset
                 };
 
                 // Phase 3
 
                 render_glyphs(col);
 
-                if let (Some(selection_matrix), Some(text_cursor)) =
-                    (reset_stencil, &self.normal_rectangle)
-                {
+                if let (true, Some(text_cursor)) = (reset_stencil, &self.normal_rectangle) {
                     // Phase 4
                     unsafe {
                         self.context.stencil_mask(0xff);
@@ -928,12 +1469,14 @@ impl GLFrame {
                         self.context.color_mask(false, false, false, false);
                     }
 
-                    self.fill_path(
-                        &selection_matrix,
-                        &text_cursor.vertices,
-                        &text_cursor.indices,
-                        col,
-                    );
+                    for selection_matrix in &selection_matrices {
+                        self.fill_path(
+                            selection_matrix,
+                            &text_cursor.vertices,
+                            &text_cursor.indices,
+                            col,
+                        );
+                    }
                     unsafe {
                         self.context.stencil_mask(0);
                         self.context.color_mask(true, true, true, true);
@@ -941,10 +1484,25 @@ impl GLFrame {
                     }
                 }
 
+                while let Some(RenderingVariable::TextUnderline(x, y, width, height)) =
+                    rendering_var.peek()
+                {
+                    if let Some(text_cursor) = &self.normal_rectangle {
+                        let matrix = matrix
+                            * Matrix4::from_translation(cgmath::Vector3::new(*x, *y, 0.))
+                            * Matrix4::from_nonuniform_scale(*width, *height, 1.);
+                        self.fill_path(&matrix, &text_cursor.vertices, &text_cursor.indices, col);
+                    }
+                    rendering_var.next();
+                }
+
                 match (rendering_var.peek(), &self.normal_rectangle) {
-                    (Some(RenderingVariable::TextCursor(x, width, height)), Some(text_cursor)) => {
+                    (
+                        Some(RenderingVariable::TextCursor(x, y, width, height)),
+                        Some(text_cursor),
+                    ) => {
                         let matrix = matrix
-                            * Matrix4::from_translation(cgmath::Vector3::new(*x, 0., 0.))
+                            * Matrix4::from_translation(cgmath::Vector3::new(*x, *y, 0.))
                             * Matrix4::from_nonuniform_scale(*width, *height, 1.);
 
                         self.fill_path(&matrix, &text_cursor.vertices, &text_cursor.indices, col);
@@ -1100,6 +1658,7 @@ impl GLFrame {
         vertices: &GLArrayBuffer<Vertex>,
         texture_vertices: &GLArrayBuffer<Vertex>,
         texture: &texture::AtlasAllocation,
+        vertex_count: i32,
     ) {
         self.image_shader.bind(
             &self.context,
@@ -1110,7 +1669,7 @@ impl GLFrame {
         );
 
         unsafe {
-            self.context.draw_arrays(glow::TRIANGLES, 0, 6);
+            self.context.draw_arrays(glow::TRIANGLES, 0, vertex_count);
         }
 
         self.image_shader.unbind(&self.context);
@@ -1170,3 +1729,188 @@ pub type NativeWidgets = ();
 pub type NativeGlobals = ();
 pub mod native_widgets {}
 pub const HAS_NATIVE_STYLE: bool = false;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lyon::path::builder::{Build, FlatPathBuilder};
+    use lyon::path::PathEvent;
+
+    /// The start and end point of each sub-path
+    fn sub_paths(path: &lyon::path::Path) -> Vec<(Point, Point)> {
+        path.iter()
+            .filter_map(|event| match event {
+                PathEvent::End { last, first, .. } => Some((first, last)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn assert_segment_eq(actual: (Point, Point), expected: (Point, Point)) {
+        assert!(
+            (actual.0 - expected.0).length() < 0.001 && (actual.1 - expected.1).length() < 0.001,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn dashes_on_a_line() {
+        let mut builder = lyon::path::Path::builder();
+        builder.move_to(Point::new(0., 0.));
+        builder.line_to(Point::new(100., 0.));
+        builder.line_to(Point::new(100., 100.));
+        builder.close();
+        let dashes = sub_paths(&dash_path(builder.build().iter(), 3., 2.));
+        assert_segment_eq(dashes[0], (Point::new(0., 0.), Point::new(3., 0.)));
+        assert_segment_eq(dashes[1], (Point::new(5., 0.), Point::new(8., 0.)));
+        // The gap ends exactly at the corner, so the next dash starts on the next side
+        assert_segment_eq(dashes[20], (Point::new(100., 0.), Point::new(100., 3.)));
+        // The closing segment has a length of 100 * sqrt(2)
+        assert_eq!(dashes.len(), 20 + 20 + 29);
+    }
+
+    #[test]
+    fn dashes_on_a_long_border() {
+        // Large coordinates, where the rounding errors are bigger than f32::EPSILON
+        let rect = Rect::new(Point::new(1000., 1000.), Size::new(3000., 2000.));
+        let outline = rounded_rectangle_path(&rect, &BorderRadii::uniform(50.));
+        let dashes = sub_paths(&dash_path(outline.iter(), 0.3, 0.2));
+        let perimeter = 2. * (3000. + 2000.) - (8. - 2. * std::f32::consts::PI) * 50.;
+        assert!((dashes.len() as f32 - perimeter / 0.5).abs() < 10.);
+    }
+
+    #[test]
+    fn rounded_corners() {
+        let rect = Rect::new(Point::new(0., 0.), Size::new(100., 50.));
+        let radii =
+            BorderRadii { top_left: 10., top_right: 0., bottom_right: 20., bottom_left: 0. };
+        let points: Vec<Point> = rounded_rectangle_path(&rect, &radii)
+            .iter()
+            .filter_map(|event| match event {
+                PathEvent::Line { to, .. } | PathEvent::Cubic { to, .. } => Some(to),
+                _ => None,
+            })
+            .collect();
+        // The square corners are part of the outline, the rounded ones are not
+        assert!(points.contains(&Point::new(100., 0.)));
+        assert!(points.contains(&Point::new(0., 50.)));
+        assert!(!points.contains(&Point::new(0., 0.)));
+        assert!(!points.contains(&Point::new(100., 50.)));
+        assert!(points.contains(&Point::new(0., 10.)));
+        assert!(points.contains(&Point::new(100., 30.)));
+        assert!(points.contains(&Point::new(80., 50.)));
+    }
+
+    #[test]
+    fn image_source_clip_rect() {
+        let image_size = euclid::size2(40, 30);
+        assert_eq!(image_source_rect(image_size, &IntRect::zero()), euclid::rect(0., 0., 40., 30.));
+        assert_eq!(
+            image_source_rect(image_size, &euclid::rect(10, 5, 10, 10)),
+            euclid::rect(10., 5., 10., 10.)
+        );
+        // The part of the clip rect outside of the image is ignored
+        assert_eq!(
+            image_source_rect(image_size, &euclid::rect(30, 20, 50, 50)),
+            euclid::rect(30., 20., 10., 10.)
+        );
+        assert!(image_source_rect(image_size, &euclid::rect(50, 0, 10, 10)).is_empty());
+    }
+
+    #[test]
+    fn image_fit_contain() {
+        let quads = image_quads(
+            Size::new(100., 50.),
+            Size::new(200., 200.),
+            ImageFit::contain,
+            IntSideOffsets::zero(),
+        );
+        // Scaled by 2 and centered vertically
+        assert_eq!(
+            quads,
+            vec![(euclid::rect(0., 50., 200., 100.), euclid::rect(0., 0., 100., 50.))]
+        );
+    }
+
+    #[test]
+    fn image_fit_cover() {
+        let quads = image_quads(
+            Size::new(100., 50.),
+            Size::new(200., 200.),
+            ImageFit::cover,
+            IntSideOffsets::zero(),
+        );
+        // Scaled by 4, so only the middle 50 pixels of the width are visible
+        assert_eq!(
+            quads,
+            vec![(euclid::rect(0., 0., 200., 200.), euclid::rect(25., 0., 50., 50.))]
+        );
+    }
+
+    #[test]
+    fn image_fit_fill() {
+        let quads = image_quads(
+            Size::new(100., 50.),
+            Size::new(50., 200.),
+            ImageFit::fill,
+            IntSideOffsets::zero(),
+        );
+        // Stretched differently in both directions
+        assert_eq!(quads, vec![(euclid::rect(0., 0., 50., 200.), euclid::rect(0., 0., 100., 50.))]);
+    }
+
+    #[test]
+    fn image_fit_none() {
+        let quads = image_quads(
+            Size::new(100., 50.),
+            Size::new(50., 100.),
+            ImageFit::none,
+            IntSideOffsets::zero(),
+        );
+        // Cropped horizontally and centered vertically
+        assert_eq!(quads, vec![(euclid::rect(0., 25., 50., 50.), euclid::rect(25., 0., 50., 50.))]);
+    }
+
+    #[test]
+    fn nine_slice() {
+        let quads = image_quads(
+            Size::new(30., 30.),
+            Size::new(60., 80.),
+            ImageFit::fill,
+            IntSideOffsets::new(10, 10, 10, 10),
+        );
+        assert_eq!(quads.len(), 9);
+        // The corners keep their size
+        assert_eq!(quads[0], (euclid::rect(0., 0., 10., 10.), euclid::rect(0., 0., 10., 10.)));
+        assert_eq!(quads[8], (euclid::rect(50., 70., 10., 10.), euclid::rect(20., 20., 10., 10.)));
+        // The edges are stretched along their side and the center in both directions
+        assert_eq!(quads[1], (euclid::rect(10., 0., 40., 10.), euclid::rect(10., 0., 10., 10.)));
+        assert_eq!(quads[3], (euclid::rect(0., 10., 10., 60.), euclid::rect(0., 10., 10., 10.)));
+        assert_eq!(quads[4], (euclid::rect(10., 10., 40., 60.), euclid::rect(10., 10., 10., 10.)));
+    }
+
+    #[test]
+    fn nine_slice_borders_larger_than_target() {
+        let quads = image_quads(
+            Size::new(30., 30.),
+            Size::new(15., 40.),
+            ImageFit::fill,
+            IntSideOffsets::new(10, 10, 10, 10),
+        );
+        // The left and right borders are shrunk to fit the width, leaving no room for the
+        // middle column
+        assert_eq!(
+            quads,
+            vec![
+                (euclid::rect(0., 0., 7.5, 10.), euclid::rect(0., 0., 10., 10.)),
+                (euclid::rect(7.5, 0., 7.5, 10.), euclid::rect(20., 0., 10., 10.)),
+                (euclid::rect(0., 10., 7.5, 20.), euclid::rect(0., 10., 10., 10.)),
+                (euclid::rect(7.5, 10., 7.5, 20.), euclid::rect(20., 10., 10., 10.)),
+                (euclid::rect(0., 30., 7.5, 10.), euclid::rect(0., 20., 10., 10.)),
+                (euclid::rect(7.5, 30., 7.5, 10.), euclid::rect(20., 20., 10., 10.)),
+            ]
+        );
+    }
+}
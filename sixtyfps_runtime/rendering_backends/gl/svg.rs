@@ -0,0 +1,88 @@
+/* LICENSE BEGIN
+    This file is part of the SixtyFPS Project -- https://sixtyfps.io
+    Copyright (c) 2020 Olivier Goffart <olivier.goffart@sixtyfps.io>
+    Copyright (c) 2020 Simon Hausmann <simon.hausmann@sixtyfps.io>
+
+    SPDX-License-Identifier: GPL-3.0-only
+    This file is also available under commercial licensing terms.
+    Please contact info@sixtyfps.io for more information.
+LICENSE END */
+/*!
+This module rasterizes SVG documents into bitmaps that can be uploaded into the texture atlas.
+*/
+
+use sixtyfps_corelib::graphics::Size;
+use sixtyfps_corelib::items::ImageFit;
+
+/// Rasterizes the SVG document in `data` so that it covers the `size` (in physical pixels) according
+/// to the `image_fit`. If one of the dimensions of the size is zero, the natural size of the document
+/// multiplied by the `scale_factor` is used instead. Returns None if the document cannot be parsed.
+pub fn rasterize(
+    data: &[u8],
+    size: Size,
+    scale_factor: f32,
+    image_fit: ImageFit,
+) -> Option<image::RgbaImage> {
+    let tree = usvg::Tree::from_data(data, &usvg::Options::default()).ok()?;
+    let natural_size = tree.svg_node().size;
+    let natural_width = natural_size.width() as f32;
+    let natural_height = natural_size.height() as f32;
+
+    let scale = if size.width > 0. && size.height > 0. {
+        let horizontal_scale = size.width / natural_width;
+        let vertical_scale = size.height / natural_height;
+        match image_fit {
+            ImageFit::cover | ImageFit::fill => horizontal_scale.max(vertical_scale),
+            ImageFit::contain => horizontal_scale.min(vertical_scale),
+            ImageFit::none => scale_factor,
+        }
+    } else {
+        scale_factor
+    };
+
+    let width = (natural_width * scale).ceil().max(1.) as u32;
+    let height = (natural_height * scale).ceil().max(1.) as u32;
+
+    let mut pixmap = tiny_skia::Pixmap::new(width, height)?;
+    resvg::render(&tree, usvg::FitTo::Size(width, height), pixmap.as_mut())?;
+
+    // The texture atlas expects straight alpha, like the images decoded by the image crate.
+    let mut data = Vec::with_capacity(pixmap.pixels().len() * 4);
+    for pixel in pixmap.pixels() {
+        let color = pixel.demultiply();
+        data.extend_from_slice(&[color.red(), color.green(), color.blue(), color.alpha()]);
+    }
+
+    image::RgbaImage::from_raw(width, height, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A red document of 20x10 pixels
+    const SVG: &[u8] = br#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="10">
+        <rect width="20" height="10" fill="red"/>
+    </svg>"#;
+
+    #[test]
+    fn rasterize_at_two_sizes() {
+        let image = rasterize(SVG, Size::new(40., 20.), 1., ImageFit::fill).unwrap();
+        assert_eq!(image.dimensions(), (40, 20));
+        assert_eq!(image.get_pixel(20, 10).0, [0xff, 0, 0, 0xff]);
+
+        let image = rasterize(SVG, Size::new(100., 100.), 1., ImageFit::contain).unwrap();
+        assert_eq!(image.dimensions(), (100, 50));
+    }
+
+    #[test]
+    fn rasterize_at_natural_size() {
+        let image = rasterize(SVG, Size::zero(), 2., ImageFit::fill).unwrap();
+        assert_eq!(image.dimensions(), (40, 20));
+    }
+
+    #[test]
+    fn rasterize_invalid_document() {
+        assert!(rasterize(b"<svg", Size::new(10., 10.), 1., ImageFit::fill).is_none());
+    }
+}
@@ -203,16 +203,28 @@ impl Drop for AtlasAllocation {
 
 impl AtlasAllocation {
     pub(crate) fn normalized_texture_coordinates(&self) -> [Vertex; 6] {
+        let size = self.texture_coordinates.size();
+        self.normalized_texture_coordinates_for_rect(&sixtyfps_corelib::graphics::Rect::new(
+            Default::default(),
+            sixtyfps_corelib::graphics::Size::new(size.x() as f32, size.y() as f32),
+        ))
+    }
+
+    /// Returns the texture coordinates of the given sub-rectangle of the allocation, which is
+    /// specified in pixels relative to the origin of the allocation.
+    pub(crate) fn normalized_texture_coordinates_for_rect(
+        &self,
+        rect: &sixtyfps_corelib::graphics::Rect,
+    ) -> [Vertex; 6] {
         let atlas_width = self.atlas.texture.width as f32;
         let atlas_height = self.atlas.texture.height as f32;
-        let origin = self.texture_coordinates.origin();
-        let size = self.texture_coordinates.size();
-        let texture_coordinates = RectI::new(origin, size);
+        let origin_x = self.texture_coordinates.origin_x() as f32;
+        let origin_y = self.texture_coordinates.origin_y() as f32;
 
-        let tex_left = ((texture_coordinates.min_x() as f32) + 0.5) / atlas_width;
-        let tex_top = ((texture_coordinates.min_y() as f32) + 0.5) / atlas_height;
-        let tex_right = ((texture_coordinates.max_x() as f32) - 0.5) / atlas_width;
-        let tex_bottom = ((texture_coordinates.max_y() as f32) - 0.5) / atlas_height;
+        let tex_left = (origin_x + rect.min_x() + 0.5) / atlas_width;
+        let tex_top = (origin_y + rect.min_y() + 0.5) / atlas_height;
+        let tex_right = (origin_x + rect.max_x() - 0.5) / atlas_width;
+        let tex_bottom = (origin_y + rect.max_y() - 0.5) / atlas_height;
 
         let tex_vertex1 = Vertex { _pos: [tex_left, tex_top] };
         let tex_vertex2 = Vertex { _pos: [tex_right, tex_top] };
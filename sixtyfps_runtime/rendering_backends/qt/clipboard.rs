@@ -0,0 +1,51 @@
+/* LICENSE BEGIN
+    This file is part of the SixtyFPS Project -- https://sixtyfps.io
+    Copyright (c) 2020 Olivier Goffart <olivier.goffart@sixtyfps.io>
+    Copyright (c) 2020 Simon Hausmann <simon.hausmann@sixtyfps.io>
+
+    SPDX-License-Identifier: GPL-3.0-only
+    This file is also available under commercial licensing terms.
+    Please contact info@sixtyfps.io for more information.
+LICENSE END */
+/*!
+    Gives access to the clipboard through QClipboard.
+*/
+
+use cpp::cpp;
+use sixtyfps_corelib::clipboard::Clipboard;
+
+cpp! {{
+    #include <QtGui/QClipboard>
+    #include <QtGui/QGuiApplication>
+    #include <QtCore/QMimeData>
+
+    void ensure_initialized();
+}}
+
+/// The clipboard of the system, as provided by Qt.
+pub struct QtClipboard;
+
+impl Clipboard for QtClipboard {
+    fn set_text(&self, text: &str) {
+        let text: crate::qttypes::QString = text.into();
+        cpp!(unsafe [text as "QString"] {
+            ensure_initialized();
+            QGuiApplication::clipboard()->setText(text);
+        });
+    }
+
+    fn text(&self) -> Option<String> {
+        let mut has_text = false;
+        let text = cpp!(unsafe [mut has_text as "bool"] -> crate::qttypes::QString as "QString" {
+            ensure_initialized();
+            auto mime_data = QGuiApplication::clipboard()->mimeData();
+            has_text = mime_data && mime_data->hasText();
+            return has_text ? mime_data->text() : QString();
+        });
+        if has_text {
+            Some(text.into())
+        } else {
+            None
+        }
+    }
+}
@@ -13,11 +13,22 @@ LICENSE END */
 mod qttypes;
 #[cfg(not(no_qt))]
 mod widgets;
+#[cfg(not(no_qt))]
+mod clipboard;
 
 // FIXME: right now, we are just re-exposing the GL backend, but eventually, we want the Qt
 // backend to use QPainter to draw directly on the window.
 pub use sixtyfps_rendering_backend_gl::*;
 
+/// Creates a window rendered by the GL backend, which copies to and pastes from the clipboard
+/// through Qt.
+pub fn create_gl_window() -> sixtyfps_corelib::eventloop::ComponentWindow {
+    let window = sixtyfps_rendering_backend_gl::create_gl_window();
+    #[cfg(not(no_qt))]
+    window.set_clipboard(std::rc::Rc::new(clipboard::QtClipboard));
+    window
+}
+
 #[doc(hidden)]
 #[cold]
 pub fn use_modules() -> usize {
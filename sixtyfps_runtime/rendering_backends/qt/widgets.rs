@@ -80,6 +80,17 @@ impl QImageWrapArray {
         drop(self.img);
         Resource::EmbeddedRgbaImage { width: size.width, height: size.height, data: self.array }
     }
+
+    pub fn to_rendering_primitive(self) -> HighLevelRenderingPrimitive {
+        HighLevelRenderingPrimitive::Image {
+            source: self.to_resource(),
+            source_clip_rect: Default::default(),
+            image_fit: Default::default(),
+            nine_slice: Default::default(),
+            rasterization_size: Default::default(),
+            scale_factor: 1.,
+        }
+    }
 }
 
 cpp! {{
@@ -163,7 +174,7 @@ impl Item for NativeButton {
                 option.state |= QStyle::State_Enabled;
             qApp->style()->drawControl(QStyle::CE_PushButton, &option, &p, nullptr);
         });
-        return HighLevelRenderingPrimitive::Image { source: imgarray.to_resource() };
+        return imgarray.to_rendering_primitive();
     }
 
     fn rendering_variables(
@@ -189,6 +200,8 @@ impl Item for NativeButton {
         LayoutInfo {
             min_width: size.width as f32,
             min_height: size.height as f32,
+            preferred_width: size.width as f32,
+            preferred_height: size.height as f32,
             ..LayoutInfo::default()
         }
     }
@@ -293,7 +306,7 @@ impl Item for NativeCheckBox {
                 option.state |= QStyle::State_Enabled;
             qApp->style()->drawControl(QStyle::CE_CheckBox, &option, &p, nullptr);
         });
-        return HighLevelRenderingPrimitive::Image { source: imgarray.to_resource() };
+        return imgarray.to_rendering_primitive();
     }
 
     fn rendering_variables(
@@ -319,6 +332,8 @@ impl Item for NativeCheckBox {
         LayoutInfo {
             min_width: size.width as f32,
             min_height: size.height as f32,
+            preferred_width: size.width as f32,
+            preferred_height: size.height as f32,
             max_height: size.height as f32,
             horizontal_stretch: 1.,
             ..LayoutInfo::default()
@@ -442,7 +457,7 @@ impl Item for NativeSpinBox {
             auto text_rect = style->subControlRect(QStyle::CC_SpinBox, &option, QStyle::SC_SpinBoxEditField, nullptr);
             p.drawText(text_rect, QString::number(value));
         });
-        return HighLevelRenderingPrimitive::Image { source: imgarray.to_resource() };
+        return imgarray.to_rendering_primitive();
     }
 
     fn rendering_variables(
@@ -480,6 +495,8 @@ impl Item for NativeSpinBox {
         LayoutInfo {
             min_width: size.width as f32,
             min_height: size.height as f32,
+            preferred_width: size.width as f32,
+            preferred_height: size.height as f32,
             max_height: size.height as f32,
             horizontal_stretch: 1.,
             ..LayoutInfo::default()
@@ -648,7 +665,7 @@ impl Item for NativeSlider {
             auto style = qApp->style();
             style->drawComplexControl(QStyle::CC_Slider, &option, &p, nullptr);
         });
-        return HighLevelRenderingPrimitive::Image { source: imgarray.to_resource() };
+        return imgarray.to_rendering_primitive();
     }
 
     fn rendering_variables(
@@ -687,6 +704,8 @@ impl Item for NativeSlider {
         LayoutInfo {
             min_width: size.width as f32,
             min_height: size.height as f32,
+            preferred_width: size.width as f32,
+            preferred_height: size.height as f32,
             max_height: size.height as f32,
             horizontal_stretch: 1.,
             ..LayoutInfo::default()
@@ -927,7 +946,7 @@ impl Item for NativeGroupBox {
                 QStyle::SH_GroupBox_TextLabelColor, &option));
             qApp->style()->drawComplexControl(QStyle::CC_GroupBox, &option, &p, nullptr);
         });
-        return HighLevelRenderingPrimitive::Image { source: imgarray.to_resource() };
+        return imgarray.to_rendering_primitive();
     }
 
     fn rendering_variables(
@@ -1080,7 +1099,7 @@ impl Item for NativeLineEdit {
                 option.state |= QStyle::State_Enabled;
             qApp->style()->drawPrimitive(QStyle::PE_PanelLineEdit, &option, &p, nullptr);
         });
-        return HighLevelRenderingPrimitive::Image { source: imgarray.to_resource() };
+        return imgarray.to_rendering_primitive();
     }
 
     fn rendering_variables(
@@ -1330,7 +1349,7 @@ impl Item for NativeScrollView {
             data.pressed == 1,
         );
 
-        return HighLevelRenderingPrimitive::Image { source: imgarray.to_resource() };
+        return imgarray.to_rendering_primitive();
     }
 
     fn rendering_variables(
@@ -1577,7 +1596,7 @@ impl Item for NativeStandardListViewItem {
             qApp->style()->drawPrimitive(QStyle::PE_PanelItemViewRow, &option, &p, nullptr);
             qApp->style()->drawControl(QStyle::CE_ItemViewItem, &option, &p, nullptr);
         });
-        return HighLevelRenderingPrimitive::Image { source: imgarray.to_resource() };
+        return imgarray.to_rendering_primitive();
     }
 
     fn rendering_variables(
@@ -1614,6 +1633,8 @@ impl Item for NativeStandardListViewItem {
         let result = LayoutInfo {
             min_width: s.width as f32 * dpr,
             min_height: s.height as f32 * dpr,
+            preferred_width: s.width as f32 * dpr,
+            preferred_height: s.height as f32 * dpr,
             ..LayoutInfo::default()
         };
         result
@@ -1708,7 +1729,7 @@ impl Item for NativeComboBox {
             qApp->style()->drawComplexControl(QStyle::CC_ComboBox, &option, &p, nullptr);
             qApp->style()->drawControl(QStyle::CE_ComboBoxLabel, &option, &p, nullptr);
         });
-        return HighLevelRenderingPrimitive::Image { source: imgarray.to_resource() };
+        return imgarray.to_rendering_primitive();
     }
 
     fn rendering_variables(
@@ -1736,6 +1757,8 @@ impl Item for NativeComboBox {
         LayoutInfo {
             min_width: size.width as f32,
             min_height: size.height as f32,
+            preferred_width: size.width as f32,
+            preferred_height: size.height as f32,
             ..LayoutInfo::default()
         }
     }
@@ -56,8 +56,14 @@ fn gen_corelib(include_dir: &Path) -> anyhow::Result<()> {
         "EasingCurve",
         "TextHorizontalAlignment",
         "TextVerticalAlignment",
+        "TextWrap",
+        "TextOverflow",
+        "InputType",
+        "BorderStyle",
+        "ImageFit",
         "Window",
         "TextInput",
+        "StackPage",
     ]
     .iter()
     .map(|x| x.to_string())
@@ -203,6 +209,16 @@ fn gen_corelib(include_dir: &Path) -> anyhow::Result<()> {
         .body
         .insert("Flickable".to_owned(), "    inline Flickable(); inline ~Flickable();".into());
     config.export.pre_body.insert("FlickableDataBox".to_owned(), "struct FlickableData;".into());
+    config.export.body.insert("Image".to_owned(), "    inline Image(); inline ~Image();".into());
+    config
+        .export
+        .pre_body
+        .insert("AnimatedImageDataBox".to_owned(), "struct AnimatedImageData;".into());
+    config
+        .export
+        .body
+        .insert("TextInput".to_owned(), "    inline TextInput(); inline ~TextInput();".into());
+    config.export.pre_body.insert("TextInputDataBox".to_owned(), "struct TextInputData;".into());
     config.export.include.push("StandardListViewItem".into());
     cbindgen::Builder::new()
         .with_config(config)